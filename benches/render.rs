@@ -0,0 +1,164 @@
+//! Benchmarks for the per-frame hot paths: coloring, effects and building
+//! the frame string, on banners the size figlet draws.
+//!
+//! Record a baseline before a performance change and compare against it
+//! after:
+//!
+//! ```text
+//! cargo bench --bench render -- --save-baseline before
+//! cargo bench --bench render -- --baseline before
+//! ```
+//!
+//! Baseline, as medians on a single-core Linux VM; timings on other
+//! machines will differ, so compare against a baseline saved locally:
+//!
+//! ```text
+//! benchmark                        55x6      220x20
+//! gradient/horizontal             19 µs      163 µs
+//! gradient/diagonal               21 µs      177 µs
+//! effect_apply/typewriter         35 µs      494 µs
+//! effect_apply/matrix-rain        44 µs      529 µs
+//! effect_apply/scale-up           11 µs       87 µs
+//! frame/render_static             19 µs      155 µs
+//! ```
+//!
+//! When these benchmarks were added, gradient/horizontal took 50 µs and
+//! 370 µs, and frame/render_static 35 µs and 548 µs.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use piglet::{
+    animation::{effects::get_effect, effects::EffectContext, AnimationEngine},
+    color::{
+        apply::write_gradient_to_text, Attributes, ColorDepth, ColorEngine, GradientDirection,
+    },
+    utils::ascii::AsciiArt,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// The system allocator, counting every allocation it makes
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Number of allocations (and reallocations) made while running `f`
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// `piglet "Hello, World!"` in figlet's standard font
+const HELLO_WORLD: &str = r" _   _      _ _         __        __         _     _ _ 
+| | | | ___| | | ___    \ \      / /__  _ __| | __| | |
+| |_| |/ _ \ | |/ _ \    \ \ /\ / / _ \| '__| |/ _` | |
+|  _  |  __/ | | (_) |    \ V  V / (_) | |  | | (_| |_|
+|_| |_|\___|_|_|\___( )    \_/\_/ \___/|_|  |_|\__,_(_)
+                    |/                                 ";
+
+/// A one-line banner, and a long three-line one filling a wide terminal
+fn banners() -> Vec<(&'static str, String)> {
+    let wide_line: Vec<String> = HELLO_WORLD.lines().map(|line| line.repeat(4)).collect();
+    let wide_line = AsciiArt::new(wide_line.join("\n"));
+    let stacked = AsciiArt::stack(&[wide_line.clone(), wide_line.clone(), wide_line], 1);
+    vec![
+        ("55x6", HELLO_WORLD.to_string()),
+        ("220x20", stacked.render()),
+    ]
+}
+
+fn gradient_engine() -> ColorEngine {
+    ColorEngine::new()
+        .with_gradient(Some("linear-gradient(90deg, #ff5733, #33ff57, #3357ff)"))
+        .unwrap()
+}
+
+/// Gradients written into one buffer reused from frame to frame, as the
+/// render loop does; allocations per call are printed next to the timings
+fn bench_gradient(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gradient");
+    let engine = gradient_engine();
+    for (size, art) in banners() {
+        let colors = engine.get_colors(AsciiArt::new(art.clone()).width());
+        for (name, direction) in [
+            ("horizontal", GradientDirection::Horizontal),
+            ("diagonal", GradientDirection::Diagonal),
+        ] {
+            let write = |out: &mut String| {
+                out.clear();
+                write_gradient_to_text(
+                    out,
+                    black_box(&art),
+                    &colors,
+                    direction,
+                    ColorDepth::TrueColor,
+                    Attributes::default(),
+                    false,
+                )
+            };
+
+            let mut buffer = String::new();
+            write(&mut buffer);
+            let allocations = count_allocations(|| write(&mut buffer));
+            println!("gradient/{name}/{size}: {allocations} allocations per call");
+
+            group.bench_function(BenchmarkId::new(name, size), |b| {
+                b.iter(|| write(&mut buffer))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_effects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("effect_apply");
+    for (size, art) in banners() {
+        let art = AsciiArt::new(art);
+        for name in ["typewriter", "matrix-rain", "scale-up"] {
+            let effect = get_effect(name).unwrap();
+            let ctx = EffectContext::new(0.5).with_frame(45, 90);
+            group.bench_with_input(BenchmarkId::new(name, size), &art, |b, art| {
+                b.iter(|| effect.apply(black_box(art), &ctx))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame");
+    for (size, art) in banners() {
+        let engine = AnimationEngine::new(art, Duration::from_secs(3), 30)
+            .with_effect("fade-in")
+            .unwrap()
+            .with_color_engine(gradient_engine());
+        // A fresh renderer every time, so nothing comes from the frame cache
+        group.bench_function(BenchmarkId::new("render_static", size), |b| {
+            b.iter(|| engine.render_static())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_gradient, bench_effects, bench_frame);
+criterion_main!(benches);
@@ -1,5 +1,9 @@
+use crate::utils::suggest;
 use anyhow::{bail, Result};
 
+/// Easing used when neither the user nor the effect picks one
+pub const DEFAULT_EASING: &str = "ease-in-out";
+
 pub trait EasingFunction: Send + Sync {
     fn ease(&self, t: f64) -> f64;
     #[allow(dead_code)]
@@ -292,6 +296,371 @@ impl EasingFunction for EaseInOutBounce {
     }
 }
 
+// Custom curves
+/// CSS `cubic-bezier(x1, y1, x2, y2)` timing curve
+pub struct CubicBezier {
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    name: String,
+}
+
+impl CubicBezier {
+    pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Self {
+            x1,
+            y1,
+            x2,
+            y2,
+            name: format!("cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2),
+        }
+    }
+
+    /// Parse `cubic-bezier(x1, y1, x2, y2)`; x values must lie in [0, 1]
+    pub fn parse(spec: &str) -> Result<Self> {
+        let args = function_args(spec, "cubic-bezier")
+            .ok_or_else(|| anyhow::anyhow!("Invalid cubic-bezier syntax: {}", spec))?;
+        let values = args
+            .iter()
+            .map(|a| a.parse::<f64>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| anyhow::anyhow!("Invalid cubic-bezier number in: {}", spec))?;
+
+        let [x1, y1, x2, y2] = values[..] else {
+            bail!("cubic-bezier takes 4 numbers: {}", spec);
+        };
+        if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+            bail!("cubic-bezier x values must be between 0 and 1: {}", spec);
+        }
+
+        Ok(Self::new(x1, y1, x2, y2))
+    }
+
+    fn sample(a1: f64, a2: f64, t: f64) -> f64 {
+        // Bezier with endpoints fixed at 0 and 1
+        let u = 1.0 - t;
+        3.0 * u * u * t * a1 + 3.0 * u * t * t * a2 + t * t * t
+    }
+
+    fn sample_derivative(a1: f64, a2: f64, t: f64) -> f64 {
+        let u = 1.0 - t;
+        3.0 * u * u * a1 + 6.0 * u * t * (a2 - a1) + 3.0 * t * t * (1.0 - a2)
+    }
+
+    /// Find the curve parameter whose x equals `x`
+    fn solve_t(&self, x: f64) -> f64 {
+        let mut t = x;
+        for _ in 0..8 {
+            let error = Self::sample(self.x1, self.x2, t) - x;
+            if error.abs() < 1e-7 {
+                return t;
+            }
+            let slope = Self::sample_derivative(self.x1, self.x2, t);
+            if slope.abs() < 1e-6 {
+                break;
+            }
+            t -= error / slope;
+        }
+
+        // Newton stalled on a flat section, fall back to bisection
+        let (mut low, mut high) = (0.0, 1.0);
+        t = x;
+        for _ in 0..50 {
+            let sample = Self::sample(self.x1, self.x2, t);
+            if (sample - x).abs() < 1e-7 {
+                break;
+            }
+            if sample < x {
+                low = t;
+            } else {
+                high = t;
+            }
+            t = (low + high) / 2.0;
+        }
+        t
+    }
+}
+
+impl EasingFunction for CubicBezier {
+    fn ease(&self, t: f64) -> f64 {
+        if t <= 0.0 {
+            return 0.0;
+        }
+        if t >= 1.0 {
+            return 1.0;
+        }
+        Self::sample(self.y1, self.y2, self.solve_t(t))
+    }
+    #[allow(dead_code)]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Where the jumps of a `steps()` staircase fall, as in CSS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepPosition {
+    Start,
+    End,
+    Neither,
+    Both,
+}
+
+/// CSS `steps(n, <position>)` staircase easing
+pub struct Steps {
+    count: u32,
+    position: StepPosition,
+    name: String,
+}
+
+impl Steps {
+    pub fn new(count: u32, position: StepPosition) -> Self {
+        let keyword = match position {
+            StepPosition::Start => "jump-start",
+            StepPosition::End => "jump-end",
+            StepPosition::Neither => "jump-none",
+            StepPosition::Both => "jump-both",
+        };
+        Self {
+            count,
+            position,
+            name: format!("steps({}, {})", count, keyword),
+        }
+    }
+
+    /// Parse `steps(n)` or `steps(n, jump-start|jump-end|jump-none|jump-both)`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let args = function_args(spec, "steps")
+            .ok_or_else(|| anyhow::anyhow!("Invalid steps syntax: {}", spec))?;
+
+        let count: u32 = args[0]
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid step count in: {}", spec))?;
+
+        let position = match args.get(1).copied() {
+            None | Some("jump-end") | Some("end") => StepPosition::End,
+            Some("jump-start") | Some("start") => StepPosition::Start,
+            Some("jump-none") => StepPosition::Neither,
+            Some("jump-both") => StepPosition::Both,
+            Some(other) => bail!("Unknown steps position: {}", other),
+        };
+
+        if args.len() > 2 {
+            bail!("steps takes at most 2 arguments: {}", spec);
+        }
+        let min_count = if position == StepPosition::Neither {
+            2
+        } else {
+            1
+        };
+        if count < min_count {
+            bail!("steps count must be at least {}: {}", min_count, spec);
+        }
+
+        Ok(Self::new(count, position))
+    }
+}
+
+impl EasingFunction for Steps {
+    fn ease(&self, t: f64) -> f64 {
+        let n = self.count as f64;
+        let step = (t.clamp(0.0, 1.0) * n).floor();
+        let value = match self.position {
+            StepPosition::Start => (step + 1.0) / n,
+            StepPosition::End => step / n,
+            StepPosition::Neither => step / (n - 1.0),
+            StepPosition::Both => (step + 1.0) / (n + 1.0),
+        };
+        value.clamp(0.0, 1.0)
+    }
+    #[allow(dead_code)]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Damped spring pulled from 0 toward 1, like the springs of popular
+/// animation libraries. The motion is played until it has settled and then
+/// nudged so `ease(1.0)` lands exactly on 1.
+pub struct Spring {
+    stiffness: f64,
+    damping: f64,
+    /// Simulated time covered by t = 0..1
+    settle_time: f64,
+    name: String,
+}
+
+impl Spring {
+    pub const DEFAULT_STIFFNESS: f64 = 170.0;
+    pub const DEFAULT_DAMPING: f64 = 26.0;
+    /// Remaining amplitude at which the spring counts as settled
+    const SETTLE_THRESHOLD: f64 = 1e-4;
+
+    pub fn new(stiffness: f64, damping: f64) -> Self {
+        let mut spring = Self {
+            stiffness,
+            damping,
+            settle_time: 1.0,
+            name: format!("spring({}, {})", stiffness, damping),
+        };
+        spring.settle_time = -Self::SETTLE_THRESHOLD.ln() / spring.decay_rate();
+        spring
+    }
+
+    /// Parse `spring` or `spring(stiffness, damping)`; both must be positive
+    pub fn parse(spec: &str) -> Result<Self> {
+        if spec.trim() == "spring" {
+            return Ok(Self::new(Self::DEFAULT_STIFFNESS, Self::DEFAULT_DAMPING));
+        }
+
+        let args = function_args(spec, "spring")
+            .ok_or_else(|| anyhow::anyhow!("Invalid spring syntax: {}", spec))?;
+        let values = args
+            .iter()
+            .map(|a| a.parse::<f64>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| anyhow::anyhow!("Invalid spring number in: {}", spec))?;
+
+        let [stiffness, damping] = values[..] else {
+            bail!("spring takes 2 numbers (stiffness, damping): {}", spec);
+        };
+        if !(stiffness > 0.0 && damping > 0.0) {
+            bail!("spring stiffness and damping must be positive: {}", spec);
+        }
+
+        Ok(Self::new(stiffness, damping))
+    }
+
+    fn natural_frequency(&self) -> f64 {
+        self.stiffness.sqrt()
+    }
+
+    fn damping_ratio(&self) -> f64 {
+        self.damping / (2.0 * self.natural_frequency())
+    }
+
+    /// Rate at which the slowest part of the motion dies out
+    fn decay_rate(&self) -> f64 {
+        let omega = self.natural_frequency();
+        let zeta = self.damping_ratio();
+        if zeta <= 1.0 {
+            zeta * omega
+        } else {
+            omega * (zeta - (zeta * zeta - 1.0).sqrt())
+        }
+    }
+
+    /// Position at `time` of a unit-mass spring released from rest at 0
+    fn position(&self, time: f64) -> f64 {
+        let omega = self.natural_frequency();
+        let zeta = self.damping_ratio();
+
+        if (zeta - 1.0).abs() < 1e-9 {
+            // Critically damped
+            1.0 - (-omega * time).exp() * (1.0 + omega * time)
+        } else if zeta < 1.0 {
+            let damped = omega * (1.0 - zeta * zeta).sqrt();
+            let envelope = (-zeta * omega * time).exp();
+            1.0 - envelope * ((damped * time).cos() + zeta * omega / damped * (damped * time).sin())
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            1.0 + (r2 * (r1 * time).exp() - r1 * (r2 * time).exp()) / (r1 - r2)
+        }
+    }
+}
+
+impl EasingFunction for Spring {
+    fn ease(&self, t: f64) -> f64 {
+        if t <= 0.0 {
+            return 0.0;
+        }
+        if t >= 1.0 {
+            return 1.0;
+        }
+        // Spread what is left of the settling error over the whole curve
+        let residual = 1.0 - self.position(self.settle_time);
+        self.position(t * self.settle_time) + residual * t
+    }
+    #[allow(dead_code)]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Plays another easing forward over the first half and backward over the
+/// second, reaching 1 at the midpoint
+pub struct Mirror {
+    inner: Box<dyn EasingFunction>,
+    name: String,
+}
+
+impl Mirror {
+    /// How deeply `mirror(...)` may be nested
+    const MAX_DEPTH: usize = 4;
+
+    pub fn new(inner: Box<dyn EasingFunction>) -> Self {
+        let name = format!("mirror({})", inner.name());
+        Self { inner, name }
+    }
+
+    /// Parse `mirror(<easing>)`, resolving the inner easing by name
+    pub fn parse(spec: &str) -> Result<Self> {
+        Self::parse_nested(spec, 0)
+    }
+
+    fn parse_nested(spec: &str, depth: usize) -> Result<Self> {
+        if depth >= Self::MAX_DEPTH {
+            bail!("mirror() is nested too deeply: {}", spec);
+        }
+
+        let inner = spec
+            .trim()
+            .strip_prefix("mirror")
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+            .map(str::trim)
+            .filter(|inner| !inner.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Invalid mirror syntax: {}", spec))?;
+
+        let inner: Box<dyn EasingFunction> = if inner.starts_with("mirror") {
+            Box::new(Self::parse_nested(inner, depth + 1)?)
+        } else {
+            get_easing_function(inner)?
+        };
+        Ok(Self::new(inner))
+    }
+}
+
+impl EasingFunction for Mirror {
+    fn ease(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        if t <= 0.5 {
+            self.inner.ease(t * 2.0)
+        } else {
+            self.inner.ease((1.0 - t) * 2.0)
+        }
+    }
+    #[allow(dead_code)]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Arguments of a `name(a, b, ...)` call, or `None` if `spec` is not one
+fn function_args<'a>(spec: &'a str, function: &str) -> Option<Vec<&'a str>> {
+    let inner = spec
+        .trim()
+        .strip_prefix(function)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    Some(inner.split(',').map(|a| a.trim()).collect())
+}
+
 pub fn get_easing_function(name: &str) -> Result<Box<dyn EasingFunction>> {
     match name {
         "linear" => Ok(Box::new(Linear)),
@@ -313,6 +682,154 @@ pub fn get_easing_function(name: &str) -> Result<Box<dyn EasingFunction>> {
         "ease-in-bounce" => Ok(Box::new(EaseInBounce)),
         "ease-out-bounce" => Ok(Box::new(EaseOutBounce)),
         "ease-in-out-bounce" => Ok(Box::new(EaseInOutBounce)),
-        _ => bail!("Unknown easing function: {}", name),
+        _ if name.starts_with("cubic-bezier") => Ok(Box::new(CubicBezier::parse(name)?)),
+        _ if name.starts_with("steps") => Ok(Box::new(Steps::parse(name)?)),
+        _ if name.starts_with("spring") => Ok(Box::new(Spring::parse(name)?)),
+        _ if name.starts_with("mirror") => Ok(Box::new(Mirror::parse(name)?)),
+        _ => bail!(
+            "Unknown easing function '{}'{}",
+            name,
+            suggest::did_you_mean(name, list_easing_functions())
+        ),
+    }
+}
+
+/// List the named easing functions (cubic-bezier and steps take arguments,
+/// spring optionally does)
+pub fn list_easing_functions() -> Vec<&'static str> {
+    vec![
+        "linear",
+        "ease-in",
+        "ease-out",
+        "ease-in-out",
+        "ease-in-quad",
+        "ease-out-quad",
+        "ease-in-out-quad",
+        "ease-in-cubic",
+        "ease-out-cubic",
+        "ease-in-out-cubic",
+        "ease-in-back",
+        "ease-out-back",
+        "ease-in-out-back",
+        "ease-in-elastic",
+        "ease-out-elastic",
+        "ease-in-out-elastic",
+        "ease-in-bounce",
+        "ease-out-bounce",
+        "ease-in-out-bounce",
+        "spring",
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cubic_bezier_linear() {
+        let easing = get_easing_function("cubic-bezier(0, 0, 1, 1)").unwrap();
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            assert!((easing.ease(t) - t).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_cubic_bezier_endpoints() {
+        let easing = CubicBezier::parse("cubic-bezier(0.68, -0.55, 0.27, 1.55)").unwrap();
+        assert_eq!(easing.ease(0.0), 0.0);
+        assert_eq!(easing.ease(1.0), 1.0);
+        assert!(easing.ease(0.1) < 0.0);
+    }
+
+    #[test]
+    fn test_cubic_bezier_invalid() {
+        assert!(get_easing_function("cubic-bezier(0, 0, 1)").is_err());
+        assert!(get_easing_function("cubic-bezier(2, 0, 1, 1)").is_err());
+        assert!(get_easing_function("cubic-bezier(a, 0, 1, 1)").is_err());
+    }
+
+    #[test]
+    fn test_steps_jump_end() {
+        let easing = get_easing_function("steps(4, jump-end)").unwrap();
+        assert_eq!(easing.ease(0.0), 0.0);
+        assert_eq!(easing.ease(0.26), 0.25);
+        assert_eq!(easing.ease(0.99), 0.75);
+        assert_eq!(easing.ease(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_steps_jump_start() {
+        let easing = get_easing_function("steps(4, jump-start)").unwrap();
+        assert_eq!(easing.ease(0.0), 0.25);
+        assert_eq!(easing.ease(0.26), 0.5);
+        assert_eq!(easing.ease(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_steps_invalid() {
+        assert!(get_easing_function("steps(0)").is_err());
+        assert!(get_easing_function("steps(1, jump-none)").is_err());
+        assert!(get_easing_function("steps(4, sideways)").is_err());
+    }
+
+    #[test]
+    fn test_spring_endpoints() {
+        for spec in [
+            "spring",
+            "spring(170, 26)",
+            "spring(100, 5)",
+            "spring(50, 40)",
+        ] {
+            let easing = get_easing_function(spec).unwrap();
+            assert_eq!(easing.ease(0.0), 0.0, "{spec}");
+            assert_eq!(easing.ease(1.0), 1.0, "{spec}");
+            assert_eq!(easing.ease(-0.5), 0.0, "{spec}");
+            assert_eq!(easing.ease(1.5), 1.0, "{spec}");
+            assert!((easing.ease(0.999) - 1.0).abs() < 0.01, "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_underdamped_spring_overshoots() {
+        let easing = get_easing_function("spring(170, 10)").unwrap();
+        let peak = (1..100)
+            .map(|i| easing.ease(i as f64 / 100.0))
+            .fold(f64::MIN, f64::max);
+        assert!(peak > 1.05, "peak {peak}");
+
+        // An overdamped spring creeps up without overshooting
+        let easing = get_easing_function("spring(50, 40)").unwrap();
+        assert!((1..100).all(|i| easing.ease(i as f64 / 100.0) <= 1.0));
+    }
+
+    #[test]
+    fn test_spring_rejects_bad_arguments() {
+        assert!(get_easing_function("spring(170)").is_err());
+        assert!(get_easing_function("spring(0, 10)").is_err());
+        assert!(get_easing_function("spring(170, -1)").is_err());
+        assert!(get_easing_function("spring(a, b)").is_err());
+    }
+
+    #[test]
+    fn test_mirror_peaks_at_midpoint() {
+        let easing = get_easing_function("mirror(ease-in)").unwrap();
+        assert_eq!(easing.ease(0.0), 0.0);
+        assert_eq!(easing.ease(0.5), 1.0);
+        assert_eq!(easing.ease(1.0), 0.0);
+        for i in 0..=50 {
+            let t = i as f64 / 100.0;
+            assert!((easing.ease(t) - easing.ease(1.0 - t)).abs() < 1e-9);
+        }
+        assert!((easing.ease(0.25) - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mirror_nesting() {
+        assert!(get_easing_function("mirror(cubic-bezier(0.4, 0, 0.2, 1))").is_ok());
+        assert!(get_easing_function("mirror(mirror(linear))").is_ok());
+        assert!(get_easing_function("mirror(mirror(mirror(mirror(mirror(linear)))))").is_err());
+        assert!(get_easing_function("mirror()").is_err());
+        assert!(get_easing_function("mirror(nope)").is_err());
     }
 }
@@ -1,9 +1,28 @@
-use crate::utils::ascii::AsciiArt;
+use crate::utils::{
+    ascii::{is_blank_grapheme, AsciiArt},
+    suggest,
+};
 use anyhow::{bail, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub trait Effect: Send + Sync {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult;
     fn name(&self) -> &str;
+
+    /// Easing to use when none was chosen explicitly, for effects that
+    /// already bake their own motion curve
+    fn preferred_easing(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the output also moves with the frame index or elapsed time
+    /// (oscillations, blinking, a `--frequency`) rather than with progress
+    /// alone. Frames of effects that don't are shared between nearby
+    /// progress values.
+    fn varies_with_time(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -43,12 +62,100 @@ impl EffectResult {
     }
 }
 
-// Fade effects
+/// Seed used when none is given, so random effects replay the same way
+pub const DEFAULT_SEED: u64 = 0x5EED;
+
+/// Terminal size assumed when rendering without a terminal
+pub const DEFAULT_TERM_SIZE: (u16, u16) = (80, 24);
+
+/// Everything an effect knows about the frame it is drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectContext {
+    /// Eased progress through the animation, 0.0 to 1.0
+    pub progress: f64,
+    /// Index of the frame being rendered
+    pub frame: usize,
+    /// Frames in the whole animation
+    pub total_frames: usize,
+    pub term_width: u16,
+    pub term_height: u16,
+    /// Seed for effects that use randomness
+    pub seed: u64,
+    /// Frames per second, to turn frame indices into time
+    pub fps: u32,
+    /// Oscillation rate in Hz overriding an effect's own, if given
+    pub frequency: Option<f64>,
+}
+
+impl EffectContext {
+    pub fn new(progress: f64) -> Self {
+        Self {
+            progress,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_frame(mut self, frame: usize, total_frames: usize) -> Self {
+        self.frame = frame;
+        self.total_frames = total_frames;
+        self
+    }
+
+    pub fn with_term_size(mut self, width: u16, height: u16) -> Self {
+        self.term_width = width;
+        self.term_height = height;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_fps(mut self, fps: u32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: Option<f64>) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Seconds since the animation started
+    pub fn elapsed_secs(&self) -> f64 {
+        self.frame as f64 / self.fps.max(1) as f64
+    }
+
+    /// Angle in radians of an oscillation running at the chosen frequency,
+    /// or `default_hz`, so its speed doesn't depend on the duration
+    pub fn phase(&self, default_hz: f64) -> f64 {
+        let hz = self.frequency.unwrap_or(default_hz);
+        self.elapsed_secs() * hz * std::f64::consts::PI * 2.0
+    }
+}
+
+impl Default for EffectContext {
+    fn default() -> Self {
+        Self {
+            progress: 0.0,
+            frame: 0,
+            total_frames: 0,
+            term_width: DEFAULT_TERM_SIZE.0,
+            term_height: DEFAULT_TERM_SIZE.1,
+            seed: DEFAULT_SEED,
+            fps: 30,
+            frequency: None,
+        }
+    }
+}
+
+// Fade effects: the renderer turns `opacity` into dimmed colors, or into
+// lighter glyphs when there are no colors
 pub struct FadeIn;
 impl Effect for FadeIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let text = ascii_art.apply_fade(progress);
-        EffectResult::new(text).with_opacity(progress)
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        EffectResult::new(ascii_art.render()).with_opacity(ctx.progress)
     }
 
     fn name(&self) -> &str {
@@ -58,10 +165,9 @@ impl Effect for FadeIn {
 
 pub struct FadeOut;
 impl Effect for FadeOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let opacity = 1.0 - progress;
-        let text = ascii_art.apply_fade(opacity);
-        EffectResult::new(text).with_opacity(opacity)
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let opacity = 1.0 - ctx.progress;
+        EffectResult::new(ascii_art.render()).with_opacity(opacity)
     }
 
     fn name(&self) -> &str {
@@ -71,14 +177,14 @@ impl Effect for FadeOut {
 
 pub struct FadeInOut;
 impl Effect for FadeInOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         let opacity = if progress < 0.5 {
             progress * 2.0
         } else {
             (1.0 - progress) * 2.0
         };
-        let text = ascii_art.apply_fade(opacity);
-        EffectResult::new(text).with_opacity(opacity)
+        EffectResult::new(ascii_art.render()).with_opacity(opacity)
     }
 
     fn name(&self) -> &str {
@@ -89,8 +195,8 @@ impl Effect for FadeInOut {
 // Slide effects
 pub struct SlideInTop;
 impl Effect for SlideInTop {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_y = ((1.0 - progress) * -(ascii_art.height() as f64)) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_y = ((1.0 - ctx.progress) * -(ascii_art.height() as f64)) as i32;
         EffectResult::new(ascii_art.render()).with_offset(0, offset_y)
     }
 
@@ -101,8 +207,8 @@ impl Effect for SlideInTop {
 
 pub struct SlideInBottom;
 impl Effect for SlideInBottom {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_y = ((1.0 - progress) * ascii_art.height() as f64) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_y = ((1.0 - ctx.progress) * ascii_art.height() as f64) as i32;
         EffectResult::new(ascii_art.render()).with_offset(0, offset_y)
     }
 
@@ -113,8 +219,8 @@ impl Effect for SlideInBottom {
 
 pub struct SlideInLeft;
 impl Effect for SlideInLeft {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_x = ((1.0 - progress) * -(ascii_art.width() as f64)) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_x = ((1.0 - ctx.progress) * -(ascii_art.width() as f64)) as i32;
         EffectResult::new(ascii_art.render()).with_offset(offset_x, 0)
     }
 
@@ -125,8 +231,8 @@ impl Effect for SlideInLeft {
 
 pub struct SlideInRight;
 impl Effect for SlideInRight {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_x = ((1.0 - progress) * ascii_art.width() as f64) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_x = ((1.0 - ctx.progress) * ascii_art.width() as f64) as i32;
         EffectResult::new(ascii_art.render()).with_offset(offset_x, 0)
     }
 
@@ -138,9 +244,9 @@ impl Effect for SlideInRight {
 // Scale effects
 pub struct ScaleUp;
 impl Effect for ScaleUp {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let scale = progress;
-        let scaled = ascii_art.scale(scale);
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let scale = ctx.progress;
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render()).with_scale(scale)
     }
 
@@ -151,9 +257,9 @@ impl Effect for ScaleUp {
 
 pub struct ScaleDown;
 impl Effect for ScaleDown {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let scale = 2.0 - progress;
-        let scaled = ascii_art.scale(scale);
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let scale = 2.0 - ctx.progress;
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render()).with_scale(scale)
     }
 
@@ -165,9 +271,9 @@ impl Effect for ScaleDown {
 // Pulse effect
 pub struct Pulse;
 impl Effect for Pulse {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let scale = 1.0 + (progress * std::f64::consts::PI * 2.0).sin() * 0.1;
-        let scaled = ascii_art.scale(scale);
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let scale = 1.0 + (ctx.progress * std::f64::consts::PI * 2.0).sin() * 0.1;
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render()).with_scale(scale)
     }
 
@@ -179,7 +285,8 @@ impl Effect for Pulse {
 // Bounce effects
 pub struct BounceIn;
 impl Effect for BounceIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         let offset_y = if progress < 0.8 {
             ((1.0 - progress / 0.8) * -(ascii_art.height() as f64)) as i32
         } else {
@@ -192,11 +299,16 @@ impl Effect for BounceIn {
     fn name(&self) -> &str {
         "bounce-in"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 pub struct BounceOut;
 impl Effect for BounceOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         let offset_y = if progress < 0.2 {
             -(progress * 10.0 * (1.0 - progress / 0.2)) as i32
         } else {
@@ -208,39 +320,20 @@ impl Effect for BounceOut {
     fn name(&self) -> &str {
         "bounce-out"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 // Typewriter effect
 pub struct Typewriter;
 impl Effect for Typewriter {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         let total_chars = ascii_art.char_count();
-        let visible_chars = (total_chars as f64 * progress) as usize;
-
-        let positions = ascii_art.char_positions();
-        let lines = ascii_art.get_lines();
-        let mut result_lines: Vec<String> = lines
-            .iter()
-            .map(|l| {
-                l.chars()
-                    .map(|c| if c.is_whitespace() { c } else { ' ' })
-                    .collect()
-            })
-            .collect();
+        let visible_chars = (total_chars as f64 * ctx.progress) as usize;
 
-        for (i, (x, y, ch)) in positions.iter().enumerate() {
-            if i < visible_chars {
-                if let Some(line) = result_lines.get_mut(*y) {
-                    let mut chars: Vec<char> = line.chars().collect();
-                    if *x < chars.len() {
-                        chars[*x] = *ch;
-                        *line = chars.iter().collect();
-                    }
-                }
-            }
-        }
-
-        EffectResult::new(result_lines.join("\n"))
+        EffectResult::new(reveal_first(ascii_art, visible_chars))
     }
 
     fn name(&self) -> &str {
@@ -250,52 +343,67 @@ impl Effect for Typewriter {
 
 pub struct TypewriterReverse;
 impl Effect for TypewriterReverse {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         let total_chars = ascii_art.char_count();
-        let visible_chars = (total_chars as f64 * (1.0 - progress)) as usize;
+        let visible_chars = (total_chars as f64 * (1.0 - ctx.progress)) as usize;
 
-        let positions = ascii_art.char_positions();
-        let lines = ascii_art.get_lines();
-        let mut result_lines: Vec<String> = lines
-            .iter()
-            .map(|l| {
-                l.chars()
-                    .map(|c| if c.is_whitespace() { c } else { ' ' })
-                    .collect()
-            })
-            .collect();
+        EffectResult::new(reveal_first(ascii_art, visible_chars))
+    }
 
-        for (i, (x, y, ch)) in positions.iter().enumerate() {
-            if i < visible_chars {
-                if let Some(line) = result_lines.get_mut(*y) {
-                    let mut chars: Vec<char> = line.chars().collect();
-                    if *x < chars.len() {
-                        chars[*x] = *ch;
-                        *line = chars.iter().collect();
-                    }
-                }
-            }
+    fn name(&self) -> &str {
+        "typewriter-reverse"
+    }
+}
+
+// Typewriter with a blinking block cursor on the next glyph to be typed
+pub struct TypewriterCursor;
+
+impl TypewriterCursor {
+    const CURSOR: &'static str = "█";
+    /// Blinks per second
+    const HZ: f64 = 2.0;
+}
+
+impl Effect for TypewriterCursor {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let positions = ascii_art.grapheme_positions();
+        let visible_chars = (positions.len() as f64 * ctx.progress) as usize;
+        let mut cells = revealed_cells(ascii_art, visible_chars);
+
+        // Shown for the first half of every blink, and gone once all is typed
+        let cursor_on = ctx.phase(Self::HZ).sin() >= 0.0;
+        if let (true, Some(&(x, y, _))) = (cursor_on, positions.get(visible_chars)) {
+            cells[y][x] = Self::CURSOR;
         }
 
-        EffectResult::new(result_lines.join("\n"))
+        EffectResult::new(join_cells(&cells))
     }
 
     fn name(&self) -> &str {
-        "typewriter-reverse"
+        "typewriter-cursor"
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
     }
 }
 
 // Wave effect
 pub struct Wave;
+
+impl Wave {
+    const HZ: f64 = 0.5;
+}
+
 impl Effect for Wave {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let phase = ctx.phase(Self::HZ);
         let lines: Vec<String> = ascii_art
             .get_lines()
             .iter()
             .enumerate()
             .map(|(i, line)| {
-                let wave_offset =
-                    ((progress * std::f64::consts::PI * 2.0 + i as f64 * 0.5).sin() * 3.0) as usize;
+                let wave_offset = ((phase + i as f64 * 0.5).sin() * 3.0) as usize;
                 format!("{}{}", " ".repeat(wave_offset), line)
             })
             .collect();
@@ -306,31 +414,41 @@ impl Effect for Wave {
     fn name(&self) -> &str {
         "wave"
     }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
 }
 
 // Jello effect
 pub struct Jello;
 impl Effect for Jello {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         let wobble = (progress * std::f64::consts::PI * 4.0).sin() * (1.0 - progress);
         let scale = 1.0 + wobble * 0.1;
-        let scaled = ascii_art.scale(scale.abs());
+        let scaled = ascii_art.scale_centered(scale.abs());
         EffectResult::new(scaled.render()).with_scale(scale.abs())
     }
 
     fn name(&self) -> &str {
         "jello"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 // Rotate effects
 pub struct RotateIn;
 impl Effect for RotateIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Simulate rotation with scale and offset
         let angle = (1.0 - progress) * std::f64::consts::PI;
         let scale = progress;
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         let offset_x = (angle.cos() * 10.0 * (1.0 - progress)) as i32;
         EffectResult::new(scaled.render())
             .with_scale(scale)
@@ -344,10 +462,11 @@ impl Effect for RotateIn {
 
 pub struct RotateOut;
 impl Effect for RotateOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         let angle = progress * std::f64::consts::PI;
         let scale = 1.0 - progress;
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         let offset_x = (angle.cos() * 10.0 * progress) as i32;
         EffectResult::new(scaled.render())
             .with_scale(scale)
@@ -362,7 +481,7 @@ impl Effect for RotateOut {
 // Color effects (these will be enhanced by color engine)
 pub struct ColorCycle;
 impl Effect for ColorCycle {
-    fn apply(&self, ascii_art: &AsciiArt, _progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, _ctx: &EffectContext) -> EffectResult {
         EffectResult::new(ascii_art.render())
     }
 
@@ -373,7 +492,7 @@ impl Effect for ColorCycle {
 
 pub struct Rainbow;
 impl Effect for Rainbow {
-    fn apply(&self, ascii_art: &AsciiArt, _progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, _ctx: &EffectContext) -> EffectResult {
         EffectResult::new(ascii_art.render())
     }
 
@@ -384,7 +503,7 @@ impl Effect for Rainbow {
 
 pub struct GradientFlow;
 impl Effect for GradientFlow {
-    fn apply(&self, ascii_art: &AsciiArt, _progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, _ctx: &EffectContext) -> EffectResult {
         EffectResult::new(ascii_art.render())
     }
 
@@ -397,24 +516,37 @@ impl Effect for GradientFlow {
 
 // Shake effect - horizontal vibration
 pub struct Shake;
+
+impl Shake {
+    const HZ: f64 = 6.0;
+}
+
 impl Effect for Shake {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Fast oscillation that decreases over time
-        let frequency = 20.0;
-        let amplitude = 10.0 * (1.0 - progress);
-        let offset_x = (progress * frequency * std::f64::consts::PI * 2.0).sin() * amplitude;
+        let amplitude = 10.0 * (1.0 - ctx.progress);
+        let offset_x = ctx.phase(Self::HZ).sin() * amplitude;
         EffectResult::new(ascii_art.render()).with_offset(offset_x as i32, 0)
     }
 
     fn name(&self) -> &str {
         "shake"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
 }
 
 // Wobble effect - rotation wobble (simulated with offset variations)
 pub struct Wobble;
 impl Effect for Wobble {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Wobble with decreasing amplitude
         let angle = progress * std::f64::consts::PI * 4.0;
         let amplitude = 15.0 * (1.0 - progress);
@@ -426,31 +558,48 @@ impl Effect for Wobble {
     fn name(&self) -> &str {
         "wobble"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 // Vibrate effect - rapid small movements
 pub struct Vibrate;
+
+impl Vibrate {
+    const HZ: f64 = 8.0;
+}
+
 impl Effect for Vibrate {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Very fast, small vibrations
-        let frequency = 50.0;
+        let phase = ctx.phase(Self::HZ);
         let amplitude = 3.0;
-        let offset_x = (progress * frequency * std::f64::consts::PI).sin() * amplitude;
-        let offset_y = (progress * frequency * std::f64::consts::PI * 1.3).cos() * amplitude;
+        let offset_x = phase.sin() * amplitude;
+        let offset_y = (phase * 1.3).cos() * amplitude;
         EffectResult::new(ascii_art.render()).with_offset(offset_x as i32, offset_y as i32)
     }
 
     fn name(&self) -> &str {
         "vibrate"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
 }
 
 // Heartbeat effect - pulsing scale with heartbeat rhythm
 pub struct Heartbeat;
 impl Effect for Heartbeat {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Two-beat pulse pattern like a heartbeat
-        let beat_progress = (progress * 2.0) % 1.0;
+        let beat_progress = (ctx.progress * 2.0) % 1.0;
         let scale = if beat_progress < 0.3 {
             1.0 + (beat_progress / 0.3) * 0.15
         } else if beat_progress < 0.4 {
@@ -462,31 +611,35 @@ impl Effect for Heartbeat {
         } else {
             1.0
         };
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render()).with_scale(scale)
     }
 
     fn name(&self) -> &str {
         "heartbeat"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 // Flip horizontal - flip text horizontally
 pub struct FlipHorizontal;
 impl Effect for FlipHorizontal {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Scale horizontally from 1 to -1 (flip)
-        let scale = 1.0 - (progress * 2.0);
+        let scale = 1.0 - (ctx.progress * 2.0);
         if scale <= 0.0 {
             // Show reversed text when flipped
             let lines: Vec<String> = ascii_art
                 .get_lines()
                 .iter()
-                .map(|line| line.chars().rev().collect())
+                .map(|line| line.graphemes(true).rev().collect())
                 .collect();
             EffectResult::new(lines.join("\n"))
         } else {
-            let scaled = ascii_art.scale(scale);
+            let scaled = ascii_art.scale_centered(scale);
             EffectResult::new(scaled.render()).with_scale(scale)
         }
     }
@@ -499,7 +652,8 @@ impl Effect for FlipHorizontal {
 // Flip vertical - flip text vertically
 pub struct FlipVertical;
 impl Effect for FlipVertical {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Scale vertically with midpoint flip
         let scale = 1.0 - (progress * 2.0).min(1.0);
         if progress > 0.5 {
@@ -511,10 +665,10 @@ impl Effect for FlipVertical {
                 .collect();
             lines.reverse();
             let result_scale = (progress - 0.5) * 2.0;
-            let scaled = AsciiArt::new(lines.join("\n")).scale(result_scale);
+            let scaled = AsciiArt::new(lines.join("\n")).scale_centered(result_scale);
             EffectResult::new(scaled.render()).with_scale(result_scale)
         } else {
-            let scaled = ascii_art.scale(scale.max(0.1));
+            let scaled = ascii_art.scale_centered(scale.max(0.1));
             EffectResult::new(scaled.render()).with_scale(scale.max(0.1))
         }
     }
@@ -526,11 +680,15 @@ impl Effect for FlipVertical {
 
 // Swing effect - pendulum motion
 pub struct Swing;
+
+impl Swing {
+    const HZ: f64 = 0.75;
+}
+
 impl Effect for Swing {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Pendulum swing with decreasing amplitude
-        let swings = 2.0;
-        let angle = (progress * swings * std::f64::consts::PI * 2.0).sin() * (1.0 - progress);
+        let angle = ctx.phase(Self::HZ).sin() * (1.0 - ctx.progress);
         let offset_x = (angle * 20.0) as i32;
         let offset_y = (angle.abs() * 5.0) as i32;
         EffectResult::new(ascii_art.render()).with_offset(offset_x, -offset_y)
@@ -539,14 +697,22 @@ impl Effect for Swing {
     fn name(&self) -> &str {
         "swing"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
 }
 
 // Sway effect - gentle swaying motion
 pub struct Sway;
 impl Effect for Sway {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Smooth, gentle sway
-        let angle = (progress * std::f64::consts::PI * 2.0).sin();
+        let angle = (ctx.progress * std::f64::consts::PI * 2.0).sin();
         let offset_x = (angle * 8.0) as i32;
         let offset_y = (angle.abs() * 2.0) as i32;
         EffectResult::new(ascii_art.render()).with_offset(offset_x, offset_y)
@@ -560,7 +726,8 @@ impl Effect for Sway {
 // Roll-in effect - roll in from left with rotation
 pub struct RollIn;
 impl Effect for RollIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Slide in from left while appearing to roll
         let offset_x = ((1.0 - progress) * -(ascii_art.width() as f64 + 20.0)) as i32;
         let rotation_effect = ((1.0 - progress) * 5.0) as i32;
@@ -576,7 +743,8 @@ impl Effect for RollIn {
 // Roll-out effect - roll out to right with rotation
 pub struct RollOut;
 impl Effect for RollOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Slide out to right while appearing to roll
         let offset_x = (progress * (ascii_art.width() as f64 + 20.0)) as i32;
         let rotation_effect = (progress * 5.0) as i32;
@@ -594,11 +762,12 @@ impl Effect for RollOut {
 // Puff-in effect - scale up from tiny with fade in
 pub struct PuffIn;
 impl Effect for PuffIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Start very small and expand while fading in
         let scale = 0.1 + (progress * 0.9);
         let opacity = progress;
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render())
             .with_scale(scale)
             .with_opacity(opacity)
@@ -612,11 +781,12 @@ impl Effect for PuffIn {
 // Puff-out effect - scale down to tiny with fade out
 pub struct PuffOut;
 impl Effect for PuffOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Shrink down while fading out
         let scale = 1.0 - (progress * 0.9);
         let opacity = 1.0 - progress;
-        let scaled = ascii_art.scale(scale.max(0.1));
+        let scaled = ascii_art.scale_centered(scale.max(0.1));
         EffectResult::new(scaled.render())
             .with_scale(scale)
             .with_opacity(opacity)
@@ -630,7 +800,8 @@ impl Effect for PuffOut {
 // Slide-rotate horizontal - slide from left with rotation
 pub struct SlideRotateHor;
 impl Effect for SlideRotateHor {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Slide in from left while rotating
         let offset_x = ((1.0 - progress) * -(ascii_art.width() as f64 + 10.0)) as i32;
         let rotation_progress = 1.0 - progress;
@@ -647,7 +818,8 @@ impl Effect for SlideRotateHor {
 // Slide-rotate vertical - slide from top with rotation
 pub struct SlideRotateVer;
 impl Effect for SlideRotateVer {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Slide in from top while rotating
         let offset_y = ((1.0 - progress) * -(ascii_art.height() as f64 + 5.0)) as i32;
         let rotation_progress = 1.0 - progress;
@@ -664,7 +836,8 @@ impl Effect for SlideRotateVer {
 // Flicker effect - random flickering opacity
 pub struct Flicker;
 impl Effect for Flicker {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Fast flickering that stabilizes
         let flicker_speed = 30.0;
         let stability = progress; // Gets more stable over time
@@ -681,9 +854,9 @@ impl Effect for Flicker {
 // Tracking-in effect - letters expand from center
 pub struct TrackingIn;
 impl Effect for TrackingIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Simulate letter spacing by adding spaces between characters
-        let spacing = ((1.0 - progress) * 3.0) as usize;
+        let spacing = ((1.0 - ctx.progress) * 3.0) as usize;
         if spacing == 0 {
             EffectResult::new(ascii_art.render())
         } else {
@@ -691,9 +864,9 @@ impl Effect for TrackingIn {
                 .get_lines()
                 .iter()
                 .map(|line| {
-                    line.chars()
+                    line.graphemes(true)
                         .map(|c| {
-                            if c == ' ' {
+                            if c == " " {
                                 " ".repeat(spacing + 1)
                             } else {
                                 format!("{}{}", c, " ".repeat(spacing))
@@ -714,9 +887,9 @@ impl Effect for TrackingIn {
 // Tracking-out effect - letters contract to center
 pub struct TrackingOut;
 impl Effect for TrackingOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Simulate letter spacing by adding spaces between characters
-        let spacing = (progress * 3.0) as usize;
+        let spacing = (ctx.progress * 3.0) as usize;
         if spacing == 0 {
             EffectResult::new(ascii_art.render())
         } else {
@@ -724,9 +897,9 @@ impl Effect for TrackingOut {
                 .get_lines()
                 .iter()
                 .map(|line| {
-                    line.chars()
+                    line.graphemes(true)
                         .map(|c| {
-                            if c == ' ' {
+                            if c == " " {
                                 " ".repeat(spacing + 1)
                             } else {
                                 format!("{}{}", c, " ".repeat(spacing))
@@ -747,7 +920,8 @@ impl Effect for TrackingOut {
 // Bounce-top effect - bounce down from top
 pub struct BounceTop;
 impl Effect for BounceTop {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Bounce from top with easing
         let bounces = 2.0;
         let bounce_height = ascii_art.height() as f64 + 10.0;
@@ -761,12 +935,17 @@ impl Effect for BounceTop {
     fn name(&self) -> &str {
         "bounce-top"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 // Bounce-bottom effect - bounce up from bottom
 pub struct BounceBottom;
 impl Effect for BounceBottom {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Bounce from bottom with easing
         let bounces = 2.0;
         let bounce_height = ascii_art.height() as f64 + 10.0;
@@ -780,18 +959,23 @@ impl Effect for BounceBottom {
     fn name(&self) -> &str {
         "bounce-bottom"
     }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
 }
 
 // Tilt-in effect - tilt in with perspective simulation
 pub struct TiltIn;
 impl Effect for TiltIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Simulate tilting in with combined scale and offset
         let tilt_progress = 1.0 - progress;
         let scale = 0.5 + (progress * 0.5);
         let offset_x = (tilt_progress * 20.0 * (tilt_progress * std::f64::consts::PI).sin()) as i32;
         let offset_y = -(tilt_progress * 15.0) as i32;
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render())
             .with_scale(scale)
             .with_offset(offset_x, offset_y)
@@ -807,8 +991,8 @@ impl Effect for TiltIn {
 // Slide-out-top effect - slide out to top
 pub struct SlideOutTop;
 impl Effect for SlideOutTop {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_y = -(progress * (ascii_art.height() as f64 + 10.0)) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_y = -(ctx.progress * (ascii_art.height() as f64 + 10.0)) as i32;
         EffectResult::new(ascii_art.render()).with_offset(0, offset_y)
     }
 
@@ -820,8 +1004,8 @@ impl Effect for SlideOutTop {
 // Slide-out-bottom effect - slide out to bottom
 pub struct SlideOutBottom;
 impl Effect for SlideOutBottom {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_y = (progress * (ascii_art.height() as f64 + 10.0)) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_y = (ctx.progress * (ascii_art.height() as f64 + 10.0)) as i32;
         EffectResult::new(ascii_art.render()).with_offset(0, offset_y)
     }
 
@@ -833,8 +1017,8 @@ impl Effect for SlideOutBottom {
 // Slide-out-left effect - slide out to left
 pub struct SlideOutLeft;
 impl Effect for SlideOutLeft {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_x = -(progress * (ascii_art.width() as f64 + 10.0)) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_x = -(ctx.progress * (ascii_art.width() as f64 + 10.0)) as i32;
         EffectResult::new(ascii_art.render()).with_offset(offset_x, 0)
     }
 
@@ -846,8 +1030,8 @@ impl Effect for SlideOutLeft {
 // Slide-out-right effect - slide out to right
 pub struct SlideOutRight;
 impl Effect for SlideOutRight {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
-        let offset_x = (progress * (ascii_art.width() as f64 + 10.0)) as i32;
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_x = (ctx.progress * (ascii_art.width() as f64 + 10.0)) as i32;
         EffectResult::new(ascii_art.render()).with_offset(offset_x, 0)
     }
 
@@ -859,10 +1043,10 @@ impl Effect for SlideOutRight {
 // Blink effect - rapid on/off blinking
 pub struct Blink;
 impl Effect for Blink {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Blink 3 times during animation
         let blinks = 6.0;
-        let blink_state = ((progress * blinks).floor() % 2.0) as i32;
+        let blink_state = ((ctx.progress * blinks).floor() % 2.0) as i32;
         let opacity = if blink_state == 0 { 1.0 } else { 0.0 };
         EffectResult::new(ascii_art.render()).with_opacity(opacity)
     }
@@ -875,11 +1059,12 @@ impl Effect for Blink {
 // Focus-in effect - simulate coming into focus with scale and opacity
 pub struct FocusIn;
 impl Effect for FocusIn {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Start blurry (small scale, low opacity) and come into focus
         let scale = 0.7 + (progress * 0.3);
         let opacity = progress.powf(0.5);
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render())
             .with_scale(scale)
             .with_opacity(opacity)
@@ -893,11 +1078,12 @@ impl Effect for FocusIn {
 // Blur-out effect - simulate going out of focus
 pub struct BlurOut;
 impl Effect for BlurOut {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Go out of focus (reduce scale, reduce opacity)
         let scale = 1.0 - (progress * 0.3);
         let opacity = (1.0 - progress).powf(0.5);
-        let scaled = ascii_art.scale(scale);
+        let scaled = ascii_art.scale_centered(scale);
         EffectResult::new(scaled.render())
             .with_scale(scale)
             .with_opacity(opacity)
@@ -911,7 +1097,8 @@ impl Effect for BlurOut {
 // Shadow-drop effect - drop down with shadow simulation
 pub struct ShadowDrop;
 impl Effect for ShadowDrop {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Drop down from above with increasing shadow (opacity)
         let drop_distance = 20.0;
         let offset_y = -((1.0 - progress) * drop_distance) as i32;
@@ -929,14 +1116,15 @@ impl Effect for ShadowDrop {
 // Shadow-pop effect - pop forward with shadow simulation
 pub struct ShadowPop;
 impl Effect for ShadowPop {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
         // Scale up quickly then settle, simulating popping forward
         let pop_scale = if progress < 0.5 {
             1.0 + (progress * 2.0) * 0.3
         } else {
             1.3 - ((progress - 0.5) * 2.0) * 0.3
         };
-        let scaled = ascii_art.scale(pop_scale);
+        let scaled = ascii_art.scale_centered(pop_scale);
         EffectResult::new(scaled.render()).with_scale(pop_scale)
     }
 
@@ -948,10 +1136,10 @@ impl Effect for ShadowPop {
 // Rotate-center effect - rotate around center point
 pub struct RotateCenter;
 impl Effect for RotateCenter {
-    fn apply(&self, ascii_art: &AsciiArt, progress: f64) -> EffectResult {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
         // Simulate rotation with alternating line offsets
         let rotations = 1.0;
-        let angle = progress * rotations * std::f64::consts::PI * 2.0;
+        let angle = ctx.progress * rotations * std::f64::consts::PI * 2.0;
         let max_offset = 5.0;
 
         let lines: Vec<String> = ascii_art
@@ -964,7 +1152,9 @@ impl Effect for RotateCenter {
                 if offset > 0 {
                     format!("{}{}", " ".repeat(offset as usize), line)
                 } else if offset < 0 {
-                    line.chars().skip(offset.unsigned_abs() as usize).collect()
+                    line.graphemes(true)
+                        .skip(offset.unsigned_abs() as usize)
+                        .collect()
                 } else {
                     line.to_string()
                 }
@@ -979,6 +1169,425 @@ impl Effect for RotateCenter {
     }
 }
 
+// Phase 4: Procedural Reveal Effects
+
+/// Cheap deterministic hash used to derive per-cell variation
+fn hash(value: u64) -> u64 {
+    // splitmix64 finalizer
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// RNG seeded from the run's seed and progress quantized to 1/1000, so a
+/// frame always renders the same way for a given seed
+fn frame_rng(ctx: &EffectContext) -> StdRng {
+    let step = (ctx.progress.clamp(0.0, 1.0) * 1000.0).round() as u64;
+    StdRng::seed_from_u64(hash(ctx.seed) ^ step)
+}
+
+/// The art with only its first `visible` grapheme clusters drawn, in reading
+/// order; the rest are blanked
+fn reveal_first(ascii_art: &AsciiArt, visible: usize) -> String {
+    join_cells(&revealed_cells(ascii_art, visible))
+}
+
+/// The art's cells, one grapheme cluster each, with only its first
+/// `visible` glyphs shown; with 0, every glyph is blanked out
+fn revealed_cells(ascii_art: &AsciiArt, visible: usize) -> Vec<Vec<&str>> {
+    let mut lines: Vec<Vec<&str>> = ascii_art
+        .get_lines()
+        .iter()
+        .map(|l| {
+            l.graphemes(true)
+                .map(|g| if is_blank_grapheme(g) { g } else { " " })
+                .collect()
+        })
+        .collect();
+
+    for (x, y, grapheme) in ascii_art.grapheme_positions().into_iter().take(visible) {
+        lines[y][x] = grapheme;
+    }
+
+    lines
+}
+
+fn join_cells(cells: &[Vec<&str>]) -> String {
+    cells
+        .iter()
+        .map(|line| line.concat())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Matrix rain effect - glyphs rain down column by column
+pub struct MatrixRain;
+
+impl MatrixRain {
+    /// Rows behind the leading edge that are still settling
+    const TRAIL: f64 = 3.0;
+    const RAIN_CHARS: [&'static str; 8] = ["0", "1", "|", "/", "\\", "*", "+", ":"];
+    /// Settling glyphs, dimmest (right behind the edge) first
+    const TRAIL_CHARS: [&'static str; 3] = [".", ":", "+"];
+    /// Latest fraction of the animation a column may start at
+    const MAX_DELAY: f64 = 0.4;
+}
+
+impl Effect for MatrixRain {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
+        let mut grid = revealed_cells(ascii_art, 0);
+        let height = ascii_art.height() as f64;
+        let frame = (progress * 60.0) as u64;
+
+        for (x, y, glyph) in ascii_art.grapheme_positions() {
+            // Each column starts falling at its own time but lands by 1.0
+            let delay = (hash(ctx.seed ^ x as u64) % 1000) as f64 / 1000.0 * Self::MAX_DELAY;
+            let column_progress = ((progress - delay) / (1.0 - delay)).clamp(0.0, 1.0);
+            let head = column_progress * (height + Self::TRAIL + 1.0);
+            let distance = head - y as f64;
+
+            if distance <= 0.0 {
+                continue;
+            }
+
+            grid[y][x] = if distance < 1.0 {
+                let seed = hash(ctx.seed ^ (x as u64) << 32 ^ (y as u64) << 16 ^ frame);
+                Self::RAIN_CHARS[(seed % Self::RAIN_CHARS.len() as u64) as usize]
+            } else if distance < Self::TRAIL + 1.0 {
+                Self::TRAIL_CHARS[(distance - 1.0) as usize]
+            } else {
+                glyph
+            };
+        }
+
+        EffectResult::new(join_cells(&grid))
+    }
+
+    fn name(&self) -> &str {
+        "matrix-rain"
+    }
+}
+
+// Glitch effect - rows jump sideways and glyphs corrupt, settling by the end
+pub struct Glitch;
+
+impl Glitch {
+    const BLOCK_CHARS: [&'static str; 4] = ["█", "▓", "▒", "░"];
+    const MAX_SHIFT: i32 = 4;
+}
+
+impl Effect for Glitch {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
+        let intensity = 1.0 - progress.clamp(0.0, 1.0);
+        if intensity <= 0.0 {
+            return EffectResult::new(ascii_art.render());
+        }
+
+        let mut rng = frame_rng(ctx);
+        let mut shift = 0;
+
+        let lines: Vec<String> = ascii_art
+            .get_lines()
+            .iter()
+            .map(|line| {
+                // Start a new slice now and then; rows in a slice share a shift
+                if rng.gen_bool(0.3) {
+                    shift = if rng.gen_bool(intensity * 0.6) {
+                        rng.gen_range(-Self::MAX_SHIFT..=Self::MAX_SHIFT)
+                    } else {
+                        0
+                    };
+                }
+
+                let cells: Vec<&str> = line
+                    .graphemes(true)
+                    .map(|g| {
+                        if !is_blank_grapheme(g) && rng.gen_bool(intensity * 0.08) {
+                            Self::BLOCK_CHARS[rng.gen_range(0..Self::BLOCK_CHARS.len())]
+                        } else {
+                            g
+                        }
+                    })
+                    .collect();
+
+                // Shift within the line's own width so its length stays put
+                let len = cells.len();
+                (0..len)
+                    .map(|i| {
+                        let source = i as i32 - shift;
+                        if source >= 0 && (source as usize) < len {
+                            cells[source as usize]
+                        } else {
+                            " "
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        EffectResult::new(lines.join("\n"))
+    }
+
+    fn name(&self) -> &str {
+        "glitch"
+    }
+}
+
+// Scramble effect - glyphs cycle through random characters and lock in one
+// after another
+pub struct Scramble;
+
+impl Scramble {
+    /// Printable ASCII, '!' through '~', to draw noise from
+    const NOISE: &'static str =
+        "!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+}
+
+impl Effect for Scramble {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let progress = ctx.progress;
+        let positions = ascii_art.grapheme_positions();
+        let total = positions.len().max(1) as f64;
+        let mut rng = frame_rng(ctx);
+        let mut grid = revealed_cells(ascii_art, 0);
+
+        for (i, (x, y, glyph)) in positions.into_iter().enumerate() {
+            // Glyph i locks in once progress reaches (i + 1) / total
+            grid[y][x] = if progress * total >= (i + 1) as f64 {
+                glyph
+            } else {
+                loop {
+                    let at = (rng.gen_range(b'!'..=b'~') - b'!') as usize;
+                    let noise = &Self::NOISE[at..=at];
+                    if noise != glyph {
+                        break noise;
+                    }
+                }
+            };
+        }
+
+        EffectResult::new(join_cells(&grid))
+    }
+
+    fn name(&self) -> &str {
+        "scramble"
+    }
+}
+
+// Line reveal effect - the art appears one line at a time, top to bottom
+pub struct LineReveal;
+
+impl Effect for LineReveal {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let height = ascii_art.height();
+        let visible = ((height as f64 * ctx.progress.clamp(0.0, 1.0)).floor() as usize).min(height);
+
+        let lines: Vec<String> = ascii_art
+            .get_lines()
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                if i < visible {
+                    line.clone()
+                } else {
+                    " ".repeat(line.graphemes(true).count())
+                }
+            })
+            .collect();
+
+        EffectResult::new(lines.join("\n"))
+    }
+
+    fn name(&self) -> &str {
+        "line-reveal"
+    }
+}
+
+// Word reveal effect - the art appears one figlet word at a time, left to
+// right
+pub struct WordReveal;
+
+impl WordReveal {
+    /// Blank columns that separate words; letters are set closer than this
+    const WORD_GAP: usize = 2;
+}
+
+impl Effect for WordReveal {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let words = ascii_art.column_groups(Self::WORD_GAP);
+        let visible =
+            ((words.len() as f64 * ctx.progress.clamp(0.0, 1.0)).floor() as usize).min(words.len());
+        let shown_until = visible.checked_sub(1).map_or(0, |last| words[last].end);
+
+        let lines: Vec<String> = ascii_art
+            .get_lines()
+            .iter()
+            .map(|line| {
+                line.graphemes(true)
+                    .enumerate()
+                    .map(|(x, g)| {
+                        if x < shown_until || is_blank_grapheme(g) {
+                            g
+                        } else {
+                            " "
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        EffectResult::new(lines.join("\n"))
+    }
+
+    fn name(&self) -> &str {
+        "word-reveal"
+    }
+}
+
+// Marquee effects - the art scrolls across the whole terminal without
+// settling, re-entering at one edge as it leaves through the other, so
+// --loop keeps it going. One pass takes the whole duration, or 1/frequency
+// seconds when a frequency is given.
+pub struct MarqueeUp;
+impl Effect for MarqueeUp {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_y = marquee_offset(ascii_art.height(), ctx.term_height, marquee_position(ctx));
+        EffectResult::new(ascii_art.render()).with_offset(0, -offset_y)
+    }
+
+    fn name(&self) -> &str {
+        "marquee-up"
+    }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
+}
+
+pub struct MarqueeDown;
+impl Effect for MarqueeDown {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_y = marquee_offset(ascii_art.height(), ctx.term_height, marquee_position(ctx));
+        EffectResult::new(ascii_art.render()).with_offset(0, offset_y)
+    }
+
+    fn name(&self) -> &str {
+        "marquee-down"
+    }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
+}
+
+// Scrolls right to left, for banners wider than the terminal
+pub struct MarqueeLeft;
+impl Effect for MarqueeLeft {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let offset_x = marquee_offset(ascii_art.width(), ctx.term_width, marquee_position(ctx));
+        EffectResult::new(ascii_art.render()).with_offset(-offset_x, 0)
+    }
+
+    fn name(&self) -> &str {
+        "marquee-left"
+    }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        Some("linear")
+    }
+
+    fn varies_with_time(&self) -> bool {
+        true
+    }
+}
+
+/// How far through its current pass a marquee is
+fn marquee_position(ctx: &EffectContext) -> f64 {
+    match ctx.frequency {
+        Some(hz) => ctx.elapsed_secs() * hz,
+        None => ctx.progress,
+    }
+}
+
+/// Offset from the centered position of art `size` cells long scrolling
+/// across a `viewport`: from just out of view on one side at progress 0.0
+/// to just out of view on the other, wrapping back at 1.0
+pub fn marquee_offset(size: usize, viewport: u16, progress: f64) -> i32 {
+    let half = (size as i32 + viewport as i32 + 1) / 2;
+    (progress.rem_euclid(1.0) * (2 * half) as f64) as i32 - half
+}
+
+/// Several effects joined with `+`, e.g. `slide-in-left+fade-in`.
+///
+/// Each effect transforms the text produced by the one before it, so the
+/// last one sees everything the others did. Offsets are summed, opacities
+/// and scales multiplied. The first effect with a preferred easing sets it.
+pub struct CompositeEffect {
+    effects: Vec<Box<dyn Effect>>,
+    name: String,
+}
+
+impl CompositeEffect {
+    pub fn parse(name: &str) -> Result<Self> {
+        let mut effects = Vec::new();
+        for part in name.split('+').map(str::trim) {
+            if part.is_empty() {
+                bail!("Empty effect in '{}'; join effect names with '+'", name);
+            }
+            effects.push(get_effect(part)?);
+        }
+
+        let name = effects
+            .iter()
+            .map(|e| e.name())
+            .collect::<Vec<_>>()
+            .join("+");
+        Ok(Self { effects, name })
+    }
+}
+
+impl Effect for CompositeEffect {
+    fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+        let mut result = EffectResult::new(ascii_art.render());
+        let mut art = ascii_art.clone();
+
+        for effect in &self.effects {
+            let part = effect.apply(&art, ctx);
+            result.offset_x += part.offset_x;
+            result.offset_y += part.offset_y;
+            result.opacity *= part.opacity;
+            result.scale *= part.scale;
+            art = AsciiArt::new(part.text);
+        }
+
+        result.text = art.render();
+        result
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn preferred_easing(&self) -> Option<&str> {
+        self.effects.iter().find_map(|e| e.preferred_easing())
+    }
+
+    fn varies_with_time(&self) -> bool {
+        self.effects.iter().any(|e| e.varies_with_time())
+    }
+}
+
 /// Get effect by name
 pub fn get_effect(name: &str) -> Result<Box<dyn Effect>> {
     match name {
@@ -996,6 +1605,7 @@ pub fn get_effect(name: &str) -> Result<Box<dyn Effect>> {
         "bounce-out" => Ok(Box::new(BounceOut)),
         "typewriter" => Ok(Box::new(Typewriter)),
         "typewriter-reverse" => Ok(Box::new(TypewriterReverse)),
+        "typewriter-cursor" => Ok(Box::new(TypewriterCursor)),
         "wave" => Ok(Box::new(Wave)),
         "jello" => Ok(Box::new(Jello)),
         "color-cycle" => Ok(Box::new(ColorCycle)),
@@ -1033,12 +1643,24 @@ pub fn get_effect(name: &str) -> Result<Box<dyn Effect>> {
         "shadow-drop" => Ok(Box::new(ShadowDrop)),
         "shadow-pop" => Ok(Box::new(ShadowPop)),
         "rotate-center" => Ok(Box::new(RotateCenter)),
-        _ => bail!("Unknown effect: {}", name),
+        "matrix-rain" => Ok(Box::new(MatrixRain)),
+        "glitch" => Ok(Box::new(Glitch)),
+        "scramble" => Ok(Box::new(Scramble)),
+        "line-reveal" => Ok(Box::new(LineReveal)),
+        "word-reveal" => Ok(Box::new(WordReveal)),
+        "marquee-up" => Ok(Box::new(MarqueeUp)),
+        "marquee-down" => Ok(Box::new(MarqueeDown)),
+        "marquee-left" => Ok(Box::new(MarqueeLeft)),
+        _ if name.contains('+') => Ok(Box::new(CompositeEffect::parse(name)?)),
+        _ => bail!(
+            "Unknown effect '{}'{}",
+            name,
+            suggest::did_you_mean(name, list_effects())
+        ),
     }
 }
 
 /// List all available effects
-#[allow(dead_code)]
 pub fn list_effects() -> Vec<&'static str> {
     vec![
         "fade-in",
@@ -1055,6 +1677,7 @@ pub fn list_effects() -> Vec<&'static str> {
         "bounce-out",
         "typewriter",
         "typewriter-reverse",
+        "typewriter-cursor",
         "wave",
         "jello",
         "color-cycle",
@@ -1092,5 +1715,13 @@ pub fn list_effects() -> Vec<&'static str> {
         "shadow-drop",
         "shadow-pop",
         "rotate-center",
+        "matrix-rain",
+        "glitch",
+        "scramble",
+        "line-reveal",
+        "word-reveal",
+        "marquee-up",
+        "marquee-down",
+        "marquee-left",
     ]
 }
@@ -0,0 +1,98 @@
+use super::{effects, renderer::PlaybackKey, AnimationEngine};
+use crate::utils::terminal::TerminalManager;
+use anyhow::Result;
+use crossterm::event::{Event, KeyCode};
+use futures::{Stream, StreamExt};
+use std::cell::Cell;
+use std::time::Duration;
+
+/// How long each effect plays in the gallery
+pub const EFFECT_DURATION: Duration = Duration::from_secs(2);
+
+/// Play every effect in `list_effects` order, each built by `build` from its
+/// name and captioned with it. `q` skips to the next effect; `Q`, Esc and
+/// Ctrl-C leave the gallery. Returns the effects that were shown.
+pub async fn run_gallery<S, F>(
+    terminal: &mut TerminalManager,
+    events: &mut S,
+    mut build: F,
+) -> Result<Vec<&'static str>>
+where
+    S: Stream<Item = std::io::Result<Event>> + Unpin,
+    F: FnMut(&str) -> Result<AnimationEngine>,
+{
+    let quit = Cell::new(false);
+    let mut events = events.inspect(|event| {
+        if let Ok(event) = event {
+            if leaves_gallery(event) {
+                quit.set(true);
+            }
+        }
+    });
+
+    let mut shown = Vec::new();
+    for name in effects::list_effects() {
+        let engine = build(name)?;
+        shown.push(name);
+        engine
+            .renderer()
+            .with_caption(Some(format!(" {} ", name)))
+            .render_with_events(terminal, &mut events)
+            .await?;
+        if quit.get() {
+            break;
+        }
+    }
+
+    Ok(shown)
+}
+
+/// Every exit key but a plain `q`, which only skips the current effect
+fn leaves_gallery(event: &Event) -> bool {
+    let is_q = matches!(event, Event::Key(key) if key.code == KeyCode::Char('q'));
+    PlaybackKey::from_event(event) == Some(PlaybackKey::Exit) && !is_q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use futures::stream;
+
+    fn engine() -> Result<AnimationEngine> {
+        Ok(AnimationEngine::new("Hi".to_string(), Duration::ZERO, 10))
+    }
+
+    fn key(ch: char) -> std::io::Result<Event> {
+        Ok(Event::Key(KeyEvent::new(
+            KeyCode::Char(ch),
+            KeyModifiers::NONE,
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_gallery_plays_every_effect_in_order() {
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+        let mut built = Vec::new();
+        let shown = run_gallery(&mut terminal, &mut stream::pending(), |name| {
+            built.push(name.to_string());
+            engine()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(shown, effects::list_effects());
+        assert_eq!(built, effects::list_effects());
+    }
+
+    #[tokio::test]
+    async fn test_q_skips_and_shift_q_quits() {
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+        let mut events = stream::iter([key('q'), key('q'), key('Q')]).chain(stream::pending());
+        let shown = run_gallery(&mut terminal, &mut events, |_| engine())
+            .await
+            .unwrap();
+
+        assert_eq!(shown, effects::list_effects()[..3]);
+    }
+}
@@ -1,33 +1,76 @@
 pub mod easing;
 pub mod effects;
+pub mod gallery;
 pub mod renderer;
+pub mod sequence;
 pub mod timeline;
 
 use crate::color::ColorEngine;
-use crate::utils::{ascii::AsciiArt, terminal::TerminalManager};
-use anyhow::Result;
+use crate::utils::{
+    ascii::AsciiArt,
+    border::Border,
+    layout::{Alignment, VerticalPlacement},
+    shadow::Shadow,
+    terminal::TerminalManager,
+};
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::time::Duration;
+
+/// One point on the timeline, rendered but not drawn anywhere
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Timeline frame number, from 0 through the total frame count
+    pub index: usize,
+    /// Linear (un-eased) position on the timeline, 0.0 to 1.0
+    pub progress: f64,
+    /// The effect's output: plain text plus opacity, offset and scale
+    pub effect: effects::EffectResult,
+    /// The text with colors applied, as ANSI escapes
+    pub text: String,
+}
 
 pub struct AnimationEngine {
     ascii_art: AsciiArt,
-    duration_ms: u64,
+    duration: Duration,
     fps: u32,
     effect: Box<dyn effects::Effect>,
     easing: Box<dyn easing::EasingFunction>,
     color_engine: ColorEngine,
+    border: Option<Border>,
+    shadow: Option<Shadow>,
+    alignment: Alignment,
+    vertical: VerticalPlacement,
+    seed: u64,
+    frequency: Option<f64>,
+    verbose: bool,
 }
 
 impl AnimationEngine {
-    pub fn new(ascii_text: String, duration_ms: u64, fps: u32) -> Self {
+    pub fn new(ascii_text: String, duration: Duration, fps: u32) -> Self {
         Self {
             ascii_art: AsciiArt::new(ascii_text),
-            duration_ms,
+            duration,
             fps,
             effect: Box::new(effects::FadeIn),
             easing: Box::new(easing::Linear),
             color_engine: ColorEngine::new(),
+            border: None,
+            shadow: None,
+            alignment: Alignment::default(),
+            vertical: VerticalPlacement::default(),
+            seed: effects::DEFAULT_SEED,
+            frequency: None,
+            verbose: false,
         }
     }
 
+    /// Play over `duration` instead of the length given to `new`
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
     pub fn with_effect(mut self, effect_name: &str) -> Result<Self> {
         self.effect = effects::get_effect(effect_name)?;
         Ok(self)
@@ -38,21 +81,173 @@ impl AnimationEngine {
         Ok(self)
     }
 
+    /// Use `easing_name` when given, otherwise the effect's preferred easing,
+    /// falling back to `easing::DEFAULT_EASING`. Call after `with_effect`.
+    pub fn with_easing_or_default(self, easing_name: Option<&str>) -> Result<Self> {
+        let name = easing_name
+            .or_else(|| self.effect.preferred_easing())
+            .unwrap_or(easing::DEFAULT_EASING)
+            .to_string();
+        self.with_easing(&name)
+    }
+
+    #[allow(dead_code)]
+    pub fn easing_name(&self) -> &str {
+        self.easing.name()
+    }
+
     pub fn with_color_engine(mut self, color_engine: ColorEngine) -> Self {
         self.color_engine = color_engine;
         self
     }
 
-    pub async fn run(&self, terminal: &mut TerminalManager) -> Result<bool> {
-        let renderer = renderer::Renderer::new(
+    pub fn with_border(mut self, border: Option<Border>) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Draw a drop shadow behind the art
+    pub fn with_shadow(mut self, shadow: Option<Shadow>) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Anchor the art to the top, middle or bottom, below a top margin
+    pub fn with_vertical(mut self, vertical: VerticalPlacement) -> Self {
+        self.vertical = vertical;
+        self
+    }
+
+    /// Seed random effects so every run plays the same way
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run oscillating effects at `frequency` Hz instead of their own rate
+    pub fn with_frequency(mut self, frequency: Option<f64>) -> Result<Self> {
+        if let Some(hz) = frequency {
+            if !hz.is_finite() || hz <= 0.0 {
+                bail!("Frequency must be a positive number of Hz, got {}", hz);
+            }
+        }
+        self.frequency = frequency;
+        Ok(self)
+    }
+
+    /// Log per-frame diagnostics to stderr while rendering
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Build a renderer borrowing this engine's art, effect and colors
+    pub fn renderer(&self) -> renderer::Renderer<'_> {
+        renderer::Renderer::new(
             &self.ascii_art,
-            self.duration_ms,
+            self.duration,
             self.fps,
             &*self.effect,
             &*self.easing,
             &self.color_engine,
-        );
+        )
+        .with_border(self.border)
+        .with_shadow(self.shadow)
+        .with_alignment(self.alignment)
+        .with_vertical(self.vertical)
+        .with_seed(self.seed)
+        .with_frequency(self.frequency)
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    #[allow(dead_code)]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn ascii_art(&self) -> &AsciiArt {
+        &self.ascii_art
+    }
+
+    pub fn color_engine(&self) -> &ColorEngine {
+        &self.color_engine
+    }
+
+    pub fn border(&self) -> Option<Border> {
+        self.border
+    }
+
+    pub fn shadow(&self) -> Option<Shadow> {
+        self.shadow
+    }
+
+    pub fn alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    pub fn vertical(&self) -> VerticalPlacement {
+        self.vertical
+    }
+
+    /// Every frame of the animation from the first through the last, as
+    /// fast as they are asked for: no sleeping and no terminal I/O
+    pub fn frames(&self) -> impl Iterator<Item = Frame> + '_ {
+        let mut renderer = self.renderer();
+        let total_frames = renderer.timeline().total_frames();
+        (0..=total_frames).map(move |index| {
+            let progress = if total_frames == 0 {
+                1.0
+            } else {
+                index as f64 / total_frames as f64
+            };
+            let (effect, text) = renderer.frame_at(progress);
+            Frame {
+                index,
+                progress,
+                effect: effects::EffectResult::clone(&effect),
+                text,
+            }
+        })
+    }
+
+    /// Render the final frame (progress 1.0) as ANSI-colored text, without
+    /// touching the terminal
+    pub fn render_static(&self) -> String {
+        let (_, colored_text) = self.renderer().frame_at(1.0);
+        colored_text
+    }
+
+    /// Write the final frame to `out` as plain lines, with no cursor or
+    /// screen control sequences; without `colored` its styling is dropped
+    pub fn write_static(&self, out: &mut impl Write, colored: bool) -> Result<()> {
+        let frame = self.render_static();
+        if colored {
+            writeln!(out, "{}", frame)?;
+        } else {
+            writeln!(out, "{}", crate::utils::ansi::strip_ansi(&frame))?;
+        }
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub async fn run(&self, terminal: &mut TerminalManager) -> Result<bool> {
+        self.run_and_hold(terminal, false).await
+    }
 
-        renderer.render(terminal).await
+    /// Play the animation, optionally keeping the last frame up until a key
+    pub async fn run_and_hold(&self, terminal: &mut TerminalManager, hold: bool) -> Result<bool> {
+        self.renderer()
+            .with_hold(hold)
+            .with_frame_log(self.verbose.then(|| terminal.log_writer()))
+            .render(terminal)
+            .await
     }
 }
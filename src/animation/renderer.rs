@@ -1,14 +1,108 @@
-use crate::animation::{easing::EasingFunction, effects::Effect, timeline::Timeline};
-use crate::color::{apply, ColorEngine};
-use crate::utils::{ansi, ascii::AsciiArt, terminal::TerminalManager};
-use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use crate::animation::{
+    easing::EasingFunction,
+    effects::{Effect, EffectContext, EffectResult, DEFAULT_SEED, DEFAULT_TERM_SIZE},
+    timeline::Timeline,
+};
+use crate::color::{apply, Attributes, ColorEngine, ColorMapping, GradientDirection};
+use crate::parser::color::Color;
+use crate::utils::{
+    ansi,
+    ascii::{glyphs, is_blank_grapheme, AsciiArt},
+    border::Border,
+    buffer::{self, FrameBuffer},
+    layout::{Alignment, VerticalPlacement},
+    shadow::Shadow,
+    signals,
+    terminal::TerminalManager,
 };
-use std::time::Duration;
-use tokio::time::sleep;
+use anyhow::Result;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures::{stream, Stream, StreamExt};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep_until;
+
+/// Number of progress buckets effects that follow progress alone are
+/// cached in; frames falling in the same bucket share their output
+const FRAME_CACHE_RESOLUTION: f64 = 1000.0;
+
+/// Where a cached frame sits on the timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FramePosition {
+    /// Frame index, for effects that vary with time
+    Frame(usize),
+    /// Eased progress in `FRAME_CACHE_RESOLUTION` buckets, for the rest
+    Progress(i64),
+}
+
+/// Position on the timeline and terminal size
+type FrameKey = (FramePosition, u16, u16);
+
+/// Most frames the cache holds; enough to redraw the recent past while
+/// paused or stepping, without growing with the animation's length
+const FRAME_CACHE_CAPACITY: usize = 64;
+
+/// Cache of the most recently drawn effect output, keyed by frame (and
+/// terminal size), so frames drawn again, when paused, stepping or redrawn
+/// after a resize, aren't recomputed and re-allocated. The least recently
+/// used frame makes way once it is full.
+#[derive(Default)]
+pub struct FrameCache {
+    /// Oldest first
+    frames: VecDeque<(FrameKey, Rc<EffectResult>)>,
+}
+
+impl FrameCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Effects that vary with time are keyed by frame index, as two frames
+    /// at nearly the same progress may still differ. The others are keyed
+    /// by eased progress, so at high frame rates neighbouring frames share
+    /// one entry instead of each being recomputed.
+    fn key(effect: &dyn Effect, ctx: &EffectContext) -> FrameKey {
+        let position = if effect.varies_with_time() && ctx.total_frames > 0 {
+            FramePosition::Frame(ctx.frame)
+        } else {
+            FramePosition::Progress((ctx.progress * FRAME_CACHE_RESOLUTION).round() as i64)
+        };
+        (position, ctx.term_width, ctx.term_height)
+    }
+
+    pub fn get_or_apply(
+        &mut self,
+        effect: &dyn Effect,
+        ascii_art: &AsciiArt,
+        ctx: &EffectContext,
+    ) -> Rc<EffectResult> {
+        let key = Self::key(effect, ctx);
+        let entry = match self.frames.iter().position(|(cached, _)| *cached == key) {
+            Some(index) => self.frames.remove(index).expect("index was just found"),
+            None => {
+                if self.frames.len() == FRAME_CACHE_CAPACITY {
+                    self.frames.pop_front();
+                }
+                (key, Rc::new(effect.apply(ascii_art, ctx)))
+            }
+        };
+        let frame = entry.1.clone();
+        self.frames.push_back(entry);
+        frame
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
 
 pub struct Renderer<'a> {
     ascii_art: &'a AsciiArt,
@@ -16,12 +110,23 @@ pub struct Renderer<'a> {
     effect: &'a dyn Effect,
     easing: &'a dyn EasingFunction,
     color_engine: &'a ColorEngine,
+    frame_cache: FrameCache,
+    border: Option<Border>,
+    shadow: Option<Shadow>,
+    alignment: Alignment,
+    vertical: VerticalPlacement,
+    hold: bool,
+    seed: u64,
+    frequency: Option<f64>,
+    term_size: (u16, u16),
+    caption: Option<String>,
+    frame_log: Option<Box<dyn Write>>,
 }
 
 impl<'a> Renderer<'a> {
     pub fn new(
         ascii_art: &'a AsciiArt,
-        duration_ms: u64,
+        duration: Duration,
         fps: u32,
         effect: &'a dyn Effect,
         easing: &'a dyn EasingFunction,
@@ -29,180 +134,1297 @@ impl<'a> Renderer<'a> {
     ) -> Self {
         Self {
             ascii_art,
-            timeline: Timeline::new(duration_ms, fps),
+            timeline: Timeline::new(duration, fps),
             effect,
             easing,
             color_engine,
+            frame_cache: FrameCache::new(),
+            border: None,
+            shadow: None,
+            alignment: Alignment::default(),
+            vertical: VerticalPlacement::default(),
+            hold: false,
+            seed: DEFAULT_SEED,
+            frequency: None,
+            term_size: DEFAULT_TERM_SIZE,
+            caption: None,
+            frame_log: None,
         }
     }
 
-    pub async fn render(&self, terminal: &mut TerminalManager) -> Result<bool> {
-        let mut timeline = Timeline::new(self.timeline.duration_ms(), self.timeline.fps());
-        timeline.start();
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
 
-        // Spawn background thread to listen for exit keys
-        let should_exit = Arc::new(AtomicBool::new(false));
-        let should_exit_clone = should_exit.clone();
+    pub fn with_vertical(mut self, vertical: VerticalPlacement) -> Self {
+        self.vertical = vertical;
+        self
+    }
 
-        std::thread::spawn(move || loop {
-            if let Ok(true) = event::poll(Duration::from_millis(100)) {
-                if let Ok(Event::Key(key)) = event::read() {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
-                            should_exit_clone.store(true, Ordering::Relaxed);
-                            break;
-                        }
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            should_exit_clone.store(true, Ordering::Relaxed);
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
-            }
-            if should_exit_clone.load(Ordering::Relaxed) {
-                break;
-            }
-        });
+    pub fn with_border(mut self, border: Option<Border>) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Keep the final frame on screen until q, Esc or Enter is pressed
+    pub fn with_hold(mut self, hold: bool) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Seed for effects with randomness
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Oscillation rate in Hz for wave, shake, vibrate and swing
+    pub fn with_frequency(mut self, frequency: Option<f64>) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Text drawn in the bottom-left corner of every full-screen frame
+    pub fn with_caption(mut self, caption: Option<String>) -> Self {
+        self.caption = caption;
+        self
+    }
+
+    /// Write a line of timing and effect diagnostics to `log` for every
+    /// frame drawn
+    pub fn with_frame_log(mut self, log: Option<Box<dyn Write>>) -> Self {
+        self.frame_log = log;
+        self
+    }
+
+    /// Terminal size reported to effects when rendering off-screen
+    #[allow(dead_code)]
+    pub fn with_term_size(mut self, width: u16, height: u16) -> Self {
+        self.term_size = (width, height);
+        self
+    }
+
+    pub async fn render(&mut self, terminal: &mut TerminalManager) -> Result<bool> {
+        self.render_with_events(terminal, &mut playback_events())
+            .await
+    }
+
+    /// Render loop reading key presses from `events`. Returns true when the
+    /// user pressed an exit key, false when the animation completed.
+    /// Space pauses and resumes, right arrow steps a paused animation by one
+    /// frame and `r` restarts it.
+    pub async fn render_with_events<S>(
+        &mut self,
+        terminal: &mut TerminalManager,
+        events: &mut S,
+    ) -> Result<bool>
+    where
+        S: Stream<Item = std::io::Result<Event>> + Unpin,
+    {
+        self.timeline.start();
+        let mut previous_start = None;
+        // Colored frames are written here, so its capacity is allocated
+        // once instead of for every frame
+        let mut colored_text = String::new();
 
         loop {
-            // Check for exit FIRST
-            if should_exit.load(Ordering::Relaxed) {
-                return Ok(true); // User requested exit
-            }
+            let frame_start = Instant::now();
 
-            let frame_start = std::time::Instant::now();
+            // Signals flag resizes; without them the size is polled
+            match signals::installed() {
+                Some(flags) if !flags.take_resized() => {}
+                _ => terminal.refresh_size()?,
+            }
 
             // Calculate progress with easing
-            let linear_progress = timeline.progress();
-            let eased_progress = self.easing.ease(linear_progress);
+            self.term_size = terminal.get_size();
+            let linear_progress = self.timeline.progress();
+            let effect_result = self.frame_into(linear_progress, &mut colored_text);
+
+            // Render to terminal
+            if terminal.is_inline() || terminal.is_plain() {
+                // Only as many rows as the art needs, drawn below the cursor
+                let (width, _) = terminal.get_size();
+                let height = (self.ascii_art.height().max(colored_text.lines().count())) as u16;
+                let frame = self.compose(&effect_result, &colored_text, width, height);
+                if terminal.is_plain() {
+                    terminal.present_plain(&frame)?;
+                } else {
+                    terminal.present_inline(&frame)?;
+                }
+            } else if terminal.diff_rendering() {
+                let (width, height) = terminal.get_size();
+                let mut frame = self.compose(&effect_result, &colored_text, width, height);
+                if let Some(caption) = &self.caption {
+                    frame.draw_line(0, height as i32 - 1, caption);
+                }
+                terminal.present(frame)?;
+            } else {
+                terminal.clear()?;
+
+                // Apply offsets and render
+                if effect_result.offset_x == 0 && effect_result.offset_y == 0 {
+                    terminal.print_aligned(&colored_text, self.alignment, self.vertical)?;
+                } else {
+                    let (width, height) = terminal.get_size();
+                    let (x, y) = buffer::offset_origin(
+                        &colored_text,
+                        effect_result.offset_x,
+                        effect_result.offset_y,
+                        width,
+                        height,
+                        self.alignment,
+                        self.vertical,
+                    );
+
+                    // Rows scrolled off the top or bottom are left out, and
+                    // columns off either side cut off
+                    let skip = (-x).max(0) as usize;
+                    let visible = (width as i32 - x.max(0)).max(0) as usize;
+                    for (i, line) in colored_text.lines().enumerate() {
+                        let line_y = y + i as i32;
+                        if (0..height as i32).contains(&line_y) {
+                            let line = ansi::slice_columns(line, skip, visible);
+                            terminal.print_at(x.max(0) as u16, line_y as u16, &line)?;
+                        }
+                    }
+                }
+
+                if let Some(caption) = &self.caption {
+                    let (_, height) = terminal.get_size();
+                    terminal.print_at(0, height.saturating_sub(1), caption)?;
+                }
+            }
 
-            // Check again before rendering
-            if should_exit.load(Ordering::Relaxed) {
-                return Ok(true); // User requested exit
+            if self.frame_log.is_some() {
+                let frame_time = frame_start - previous_start.unwrap_or(frame_start);
+                self.log_frame(linear_progress, &effect_result, frame_time);
             }
+            previous_start = Some(frame_start);
 
-            // Apply effect
-            let effect_result = self.effect.apply(self.ascii_art, eased_progress);
+            let paused = self.timeline.is_paused();
 
-            // Apply colors if available
-            let colored_text = if self.color_engine.has_colors() {
-                self.apply_colors(&effect_result.text, linear_progress)
+            // Stop once the final frame has been drawn; progress follows the
+            // wall clock, so slow frames are skipped rather than delaying this
+            let deadline = if paused {
+                None
+            } else if linear_progress >= 1.0 {
+                // Still honour an exit key that is already waiting
+                let key = wait_for_key(events, Some(Instant::now())).await?;
+                if key == Some(PlaybackKey::Exit) {
+                    return Ok(true);
+                }
+                if self.hold {
+                    return hold_until_key(events).await;
+                }
+                return Ok(false);
             } else {
-                effect_result.text.clone()
+                // Advance to next frame; it is drawn once it is due
+                self.timeline.next_frame();
+                Some(
+                    self.timeline
+                        .frame_deadline()
+                        .unwrap_or_else(|| frame_start + self.timeline.frame_duration()),
+                )
             };
 
-            // Check before terminal operations
-            if should_exit.load(Ordering::Relaxed) {
-                return Ok(true); // User requested exit
+            // Wait for the next frame, reacting to keys as soon as they arrive.
+            // A paused animation only wakes up for a key.
+            match wait_for_key(events, deadline).await? {
+                Some(PlaybackKey::Exit) => return Ok(true), // User requested exit
+                Some(PlaybackKey::TogglePause) if paused => self.timeline.resume(),
+                Some(PlaybackKey::TogglePause) => self.timeline.pause(),
+                Some(PlaybackKey::Step) => self.timeline.step(),
+                Some(PlaybackKey::Confirm) => {}
+                Some(PlaybackKey::Restart) => {
+                    self.timeline.start();
+                    if paused {
+                        self.timeline.pause();
+                    }
+                }
+                // Input closed while paused: nothing can resume us but time
+                None if paused => self.timeline.resume(),
+                None => {}
             }
+        }
+    }
 
-            // Render to terminal
-            terminal.clear()?;
-            terminal.refresh_size()?;
+    /// One `--verbose` line: where the frame is on the timeline, what the
+    /// effect made of it, and how long it took against the frame budget
+    fn log_frame(
+        &mut self,
+        linear_progress: f64,
+        effect_result: &EffectResult,
+        frame_time: Duration,
+    ) {
+        let total_frames = self.timeline.total_frames();
+        let target = self.timeline.frame_duration();
+        let eased_progress = self.easing.ease(linear_progress);
+        if let Some(log) = &mut self.frame_log {
+            // A closed stderr (EPIPE) is no reason to stop playing
+            let _ = writeln!(
+                log,
+                "frame {}/{} progress {:.3} eased {:.3} offset ({}, {}) scale {:.2} opacity {:.2} time {:.1}ms/{:.1}ms",
+                (linear_progress * total_frames as f64).round() as usize,
+                total_frames,
+                linear_progress,
+                eased_progress,
+                effect_result.offset_x,
+                effect_result.offset_y,
+                effect_result.scale,
+                effect_result.opacity,
+                frame_time.as_secs_f64() * 1000.0,
+                target.as_secs_f64() * 1000.0,
+            );
+        }
+    }
+
+    /// Lay out a frame on a `width` x `height` grid
+    pub fn compose(
+        &self,
+        effect_result: &EffectResult,
+        colored_text: &str,
+        width: u16,
+        height: u16,
+    ) -> FrameBuffer {
+        let mut frame = FrameBuffer::new(width, height);
+        frame.draw_frame_aligned(
+            colored_text,
+            effect_result.offset_x,
+            effect_result.offset_y,
+            self.alignment,
+            self.vertical,
+        );
+        frame
+    }
+
+    pub fn with_shadow(mut self, shadow: Option<Shadow>) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Apply easing, effect and colors for a single point on the timeline
+    pub fn frame_at(&mut self, linear_progress: f64) -> (Rc<EffectResult>, String) {
+        let mut colored_text = String::new();
+        let effect_result = self.frame_into(linear_progress, &mut colored_text);
+        (effect_result, colored_text)
+    }
+
+    /// `frame_at`, writing the colored text over `colored_text` so a
+    /// caller drawing frame after frame can keep reusing one buffer
+    pub fn frame_into(
+        &mut self,
+        linear_progress: f64,
+        colored_text: &mut String,
+    ) -> Rc<EffectResult> {
+        let eased_progress = self.easing.ease(linear_progress);
+        let total_frames = self.timeline.total_frames();
+        let frame = (linear_progress * total_frames as f64).round() as usize;
+        let ctx = EffectContext::new(eased_progress)
+            .with_frame(frame, total_frames)
+            .with_term_size(self.term_size.0, self.term_size.1)
+            .with_seed(self.seed)
+            .with_fps(self.timeline.fps())
+            .with_frequency(self.frequency);
 
-            // Apply offsets and render
-            if effect_result.offset_x == 0 && effect_result.offset_y == 0 {
-                terminal.print_centered(&colored_text)?;
+        // Apply effect, reusing the cached frame for this point if any
+        let effect_result = self
+            .frame_cache
+            .get_or_apply(self.effect, self.ascii_art, &ctx);
+
+        // Opacity dims colors, keeping the glyphs intact
+        let opacity = effect_result.opacity;
+
+        // Apply colors if available; color effects fall back to a hue wheel
+        // unless color output is disabled altogether
+        colored_text.clear();
+        if self.color_engine.has_colors()
+            || (self.color_engine.is_enabled() && Self::is_hue_effect(self.effect))
+        {
+            self.apply_colors(colored_text, &effect_result.text, linear_progress, opacity);
+            if self.color_engine.glow() {
+                *colored_text = apply::apply_glow(
+                    colored_text,
+                    self.color_engine.depth(),
+                    self.color_engine.background(),
+                );
+            }
+        } else {
+            let attributes = self.color_engine.attributes();
+            if opacity < 1.0 {
+                // Without colors to dim, fall back to lighter glyphs
+                let faded = AsciiArt::new(effect_result.text.clone()).apply_fade(opacity);
+                colored_text.push_str(&attributes.apply_to_text(&faded));
             } else {
-                let (width, height) = terminal.get_size();
-                let lines: Vec<&str> = colored_text.lines().collect();
-                let text_height = lines.len() as i32;
-                let text_width = lines
-                    .iter()
-                    .map(|l| ansi::visual_width(l))
-                    .max()
-                    .unwrap_or(0) as i32;
-
-                let base_x = (width as i32 - text_width) / 2;
-                let base_y = (height as i32 - text_height) / 2;
-
-                let x = (base_x + effect_result.offset_x).max(0) as u16;
-                let y = (base_y + effect_result.offset_y).max(0) as u16;
-
-                for (i, line) in lines.iter().enumerate() {
-                    let line_y = y.saturating_add(i as u16);
-                    if line_y < height {
-                        terminal.print_at(x, line_y, line)?;
-                    }
-                }
+                colored_text.push_str(&attributes.apply_to_text(&effect_result.text));
             }
+        }
+
+        if let Some(shadow) = self.shadow {
+            *colored_text = shadow.cast(colored_text, &self.shadow_style(shadow, opacity));
+        }
 
-            // Check if user wants to exit
-            if should_exit.load(Ordering::Relaxed) {
-                return Ok(true); // User requested exit
+        match self.border {
+            // A fixed border absorbs the effect offset: the text moves
+            // inside the box while the box stays centered
+            Some(border) if border.fixed => {
+                *colored_text = border.frame(
+                    colored_text,
+                    self.ascii_art.width(),
+                    self.ascii_art.height(),
+                    effect_result.offset_x,
+                    effect_result.offset_y,
+                );
+                let settled = EffectResult::clone(&effect_result).with_offset(0, 0);
+                Rc::new(settled)
             }
+            Some(border) => {
+                *colored_text = border.wrap(colored_text);
+                effect_result
+            }
+            None => effect_result,
+        }
+    }
 
-            // Check if animation is complete before advancing
-            if timeline.is_complete() {
-                return Ok(false); // Animation completed naturally
+    /// SGR style of shadow glyphs: the shadow color multiplied by the art's
+    /// opacity, so the shadow fades with it, or the dim attribute when
+    /// color output is off
+    fn shadow_style(&self, shadow: Shadow, opacity: f64) -> String {
+        if !self.color_engine.is_enabled() {
+            return Attributes {
+                dim: true,
+                ..Default::default()
             }
+            .sgr();
+        }
+        let color = shadow.color.over(self.color_engine.background());
+        self.color_engine
+            .depth()
+            .foreground(color.blend_multiply(&Color::gray(opacity)))
+    }
 
-            // Advance to next frame and wait
-            timeline.next_frame();
-            let frame_duration = timeline.frame_duration();
-            let elapsed = frame_start.elapsed();
+    fn is_hue_effect(effect: &dyn Effect) -> bool {
+        matches!(Self::color_role(effect), "rainbow" | "color-cycle")
+    }
 
-            if elapsed < frame_duration {
-                let sleep_duration = frame_duration - elapsed;
-                // Break sleep into small chunks to check should_exit frequently
-                let chunk_duration = Duration::from_millis(5);
-                let mut remaining = sleep_duration;
+    /// The part of a (possibly composite) effect that decides how text is
+    /// colored: the first color effect in it, or the effect itself
+    fn color_role(effect: &dyn Effect) -> &str {
+        effect
+            .name()
+            .split('+')
+            .find(|part| matches!(*part, "rainbow" | "color-cycle" | "gradient-flow"))
+            .unwrap_or(effect.name())
+    }
 
-                while remaining > Duration::ZERO {
-                    if should_exit.load(Ordering::Relaxed) {
-                        return Ok(true); // User requested exit during sleep
-                    }
-                    let sleep_time = remaining.min(chunk_duration);
-                    sleep(sleep_time).await;
-                    remaining = remaining.saturating_sub(sleep_time);
-                }
+    /// Fully saturated colors spread around the hue circle, shifted by progress
+    fn hue_wheel(steps: usize, progress: f64) -> Vec<Color> {
+        (0..steps)
+            .map(|i| {
+                let hue = (i as f64 / steps.max(1) as f64 + progress) * 360.0;
+                Color::from_hsv(hue, 1.0, 1.0)
+            })
+            .collect()
+    }
+
+    /// Color `text` into `out`, with every color dimmed by `opacity`
+    fn apply_colors(&self, out: &mut String, text: &str, progress: f64, opacity: f64) {
+        let depth = self.color_engine.depth();
+        let attributes = self.color_engine.attributes();
+        let dither = self.color_engine.dither();
+        let direction = self.color_engine.direction();
+        // Every color goes through here last: brightness and saturation
+        // adjustments, then dimming by opacity
+        let dim = |colors: Vec<Color>| -> Vec<Color> {
+            colors
+                .into_iter()
+                .map(|c| self.color_engine.adjust(c))
+                .map(|c| if opacity >= 1.0 { c } else { c.dim(opacity) })
+                .collect()
+        };
+
+        if self.color_engine.mapping() == ColorMapping::PerChar && self.color_engine.has_colors() {
+            let width = text.lines().map(|l| glyphs(l).count()).max().unwrap_or(0);
+            if direction == GradientDirection::Horizontal {
+                let colors = dim(self.color_engine.get_colors(width));
+                apply::write_colors_by_column(out, text, &colors, depth, attributes, dither);
+                return;
             }
+            // Rows and diagonals are spread by position just the same
+            let steps = width.max(text.lines().count());
+            let colors = dim(self.color_engine.get_colors(steps));
+            apply::write_gradient_to_text(out, text, &colors, direction, depth, attributes, dither);
+            return;
         }
-    }
 
-    fn apply_colors(&self, text: &str, progress: f64) -> String {
-        match self.effect.name() {
+        match Self::color_role(self.effect) {
+            "color-cycle" if self.color_engine.palette().is_some() => {
+                // Walk the palette one step per frame so its colors march
+                // across the glyphs
+                let palette = self.color_engine.palette().unwrap();
+                let char_count = glyph_count(text);
+                let frame = (progress * self.timeline.total_frames() as f64).round() as usize;
+                let colors = (0..char_count)
+                    .map(|i| palette.get_color(i + frame))
+                    .collect();
+                apply::write_gradient_to_text(
+                    out,
+                    text,
+                    &dim(colors),
+                    direction,
+                    depth,
+                    attributes,
+                    dither,
+                )
+            }
             "rainbow" | "color-cycle" => {
-                // For rainbow/color-cycle effects, use gradient across characters
-                let char_count = text.chars().filter(|c| !c.is_whitespace()).count();
-                let colors = self.color_engine.get_colors(char_count);
-                apply::apply_gradient_to_text(text, &colors)
+                // For rainbow/color-cycle effects, use gradient across characters,
+                // or a hue wheel rotating with progress when no colors were given
+                let char_count = glyph_count(text);
+                let colors = if self.color_engine.has_colors() {
+                    self.color_engine.get_colors(char_count)
+                } else {
+                    Self::hue_wheel(char_count, progress)
+                };
+                apply::write_gradient_to_text(
+                    out,
+                    text,
+                    &dim(colors),
+                    direction,
+                    depth,
+                    attributes,
+                    dither,
+                )
             }
             "gradient-flow" => {
                 // For gradient-flow, shift colors based on progress
-                let char_count = text.chars().filter(|c| !c.is_whitespace()).count();
+                let char_count = glyph_count(text);
                 let mut colors = self.color_engine.get_colors(char_count * 2);
                 let offset = (progress * colors.len() as f64) as usize;
                 let len = colors.len();
                 colors.rotate_left(offset % len);
                 colors.truncate(char_count);
-                apply::apply_gradient_to_text(text, &colors)
+                apply::write_gradient_to_text(
+                    out,
+                    text,
+                    &dim(colors),
+                    direction,
+                    depth,
+                    attributes,
+                    dither,
+                )
             }
             _ => {
                 // For other effects, use gradient based on progress
                 if let Some(color) = self.color_engine.color_at(progress) {
-                    let lines: Vec<String> = text
-                        .lines()
-                        .map(|line| apply::apply_color_to_line(line, &[color]))
-                        .collect();
-                    lines.join("\n")
+                    let color = dim(vec![color]);
+                    for (i, line) in text.lines().enumerate() {
+                        if i > 0 {
+                            out.push('\n');
+                        }
+                        apply::write_color_to_line(out, line, &color, depth, attributes);
+                    }
                 } else {
-                    let char_count = text.chars().filter(|c| !c.is_whitespace()).count();
-                    let colors = self.color_engine.get_colors(char_count.max(10));
-                    apply::apply_gradient_to_text(text, &colors)
+                    let char_count = glyph_count(text);
+                    let colors = dim(self.color_engine.get_colors(char_count.max(10)));
+                    apply::write_gradient_to_text(
+                        out, text, &colors, direction, depth, attributes, dither,
+                    )
                 }
             }
         }
     }
 }
 
+/// Keys that control playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackKey {
+    /// q, Q, Esc or Ctrl-C
+    Exit,
+    /// Space
+    TogglePause,
+    /// Right arrow
+    Step,
+    /// r
+    Restart,
+    /// Enter
+    Confirm,
+}
+
+impl PlaybackKey {
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('q' | 'Q') | KeyCode::Esc => Some(Self::Exit),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Self::Exit),
+            KeyCode::Char(' ') => Some(Self::TogglePause),
+            KeyCode::Right => Some(Self::Step),
+            KeyCode::Char('r') => Some(Self::Restart),
+            KeyCode::Enter => Some(Self::Confirm),
+            _ => None,
+        }
+    }
+}
+
+/// Glyphs in `text`, counted in grapheme clusters as `AsciiArt` and the
+/// color functions count them
+fn glyph_count(text: &str) -> usize {
+    text.lines()
+        .flat_map(glyphs)
+        .filter(|g| !is_blank_grapheme(g))
+        .count()
+}
+
+/// Key presses from the terminal merged with Ctrl-C, SIGTERM and SIGINT,
+/// as `render` reads them
+pub fn playback_events() -> impl Stream<Item = std::io::Result<Event>> + Unpin {
+    let interrupts = stream::select(interrupt_events(), signal_events());
+    stream::select(EventStream::new(), interrupts)
+}
+
+/// The key event Ctrl-C stands for
+pub fn ctrl_c_event() -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+}
+
+/// Ctrl-C that arrives as a signal instead of a key press, as the key event
+/// it stands for. Without raw mode (inline playback, or a Windows console
+/// that refused it) the console turns Ctrl-C into a signal that the key
+/// stream never sees.
+fn interrupt_events() -> std::pin::Pin<Box<dyn Stream<Item = std::io::Result<Event>>>> {
+    Box::pin(stream::unfold((), |()| async {
+        tokio::signal::ctrl_c().await.ok()?;
+        Some((Ok(ctrl_c_event()), ()))
+    }))
+}
+
+/// SIGTERM and SIGINT, once caught, as the Ctrl-C key event
+fn signal_events() -> std::pin::Pin<Box<dyn Stream<Item = std::io::Result<Event>>>> {
+    match signals::installed() {
+        Some(flags) => Box::pin(stream::unfold(flags, |flags| async move {
+            flags.exited().await;
+            Some((Ok(ctrl_c_event()), flags))
+        })),
+        None => Box::pin(stream::pending()),
+    }
+}
+
+/// Wait until `deadline` (forever if None), returning early with the first
+/// playback key that arrives
+async fn wait_for_key<S>(events: &mut S, deadline: Option<Instant>) -> Result<Option<PlaybackKey>>
+where
+    S: Stream<Item = std::io::Result<Event>> + Unpin,
+{
+    let timer = async {
+        match deadline {
+            Some(deadline) => sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(timer);
+
+    loop {
+        tokio::select! {
+            biased;
+            event = events.next() => match event {
+                Some(Ok(event)) => {
+                    if let Some(key) = PlaybackKey::from_event(&event) {
+                        return Ok(Some(key));
+                    }
+                }
+                Some(Err(err)) => return Err(err.into()),
+                // No more input (e.g. stdin closed): just wait out the frame
+                None => {
+                    if deadline.is_some() {
+                        timer.as_mut().await;
+                    }
+                    return Ok(None);
+                }
+            },
+            _ = timer.as_mut() => return Ok(None),
+        }
+    }
+}
+
+/// Block until q, Esc or Enter; true if the user asked to exit
+async fn hold_until_key<S>(events: &mut S) -> Result<bool>
+where
+    S: Stream<Item = std::io::Result<Event>> + Unpin,
+{
+    loop {
+        match wait_for_key(events, None).await? {
+            Some(PlaybackKey::Exit) => return Ok(true),
+            // Enter, or input closed so nothing could ever release the hold
+            Some(PlaybackKey::Confirm) | None => return Ok(false),
+            Some(_) => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::animation::easing::Linear;
     use crate::animation::effects::FadeIn;
 
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> std::io::Result<Event> {
+        Ok(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    #[tokio::test]
+    async fn test_exit_keys_abort_wait() {
+        for event in [
+            key(KeyCode::Char('q'), KeyModifiers::NONE),
+            key(KeyCode::Esc, KeyModifiers::NONE),
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        ] {
+            let mut events = stream::iter(vec![event]).chain(stream::pending());
+            let deadline = Instant::now() + Duration::from_secs(60);
+            let started = Instant::now();
+            let pressed = wait_for_key(&mut events, Some(deadline)).await.unwrap();
+            assert_eq!(pressed, Some(PlaybackKey::Exit));
+            assert!(started.elapsed() < Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_windows_key_events() {
+        // Windows reports a release for every press; only the press counts
+        let press = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        let release = KeyEvent {
+            kind: KeyEventKind::Release,
+            ..press
+        };
+        assert_eq!(
+            PlaybackKey::from_event(&Event::Key(press)),
+            Some(PlaybackKey::Exit)
+        );
+        assert_eq!(PlaybackKey::from_event(&Event::Key(release)), None);
+
+        let held = KeyEvent {
+            kind: KeyEventKind::Repeat,
+            ..KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)
+        };
+        assert_eq!(
+            PlaybackKey::from_event(&Event::Key(held)),
+            Some(PlaybackKey::Step)
+        );
+
+        // Console Ctrl-C, caught as a signal, exits like the key does
+        assert_eq!(
+            PlaybackKey::from_event(&ctrl_c_event()),
+            Some(PlaybackKey::Exit)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_keys_wait_for_deadline() {
+        let mut events = stream::iter(vec![
+            key(KeyCode::Char('c'), KeyModifiers::NONE),
+            key(KeyCode::Char('x'), KeyModifiers::NONE),
+        ])
+        .chain(stream::pending());
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let pressed = wait_for_key(&mut events, Some(deadline)).await.unwrap();
+        assert_eq!(pressed, None);
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[tokio::test]
+    async fn test_playback_control_keys() {
+        let mut events = stream::iter(vec![
+            key(KeyCode::Char(' '), KeyModifiers::NONE),
+            key(KeyCode::Right, KeyModifiers::NONE),
+            key(KeyCode::Char('r'), KeyModifiers::NONE),
+        ]);
+        for expected in [
+            PlaybackKey::TogglePause,
+            PlaybackKey::Step,
+            PlaybackKey::Restart,
+        ] {
+            let pressed = wait_for_key(&mut events, None).await.unwrap();
+            assert_eq!(pressed, Some(expected));
+        }
+        // A paused wait returns once input is exhausted instead of hanging
+        assert_eq!(wait_for_key(&mut events, None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_hold_waits_for_enter() {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut events = channel_stream(receiver);
+        sender
+            .send(key(KeyCode::Char(' '), KeyModifiers::NONE))
+            .unwrap();
+
+        let hold = hold_until_key(&mut events);
+        tokio::pin!(hold);
+        let waited = tokio::time::timeout(Duration::from_millis(50), hold.as_mut()).await;
+        assert!(waited.is_err(), "hold returned before Enter");
+
+        sender
+            .send(key(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+        assert!(!hold.await.unwrap());
+    }
+
+    /// A renderer for `text` fading in over `duration` at 50fps
+    fn fade_in<'a>(
+        ascii_art: &'a AsciiArt,
+        duration: Duration,
+        effect: &'a FadeIn,
+        easing: &'a Linear,
+        color_engine: &'a ColorEngine,
+    ) -> Renderer<'a> {
+        Renderer::new(ascii_art, duration, 50, effect, easing, color_engine)
+    }
+
+    #[tokio::test]
+    async fn test_render_loop_exits_on_a_key() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let (effect, easing, color_engine) = (FadeIn, Linear, ColorEngine::new());
+
+        for event in [
+            key(KeyCode::Char('q'), KeyModifiers::NONE),
+            key(KeyCode::Esc, KeyModifiers::NONE),
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        ] {
+            let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+            let mut renderer = fade_in(
+                &ascii_art,
+                Duration::from_secs(60),
+                &effect,
+                &easing,
+                &color_engine,
+            );
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            let mut events = channel_stream(receiver);
+
+            let render = renderer.render_with_events(&mut terminal, &mut events);
+            tokio::pin!(render);
+            let playing = tokio::time::timeout(Duration::from_millis(50), render.as_mut()).await;
+            assert!(playing.is_err(), "render returned before the key");
+
+            sender.send(event).unwrap();
+            let exited = tokio::time::timeout(Duration::from_secs(1), render)
+                .await
+                .expect("render kept playing after an exit key");
+            assert!(exited.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hold_keeps_the_last_frame_until_enter() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let (effect, easing, color_engine) = (FadeIn, Linear, ColorEngine::new());
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+        let mut renderer = fade_in(
+            &ascii_art,
+            Duration::from_millis(40),
+            &effect,
+            &easing,
+            &color_engine,
+        )
+        .with_hold(true);
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut events = channel_stream(receiver);
+
+        let render = renderer.render_with_events(&mut terminal, &mut events);
+        tokio::pin!(render);
+        // Well past the end of the animation, it is still holding
+        let held = tokio::time::timeout(Duration::from_millis(300), render.as_mut()).await;
+        assert!(held.is_err(), "render returned before Enter");
+
+        sender
+            .send(key(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+        let completed = tokio::time::timeout(Duration::from_secs(1), render)
+            .await
+            .expect("Enter did not release the hold");
+        assert!(!completed.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_hold_does_not_hold_after_an_exit_key() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let (effect, easing, color_engine) = (FadeIn, Linear, ColorEngine::new());
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+        let mut renderer = fade_in(
+            &ascii_art,
+            Duration::from_millis(40),
+            &effect,
+            &easing,
+            &color_engine,
+        )
+        .with_hold(true);
+        let mut events = stream::iter(vec![key(KeyCode::Char('q'), KeyModifiers::NONE)])
+            .chain(stream::pending());
+
+        let exited = tokio::time::timeout(
+            Duration::from_secs(1),
+            renderer.render_with_events(&mut terminal, &mut events),
+        )
+        .await
+        .expect("render held after the user asked to exit");
+        assert!(exited.unwrap());
+    }
+
+    fn channel_stream(
+        mut receiver: tokio::sync::mpsc::UnboundedReceiver<std::io::Result<Event>>,
+    ) -> impl Stream<Item = std::io::Result<Event>> + Unpin {
+        Box::pin(stream::poll_fn(move |cx| receiver.poll_recv(cx)))
+    }
+
+    /// Records the frame index of every context it is applied with
+    struct FrameRecorder(std::sync::Mutex<Vec<usize>>);
+
+    impl Effect for FrameRecorder {
+        fn apply(&self, ascii_art: &AsciiArt, ctx: &EffectContext) -> EffectResult {
+            self.0.lock().unwrap().push(ctx.frame);
+            EffectResult::new(ascii_art.render())
+        }
+
+        fn name(&self) -> &str {
+            "frame-recorder"
+        }
+
+        // Every frame is recorded, not just every step in progress
+        fn varies_with_time(&self) -> bool {
+            true
+        }
+    }
+
+    /// Counts how often it is applied; its output follows progress alone
+    struct ApplyCounter(std::sync::atomic::AtomicUsize);
+
+    impl Effect for ApplyCounter {
+        fn apply(&self, ascii_art: &AsciiArt, _: &EffectContext) -> EffectResult {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            EffectResult::new(ascii_art.render())
+        }
+
+        fn name(&self) -> &str {
+            "apply-counter"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_context_frame_advances_during_render() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let easing = Linear;
+        let color_engine = ColorEngine::new();
+        let effect = FrameRecorder(Default::default());
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(100),
+            50,
+            &effect,
+            &easing,
+            &color_engine,
+        );
+        let total_frames = renderer.timeline().total_frames();
+        let mut events = stream::pending();
+        renderer
+            .render_with_events(&mut terminal, &mut events)
+            .await
+            .unwrap();
+
+        let frames = effect.0.lock().unwrap().clone();
+        assert_eq!(frames.first(), Some(&0));
+        assert_eq!(frames.last(), Some(&total_frames));
+        assert!(frames.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    /// Cloneable sink so a test can read back the frame log
+    #[derive(Clone, Default)]
+    struct SharedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frame_log_has_a_line_per_frame() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let easing = Linear;
+        let color_engine = ColorEngine::new();
+        let effect = FrameRecorder(Default::default());
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+        let log = SharedLog::default();
+
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(100),
+            50,
+            &effect,
+            &easing,
+            &color_engine,
+        )
+        .with_frame_log(Some(Box::new(log.clone())));
+        let total_frames = renderer.timeline().total_frames();
+        renderer
+            .render_with_events(&mut terminal, &mut stream::pending())
+            .await
+            .unwrap();
+
+        let log = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), effect.0.lock().unwrap().len());
+        assert!(lines[0].starts_with(&format!("frame 0/{} progress 0.0", total_frames)));
+        assert!(lines[0].ends_with("time 0.0ms/20.0ms"));
+        let last = format!("frame {0}/{0} progress 1.000 eased 1.000", total_frames);
+        assert!(lines.last().unwrap().starts_with(&last));
+    }
+
+    /// A log whose reader went away, like stderr piped into `head`
+    struct BrokenPipe;
+
+    impl Write for BrokenPipe {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frame_log_errors_do_not_stop_playback() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let easing = Linear;
+        let color_engine = ColorEngine::new();
+        let effect = FrameRecorder(Default::default());
+        let mut terminal = TerminalManager::with_output(Box::new(std::io::sink()), 20, 5);
+
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(60),
+            50,
+            &effect,
+            &easing,
+            &color_engine,
+        )
+        .with_frame_log(Some(Box::new(BrokenPipe)));
+        let total_frames = renderer.timeline().total_frames();
+        let exited = renderer
+            .render_with_events(&mut terminal, &mut stream::pending())
+            .await
+            .unwrap();
+
+        assert!(!exited);
+        assert_eq!(effect.0.lock().unwrap().last(), Some(&total_frames));
+    }
+
+    #[test]
+    fn test_frame_cache_stays_bounded_on_long_animations() {
+        let ascii_art = AsciiArt::new("Test".to_string());
+        let effect = FrameRecorder(Default::default());
+        let applied = || effect.0.lock().unwrap().len();
+        let mut cache = FrameCache::new();
+
+        // An hour at 30fps
+        let total = 108_000;
+        for i in 0..=total {
+            let progress = i as f64 / total as f64;
+            let ctx = EffectContext::new(progress).with_frame(i, total);
+            cache.get_or_apply(&effect, &ascii_art, &ctx);
+            assert!(cache.len() <= FRAME_CACHE_CAPACITY);
+        }
+        assert_eq!(cache.len(), FRAME_CACHE_CAPACITY);
+        assert_eq!(applied(), total + 1);
+
+        // A recent frame drawn again is reused, unless the terminal was
+        // resized
+        let recent = EffectContext::new(1.0).with_frame(total - 1, total);
+        cache.get_or_apply(&effect, &ascii_art, &recent);
+        assert_eq!(applied(), total + 1);
+        cache.get_or_apply(&effect, &ascii_art, &recent.with_term_size(20, 5));
+        assert_eq!(applied(), total + 2);
+
+        // The oldest frames made way for them
+        let oldest = EffectContext::new(0.0).with_frame(0, total);
+        cache.get_or_apply(&effect, &ascii_art, &oldest);
+        assert_eq!(applied(), total + 3);
+        assert_eq!(cache.len(), FRAME_CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn test_frame_cache_shares_nearby_progress() {
+        let ascii_art = AsciiArt::new("Test".to_string());
+        let effect = ApplyCounter(Default::default());
+        let mut cache = FrameCache::new();
+
+        // 10s at 1000fps: frames a thousandth of the way apart share one
+        // computed result
+        let total = 10_000;
+        let mut previous: Option<Rc<EffectResult>> = None;
+        let mut shared = 0;
+        for i in 0..=total {
+            let progress = i as f64 / total as f64;
+            let ctx = EffectContext::new(progress).with_frame(i, total);
+            let frame = cache.get_or_apply(&effect, &ascii_art, &ctx);
+            if previous.is_some_and(|previous| Rc::ptr_eq(&previous, &frame)) {
+                shared += 1;
+            }
+            previous = Some(frame);
+        }
+        let applied = effect.0.load(std::sync::atomic::Ordering::Relaxed);
+        assert!(applied <= FRAME_CACHE_RESOLUTION as usize + 1, "{applied}");
+        assert_eq!(shared, total + 1 - applied);
+        assert!(cache.len() <= FRAME_CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn test_effects_varying_with_time_are_cached_per_frame() {
+        use crate::animation::effects::get_effect;
+        for name in [
+            "typewriter-cursor",
+            "wave",
+            "shake",
+            "marquee-left",
+            "fade-in+swing",
+        ] {
+            assert!(get_effect(name).unwrap().varies_with_time(), "{name}");
+        }
+        for name in ["fade-in", "matrix-rain", "glitch", "slide-in-left+scale-up"] {
+            assert!(!get_effect(name).unwrap().varies_with_time(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_frame_cache_follows_the_frame_index() {
+        // Blinking depends on the frame, not just on progress
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let effect = crate::animation::effects::get_effect("typewriter-cursor").unwrap();
+        let mut cache = FrameCache::new();
+        let total = 3600;
+        let texts: std::collections::HashSet<String> = (1000..1060)
+            .map(|i| {
+                let ctx = EffectContext::new(0.0).with_frame(i, total).with_fps(60);
+                cache.get_or_apply(&*effect, &ascii_art, &ctx).text.clone()
+            })
+            .collect();
+        assert!(texts.len() > 1);
+    }
+
+    #[test]
+    fn test_color_cycle_changes_over_time() {
+        let ascii_art = AsciiArt::new("Hello".to_string());
+        let effect = crate::animation::effects::ColorCycle;
+        let easing = Linear;
+        let color_engine = ColorEngine::new();
+
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            30,
+            &effect,
+            &easing,
+            &color_engine,
+        );
+        let (_, start) = renderer.frame_at(0.0);
+        let (_, middle) = renderer.frame_at(0.5);
+
+        assert!(start.contains("\x1b[38;2"));
+        assert_ne!(start, middle);
+    }
+
+    #[test]
+    fn test_color_cycle_rotates_palette() {
+        let ascii_art = AsciiArt::new("abc".to_string());
+        let effect = crate::animation::effects::ColorCycle;
+        let easing = Linear;
+        let palette = ["red", "green", "blue"].map(String::from);
+        let color_engine = ColorEngine::new().with_palette(Some(&palette)).unwrap();
+
+        // One second at 10fps, so progress 0.1 is one frame later
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            10,
+            &effect,
+            &easing,
+            &color_engine,
+        );
+        let codes = |frame: &str| -> Vec<String> {
+            frame
+                .split("\x1b[")
+                .filter(|s| s.starts_with("38;2"))
+                .map(|s| s.split('m').next().unwrap().to_string())
+                .collect()
+        };
+        let first = codes(&renderer.frame_at(0.0).1);
+        let second = codes(&renderer.frame_at(0.1).1);
+
+        assert_eq!(first, ["38;2;255;0;0", "38;2;0;128;0", "38;2;0;0;255"]);
+        assert_eq!(second, ["38;2;0;128;0", "38;2;0;0;255", "38;2;255;0;0"]);
+    }
+
+    #[test]
+    fn test_per_char_colors_slide_effect() {
+        let ascii_art = AsciiArt::new("Hello\nWorld".to_string());
+        let effect = crate::animation::effects::SlideInLeft;
+        let easing = Linear;
+        let color_engine = ColorEngine::new()
+            .with_gradient(Some("linear-gradient(90deg, red, blue)"))
+            .unwrap()
+            .with_mapping("per-char")
+            .unwrap();
+
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            30,
+            &effect,
+            &easing,
+            &color_engine,
+        );
+        let (_, frame) = renderer.frame_at(1.0);
+
+        let codes: std::collections::HashSet<&str> = frame
+            .split("\x1b[")
+            .filter(|s| s.starts_with("38;2"))
+            .map(|s| s.split('m').next().unwrap())
+            .collect();
+        assert!(codes.len() > 1);
+    }
+
+    #[test]
+    fn test_fade_dims_colors_instead_of_glyphs() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let effect = FadeIn;
+        let easing = Linear;
+        let color_engine = ColorEngine::new()
+            .with_palette(Some(&["#c86432".to_string()]))
+            .unwrap();
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            30,
+            &effect,
+            &easing,
+            &color_engine,
+        );
+
+        let (_, full) = renderer.frame_at(1.0);
+        let (_, half) = renderer.frame_at(0.5);
+        assert!(full.contains("\x1b[38;2;200;100;50mH"));
+        assert!(half.contains("\x1b[38;2;100;50;25mH"));
+        assert!(half.contains("mi"));
+
+        // Without colors the glyph ramp stands in for opacity
+        let plain = ColorEngine::new();
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            30,
+            &effect,
+            &easing,
+            &plain,
+        );
+        let (_, half) = renderer.frame_at(0.5);
+        assert!(!half.contains('H'));
+    }
+
+    #[test]
+    fn test_shadow_fades_with_the_art() {
+        let ascii_art = AsciiArt::new("Hi".to_string());
+        let effect = FadeIn;
+        let easing = Linear;
+        let color_engine = ColorEngine::new()
+            .with_palette(Some(&["#c86432".to_string()]))
+            .unwrap();
+        let mut renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            30,
+            &effect,
+            &easing,
+            &color_engine,
+        )
+        .with_shadow(Some(Shadow::parse("1,0,#804020").unwrap()));
+
+        // Only the `i` casts a visible shadow; the `H` one is under the `i`
+        let (_, full) = renderer.frame_at(1.0);
+        assert_eq!(ansi::strip_ansi(&full), "Hii");
+        assert!(full.contains("\x1b[38;2;200;100;50mi"));
+        assert!(full.ends_with("\x1b[38;2;128;64;32mi\x1b[0m"), "{full:?}");
+        assert_eq!(full.matches("128;64;32").count(), 1);
+
+        let (_, half) = renderer.frame_at(0.5);
+        assert!(half.ends_with("\x1b[38;2;64;32;16mi\x1b[0m"), "{half:?}");
+    }
+
+    #[test]
+    fn test_zero_opacity_frames_are_invisible() {
+        let ascii_art = AsciiArt::new(" _  _\n|_||_|\n| || |".to_string());
+        let easing = Linear;
+        let colors = ColorEngine::new()
+            .with_palette(Some(&["red".to_string(), "blue".to_string()]))
+            .unwrap();
+        let plain = ColorEngine::new();
+
+        // Blink is fully off at the start of its second half-cycle
+        let blink = crate::animation::effects::get_effect("blink").unwrap();
+        let progress = (0..=100)
+            .map(|i| i as f64 / 100.0)
+            .find(|p| blink.apply(&ascii_art, &EffectContext::new(*p)).opacity == 0.0)
+            .unwrap();
+
+        for effect in [&*blink, &FadeIn as &dyn Effect] {
+            let progress = if effect.name() == "blink" {
+                progress
+            } else {
+                0.0
+            };
+
+            let mut renderer = Renderer::new(
+                &ascii_art,
+                Duration::from_millis(1000),
+                30,
+                effect,
+                &easing,
+                &plain,
+            );
+            let (_, frame) = renderer.frame_at(progress);
+            assert!(frame.chars().all(|c| c == ' ' || c == '\n'), "{frame:?}");
+            assert_eq!(frame.lines().count(), ascii_art.height());
+
+            let mut renderer = Renderer::new(
+                &ascii_art,
+                Duration::from_millis(1000),
+                30,
+                effect,
+                &easing,
+                &colors,
+            );
+            let (_, frame) = renderer.frame_at(progress);
+            for code in frame.split("\x1b[").filter(|s| s.starts_with("38;2")) {
+                assert!(code.starts_with("38;2;0;0;0m"), "{code:?}");
+            }
+        }
+    }
+
     #[test]
     fn test_renderer_creation() {
         let ascii_art = AsciiArt::new("Test".to_string());
@@ -210,9 +1432,16 @@ mod tests {
         let easing = Linear;
         let color_engine = ColorEngine::new();
 
-        let renderer = Renderer::new(&ascii_art, 1000, 30, &effect, &easing, &color_engine);
+        let renderer = Renderer::new(
+            &ascii_art,
+            Duration::from_millis(1000),
+            30,
+            &effect,
+            &easing,
+            &color_engine,
+        );
 
-        assert_eq!(renderer.timeline.duration_ms(), 1000);
+        assert_eq!(renderer.timeline.duration(), Duration::from_secs(1));
         assert_eq!(renderer.timeline.fps(), 30);
     }
 }
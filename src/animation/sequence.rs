@@ -0,0 +1,191 @@
+use super::{easing, effects, renderer::Renderer, AnimationEngine};
+use crate::utils::terminal::TerminalManager;
+use anyhow::{bail, Result};
+use std::time::Duration;
+
+/// Several animations played back to back, each fading out before the
+/// next one comes in
+pub struct Sequence {
+    items: Vec<AnimationEngine>,
+    transition: Duration,
+}
+
+impl Sequence {
+    pub fn new(items: Vec<AnimationEngine>) -> Self {
+        Self {
+            items,
+            transition: Duration::ZERO,
+        }
+    }
+
+    /// Length of the fade-out played between items (0 cuts straight over)
+    pub fn with_transition(mut self, transition: Duration) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// Give every item a new length, e.g. a fresh pick from a duration range
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.items = self
+            .items
+            .into_iter()
+            .map(|item| item.with_duration(duration))
+            .collect();
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn segments(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Items plus the transitions between them
+    #[allow(dead_code)]
+    pub fn total_duration(&self) -> Duration {
+        let items: Duration = self.items.iter().map(|item| item.duration()).sum();
+        let transitions = self.items.len().saturating_sub(1) as u32 * self.transition;
+        items + transitions
+    }
+
+    /// Play every item in order. Returns true as soon as the user presses an
+    /// exit key, aborting the rest of the sequence. With `hold`, the last
+    /// item's final frame stays up until a key is pressed.
+    pub async fn run(&self, terminal: &mut TerminalManager, hold: bool) -> Result<bool> {
+        let last = self.items.len().saturating_sub(1);
+
+        for (i, item) in self.items.iter().enumerate() {
+            if item.run_and_hold(terminal, hold && i == last).await? {
+                return Ok(true);
+            }
+
+            if i < last
+                && !self.transition.is_zero()
+                && self.fade_out(item).render(terminal).await?
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// The transition after `item`: its art fading out where it came to
+    /// rest, border and shadow included
+    fn fade_out<'a>(&self, item: &'a AnimationEngine) -> Renderer<'a> {
+        Renderer::new(
+            item.ascii_art(),
+            self.transition,
+            item.fps(),
+            &effects::FadeOut,
+            &easing::Linear,
+            item.color_engine(),
+        )
+        .with_border(item.border())
+        .with_shadow(item.shadow())
+        .with_alignment(item.alignment())
+        .with_vertical(item.vertical())
+    }
+}
+
+/// How many times to play an animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    Times(u32),
+    Forever,
+}
+
+impl Repeat {
+    /// From `--loop` and `--loop-count`; a single play when neither is given
+    pub fn from_args(forever: bool, count: Option<u32>) -> Result<Self> {
+        match (forever, count) {
+            (true, _) => Ok(Self::Forever),
+            (false, Some(0)) => bail!("--loop-count must be at least 1"),
+            (false, Some(count)) => Ok(Self::Times(count)),
+            (false, None) => Ok(Self::Times(1)),
+        }
+    }
+
+    /// One entry per pass, true for the final one
+    pub fn passes(self) -> Box<dyn Iterator<Item = bool>> {
+        match self {
+            Self::Times(count) => Box::new((1..=count).map(move |pass| pass == count)),
+            Self::Forever => Box::new(std::iter::repeat(false)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{
+        ansi,
+        border::{Border, BorderStyle},
+        shadow::Shadow,
+    };
+
+    #[test]
+    fn test_sequence_segments_and_duration() {
+        let items = ["One", "Two", "Three"]
+            .iter()
+            .map(|text| AnimationEngine::new(text.to_string(), Duration::from_secs(1), 30))
+            .collect();
+        let sequence = Sequence::new(items).with_transition(Duration::from_millis(500));
+
+        assert_eq!(sequence.segments(), 3);
+        assert_eq!(sequence.total_duration(), Duration::from_secs(4));
+
+        let sequence = sequence.with_duration(Duration::from_secs(2));
+        assert_eq!(sequence.total_duration(), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn test_transition_keeps_border_and_shadow() {
+        let items: Vec<AnimationEngine> = ["One", "Two"]
+            .iter()
+            .map(|text| {
+                AnimationEngine::new(text.to_string(), Duration::from_secs(1), 30)
+                    .with_border(Some(Border::new(BorderStyle::Single)))
+                    .with_shadow(Some(Shadow::parse("1,1").unwrap()))
+            })
+            .collect();
+        let sequence = Sequence::new(items).with_transition(Duration::from_millis(500));
+        let item = &sequence.items[0];
+
+        // The fade-out starts from the frame the item came to rest on
+        let (_, rested) = item.renderer().frame_at(1.0);
+        let (_, fading) = sequence.fade_out(item).frame_at(0.0);
+        let fading = ansi::strip_ansi(&fading);
+        assert!(fading.starts_with('┌'), "{fading}");
+        assert_eq!(fading, ansi::strip_ansi(&rested));
+        // Halfway through, the box is still there around the fading art
+        let (_, halfway) = sequence.fade_out(item).frame_at(0.5);
+        assert!(ansi::strip_ansi(&halfway).starts_with('┌'));
+    }
+
+    #[test]
+    fn test_repeat_passes() {
+        assert_eq!(Repeat::from_args(false, None).unwrap(), Repeat::Times(1));
+        assert_eq!(Repeat::from_args(true, None).unwrap(), Repeat::Forever);
+        assert!(Repeat::from_args(false, Some(0)).is_err());
+
+        let mut runs = 0;
+        let mut last_seen = 0;
+        for last in Repeat::from_args(false, Some(3)).unwrap().passes() {
+            runs += 1;
+            if last {
+                last_seen += 1;
+            }
+        }
+        assert_eq!(runs, 3);
+        assert_eq!(last_seen, 1);
+
+        assert_eq!(
+            Repeat::Forever
+                .passes()
+                .take(100)
+                .filter(|last| *last)
+                .count(),
+            0
+        );
+    }
+}
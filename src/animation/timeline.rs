@@ -1,68 +1,180 @@
+use anyhow::{bail, Result};
+use std::io::Write;
 use std::time::{Duration, Instant};
 
+/// Frame rate cap unless `--max-fps` says otherwise; terminals don't draw
+/// any faster, so higher rates only keep the CPU busy
+pub const DEFAULT_MAX_FPS: u32 = 240;
+
+/// Highest `--max-fps` accepted, so `--fps` always ends up at a rate the
+/// timeline and the exports can step through
+pub const MAX_FPS_CAP: u32 = 1000;
+
+/// Check a requested frame rate: 0 is an error, and anything above
+/// `max_fps` is lowered to it with a warning written to `warnings`
+pub fn check_fps(fps: u32, max_fps: u32, warnings: &mut impl Write) -> Result<u32> {
+    if fps == 0 {
+        bail!("--fps must be at least 1");
+    }
+    if max_fps == 0 {
+        bail!("--max-fps must be at least 1");
+    }
+    if fps > max_fps {
+        // Failing to warn is no reason not to play
+        let _ = writeln!(
+            warnings,
+            "Warning: --fps {} is above the {} fps cap, using {} (raise it with --max-fps)",
+            fps, max_fps, max_fps
+        );
+        return Ok(max_fps);
+    }
+    Ok(fps)
+}
+
+/// Tracks animation progress. Once started, progress follows the wall
+/// clock so slow frames are dropped instead of stretching the animation;
+/// an unstarted timeline (as used by exports) advances frame by frame.
 pub struct Timeline {
-    duration_ms: u64,
+    clock: Box<dyn Fn() -> Instant>,
+    duration: Duration,
     fps: u32,
     start_time: Option<Instant>,
+    paused_at: Option<Instant>,
+    paused_total: Duration,
     current_frame: usize,
     total_frames: usize,
 }
 
 impl Timeline {
-    pub fn new(duration_ms: u64, fps: u32) -> Self {
-        let total_frames = ((duration_ms as f64 / 1000.0) * fps as f64).ceil() as usize;
+    pub fn new(duration: Duration, fps: u32) -> Self {
+        let total_frames = (duration.as_secs_f64() * fps as f64).ceil() as usize;
 
         Self {
-            duration_ms,
+            clock: Box::new(Instant::now),
+            duration,
             fps,
             start_time: None,
+            paused_at: None,
+            paused_total: Duration::ZERO,
             current_frame: 0,
             total_frames,
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    #[cfg(test)]
+    pub(crate) fn with_clock(mut self, clock: impl Fn() -> Instant + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
     pub fn start(&mut self) {
-        self.start_time = Some(Instant::now());
+        self.start_time = Some((self.clock)());
+        self.paused_at = None;
+        self.paused_total = Duration::ZERO;
         self.current_frame = 0;
     }
 
     #[allow(dead_code)]
     pub fn reset(&mut self) {
         self.start_time = None;
+        self.paused_at = None;
+        self.paused_total = Duration::ZERO;
         self.current_frame = 0;
     }
 
+    /// Freeze progress until `resume` is called
+    pub fn pause(&mut self) {
+        if self.start_time.is_some() && self.paused_at.is_none() {
+            self.paused_at = Some((self.clock)());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += (self.clock)().saturating_duration_since(paused_at);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Move a paused timeline forward by one frame
+    pub fn step(&mut self) {
+        if !self.is_paused() || self.is_complete() {
+            return;
+        }
+        if let Some(start) = self.start_time {
+            let frame = self.frame_duration();
+            self.start_time = Some(start.checked_sub(frame).unwrap_or(start));
+        }
+        self.current_frame += 1;
+    }
+
     pub fn is_complete(&self) -> bool {
-        self.current_frame >= self.total_frames
+        self.progress() >= 1.0
     }
 
     pub fn progress(&self) -> f64 {
         if self.total_frames == 0 {
             return 1.0;
         }
+        if let Some(start) = self.start_time {
+            if self.duration.is_zero() {
+                return 1.0;
+            }
+            // Frames never run behind the clock; the frame count only
+            // matters when frames are advanced faster than real time
+            let by_clock = self.active_since(start).as_secs_f64() / self.duration.as_secs_f64();
+            let by_frame = self.current_frame as f64 / self.total_frames as f64;
+            return by_clock.max(by_frame).min(1.0);
+        }
         (self.current_frame as f64 / self.total_frames as f64).min(1.0)
     }
 
+    /// Move on to the next frame. After a stall the frames already past
+    /// due are skipped, so playback picks up where the clock is instead of
+    /// drawing them all in a burst.
     pub fn next_frame(&mut self) -> bool {
         if self.is_complete() {
             return false;
         }
 
         self.current_frame += 1;
+        if let Some(start) = self.start_time {
+            let due = self.active_since(start).as_secs_f64() * self.fps as f64;
+            self.current_frame = self.current_frame.max(due as usize);
+        }
         true
     }
 
     pub fn frame_duration(&self) -> Duration {
-        Duration::from_millis(1000 / self.fps as u64)
+        Duration::from_secs_f64(1.0 / self.fps.max(1) as f64)
+    }
+
+    /// Instant the current frame is due, measured from the start so that
+    /// sleep overshoot on one frame doesn't push back every later frame
+    pub fn frame_deadline(&self) -> Option<Instant> {
+        self.start_time.map(|start| {
+            start + self.paused_total + self.frame_duration() * self.current_frame as u32
+        })
     }
 
+    /// Time spent playing since `start`, not counting pauses
     #[allow(dead_code)]
     pub fn elapsed(&self) -> Duration {
         self.start_time
-            .map(|start| start.elapsed())
+            .map(|start| self.active_since(start))
             .unwrap_or(Duration::ZERO)
     }
 
+    fn active_since(&self, start: Instant) -> Duration {
+        let now = self.paused_at.unwrap_or_else(|| (self.clock)());
+        now.saturating_duration_since(start)
+            .saturating_sub(self.paused_total)
+    }
+
     #[allow(dead_code)]
     pub fn current_frame(&self) -> usize {
         self.current_frame
@@ -73,32 +185,81 @@ impl Timeline {
         self.total_frames
     }
 
+    #[allow(dead_code)]
     pub fn fps(&self) -> u32 {
         self.fps
     }
 
-    pub fn duration_ms(&self) -> u64 {
-        self.duration_ms
+    #[allow(dead_code)]
+    pub fn duration(&self) -> Duration {
+        self.duration
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A clock that only moves when told to
+    fn manual_clock() -> (Rc<Cell<Instant>>, impl Fn() -> Instant) {
+        let now = Rc::new(Cell::new(Instant::now()));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || now.get()
+        };
+        (now, clock)
+    }
+
+    fn advance(now: &Cell<Instant>, millis: u64) {
+        now.set(now.get() + Duration::from_millis(millis));
+    }
+
+    #[test]
+    fn test_check_fps() {
+        let mut warnings = Vec::new();
+        assert_eq!(check_fps(30, DEFAULT_MAX_FPS, &mut warnings).unwrap(), 30);
+        assert_eq!(check_fps(240, 240, &mut warnings).unwrap(), 240);
+        assert!(warnings.is_empty());
+
+        assert!(check_fps(0, DEFAULT_MAX_FPS, &mut warnings).is_err());
+        assert!(check_fps(30, 0, &mut warnings).is_err());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_fps_above_the_cap_is_clamped_with_a_warning() {
+        let mut warnings = Vec::new();
+        assert_eq!(
+            check_fps(10_000, DEFAULT_MAX_FPS, &mut warnings).unwrap(),
+            DEFAULT_MAX_FPS
+        );
+        let warnings = String::from_utf8(warnings).unwrap();
+        assert_eq!(warnings.lines().count(), 1);
+        assert!(
+            warnings.contains("10000") && warnings.contains("240"),
+            "{warnings}"
+        );
+
+        let mut warnings = Vec::new();
+        assert_eq!(check_fps(500, 1000, &mut warnings).unwrap(), 500);
+        assert!(warnings.is_empty());
+    }
 
     #[test]
     fn test_timeline_creation() {
-        let timeline = Timeline::new(1000, 30);
+        let timeline = Timeline::new(Duration::from_millis(1000), 30);
         assert_eq!(timeline.total_frames(), 30);
         assert_eq!(timeline.fps(), 30);
     }
 
     #[test]
     fn test_timeline_progress() {
-        let mut timeline = Timeline::new(1000, 10);
+        let mut timeline = Timeline::new(Duration::from_millis(1000), 10);
         timeline.start();
 
-        assert_eq!(timeline.progress(), 0.0);
+        assert!(timeline.progress() < 0.1);
 
         for _ in 0..5 {
             timeline.next_frame();
@@ -109,7 +270,7 @@ mod tests {
 
     #[test]
     fn test_timeline_completion() {
-        let mut timeline = Timeline::new(1000, 10);
+        let mut timeline = Timeline::new(Duration::from_millis(1000), 10);
         timeline.start();
 
         assert!(!timeline.is_complete());
@@ -120,4 +281,104 @@ mod tests {
 
         assert!(timeline.is_complete());
     }
+
+    #[test]
+    fn test_slow_frames_finish_on_time() {
+        let (now, clock) = manual_clock();
+        let mut timeline = Timeline::new(Duration::from_millis(100), 100).with_clock(clock);
+        timeline.start();
+
+        // Each frame takes three times its 10ms budget
+        let mut drawn = 0;
+        while !timeline.is_complete() {
+            advance(&now, 30);
+            timeline.next_frame();
+            drawn += 1;
+        }
+
+        assert_eq!(timeline.elapsed(), Duration::from_millis(120));
+        assert_eq!(drawn, 4);
+        assert!(timeline.current_frame() < timeline.total_frames());
+    }
+
+    #[test]
+    fn test_absolute_schedule_does_not_drift() {
+        let (now, clock) = manual_clock();
+        let mut timeline = Timeline::new(Duration::from_millis(1000), 10).with_clock(clock);
+        timeline.start();
+
+        while !timeline.is_complete() {
+            // Some render work plus a sleep that overshoots a little
+            advance(&now, 3);
+            timeline.next_frame();
+            if let Some(deadline) = timeline.frame_deadline() {
+                if deadline > now.get() {
+                    now.set(deadline + Duration::from_millis(2));
+                }
+            }
+        }
+
+        // Only the last overshoot counts, not one per frame
+        assert_eq!(timeline.elapsed(), Duration::from_millis(1002));
+    }
+
+    #[test]
+    fn test_stall_skips_frames_instead_of_catching_up() {
+        let (now, clock) = manual_clock();
+        let mut timeline = Timeline::new(Duration::from_millis(1000), 10).with_clock(clock);
+        timeline.start();
+        timeline.next_frame();
+
+        // A 450ms stall: frames 2 and 3 were missed
+        advance(&now, 450);
+        timeline.next_frame();
+        assert_eq!(timeline.current_frame(), 4);
+        assert!(timeline.frame_deadline().unwrap() <= now.get());
+
+        // Frame 4 is drawn right away, then the schedule is back to normal
+        timeline.next_frame();
+        assert_eq!(timeline.current_frame(), 5);
+        assert!(timeline.frame_deadline().unwrap() > now.get());
+    }
+
+    #[test]
+    fn test_pause_freezes_progress() {
+        let (now, clock) = manual_clock();
+        let mut timeline = Timeline::new(Duration::from_millis(1000), 30).with_clock(clock);
+        timeline.start();
+        advance(&now, 20);
+
+        timeline.pause();
+        assert!(timeline.is_paused());
+        let frozen = timeline.progress();
+        advance(&now, 50);
+        assert_eq!(timeline.progress(), frozen);
+
+        timeline.resume();
+        assert!(!timeline.is_paused());
+        assert_eq!(timeline.progress(), frozen);
+        assert_eq!(timeline.elapsed(), Duration::from_millis(20));
+
+        // Frames missed while paused aren't skipped on resume
+        timeline.next_frame();
+        assert_eq!(timeline.current_frame(), 1);
+    }
+
+    #[test]
+    fn test_step_while_paused() {
+        let mut timeline = Timeline::new(Duration::from_millis(1000), 10);
+        timeline.start();
+        timeline.pause();
+        let before = timeline.progress();
+
+        timeline.step();
+        let after = timeline.progress();
+        assert!((after - before - 0.1).abs() < 0.01);
+
+        // Stepping does nothing while playing
+        timeline.resume();
+        let frame = timeline.current_frame();
+        timeline.step();
+        assert_eq!(timeline.current_frame(), frame);
+    }
 }
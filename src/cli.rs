@@ -1,15 +1,51 @@
+use crate::animation::{
+    easing, effects,
+    timeline::{DEFAULT_MAX_FPS, MAX_FPS_CAP},
+};
+use crate::color::gradient::MAX_RESOLUTION;
+use crate::figlet::MAX_LINE_SPACING;
+use crate::utils::border::MAX_PADDING;
+use clap::builder::{PossibleValue, PossibleValuesParser, RangedU64ValueParser, TypedValueParser};
+use clap::error::ErrorKind;
 use clap::Parser;
+use std::ffi::OsStr;
+use std::path::PathBuf;
 
-#[derive(Parser, Debug)]
+/// Values accepted by `--color-depth`
+const COLOR_DEPTHS: [&str; 5] = ["truecolor", "24bit", "256", "16", "auto"];
+
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 #[command(name = "piglet")]
 #[command(about = "🐷 Animated and colorful figlet wrapper", long_about = None)]
 pub struct PigletCli {
-    /// Text to render with figlet
+    /// Text to render with figlet; read from piped stdin when it is `-` or
+    /// left out
     #[arg(value_name = "TEXT")]
-    pub text: String,
+    pub text: Option<String>,
+
+    /// Play several texts one after another (comma-separated)
+    /// Example: "One,Two,Three"
+    #[arg(
+        long,
+        value_name = "TEXTS",
+        value_delimiter = ',',
+        conflicts_with_all = ["text", "export", "output_ansi", "dump_json"]
+    )]
+    pub sequence: Option<Vec<String>>,
+
+    /// Apply a named preset from ~/.config/piglet/presets.toml; flags given
+    /// on the command line take precedence
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
 
-    /// Duration of animation (e.g., 3000ms, 0.3s, 0.5h, 5m)
+    /// Ignore the project's .pigletrc (looked up in the current directory
+    /// and its parents)
+    #[arg(long)]
+    pub no_config: bool,
+
+    /// Duration of animation (e.g., 3000ms, 0.3s, 0.5h, 5m, 1m30s, 500us), or a range
+    /// such as 1s-3s to pick a new duration on every loop
     #[arg(short, long, default_value = "3s")]
     pub duration: String,
 
@@ -23,27 +59,154 @@ pub struct PigletCli {
     #[arg(short = 'g', long)]
     pub color_gradient: Option<String>,
 
-    /// Motion easing function
-    /// Options: linear, ease-in, ease-out, ease-in-out, ease-in-quad,
-    /// ease-out-quad, ease-in-out-quad, ease-in-cubic, ease-out-cubic,
-    /// ease-in-out-cubic, ease-in-back, ease-out-back, ease-in-out-back,
-    /// ease-in-elastic, ease-out-elastic, ease-in-out-elastic,
-    /// ease-in-bounce, ease-out-bounce, ease-in-out-bounce
-    #[arg(short = 'i', long, default_value = "ease-in-out")]
-    pub motion_ease: String,
-
-    /// Motion effect name
-    /// Options: fade-in, fade-out, fade-in-out, slide-in-top, slide-in-bottom,
-    /// slide-in-left, slide-in-right, scale-up, scale-down, pulse,
-    /// bounce-in, bounce-out, typewriter, typewriter-reverse, wave,
-    /// jello, color-cycle, rainbow, gradient-flow, rotate-in, rotate-out
-    #[arg(short, long, default_value = "fade-in")]
+    /// How colors are spread over the text: auto (per effect) or per-char
+    /// (every glyph colored by its column, for any effect)
+    #[arg(long, value_name = "MODE", default_value = "auto")]
+    pub color_mode: String,
+
+    /// Flip the gradient, or the palette order
+    #[arg(long)]
+    pub reverse_colors: bool,
+
+    /// Which way gradients run across the art: horizontal, vertical or
+    /// diagonal
+    #[arg(long, value_name = "DIRECTION", default_value = "horizontal")]
+    pub gradient_direction: String,
+
+    /// Output color depth: truecolor, 256, 16, or auto to detect it from
+    /// $COLORTERM and $TERM [default: truecolor]. Setting this overrides NO_COLOR
+    #[arg(
+        long,
+        value_name = "DEPTH",
+        value_parser = PossibleValuesParser::new(COLOR_DEPTHS)
+    )]
+    pub color_depth: Option<String>,
+
+    /// Lighten (up to 1.0) or darken (down to -1.0) every color
+    #[arg(
+        long,
+        value_name = "AMOUNT",
+        default_value = "0",
+        allow_negative_numbers = true
+    )]
+    pub brightness: f64,
+
+    /// Saturate (up to 1.0) or desaturate (down to -1.0, gray) every color
+    #[arg(
+        long,
+        value_name = "AMOUNT",
+        default_value = "0",
+        allow_negative_numbers = true
+    )]
+    pub saturation: f64,
+
+    /// Lighten or darken glyphs until they reach this WCAG contrast ratio
+    /// (1-21, e.g. 4.5) against the background
+    #[arg(long, value_name = "RATIO")]
+    pub min_contrast: Option<f64>,
+
+    /// Terminal background color, for --min-contrast and translucent colors
+    /// [default: from $COLORFGBG, else black]
+    #[arg(long, value_name = "COLOR")]
+    pub bg_color: Option<String>,
+
+    /// Smooth gradient bands with ordered dithering when colors are reduced
+    /// to 256 or 16 by --color-depth
+    #[arg(long)]
+    pub dither: bool,
+
+    /// Surround colored glyphs with a dim neon halo
+    #[arg(long)]
+    pub glow: bool,
+
+    /// Draw the glyphs in bold
+    #[arg(long)]
+    pub bold: bool,
+
+    /// Draw the glyphs dimmed
+    #[arg(long)]
+    pub dim: bool,
+
+    /// Draw the glyphs in italics
+    #[arg(long)]
+    pub italic: bool,
+
+    /// Underline the glyphs
+    #[arg(long)]
+    pub underline: bool,
+
+    /// Color space for blending gradient stops (rgb, oklab)
+    #[arg(long, value_name = "SPACE")]
+    pub interpolate: Option<String>,
+
+    /// Entries in the gradient lookup table, 2 to 4096; raise it for
+    /// gradients with sharp transitions [default: 256]
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = RangedU64ValueParser::<usize>::new().range(2..=MAX_RESOLUTION as u64)
+    )]
+    pub gradient_resolution: Option<usize>,
+
+    /// Motion easing function: a named curve, or
+    /// cubic-bezier(x1, y1, x2, y2), steps(n, jump-start|jump-end),
+    /// spring(stiffness, damping), mirror(<easing>)
+    /// [default: ease-in-out, or the effect's own preference]
+    #[arg(short = 'i', long, value_parser = EasingValueParser)]
+    pub motion_ease: Option<String>,
+
+    /// Motion effect name; join several with '+' to combine them
+    /// (e.g. slide-in-left+fade-in)
+    #[arg(short, long, default_value = "fade-in", value_parser = EffectValueParser)]
     pub motion_effect: String,
 
+    /// Seed for effects with randomness (glitch, scramble, matrix-rain)
+    #[arg(long, value_name = "SEED")]
+    pub seed: Option<u64>,
+
+    /// Oscillation rate in Hz for wave, shake, vibrate and swing, or passes
+    /// per second for the marquee effects
+    #[arg(long, value_name = "HZ")]
+    pub frequency: Option<f64>,
+
     /// Figlet font
     #[arg(short = 'f', long)]
     pub font: Option<String>,
 
+    /// Program that draws the text: figlet or toilet
+    #[arg(long, value_name = "BACKEND", default_value = "figlet")]
+    pub backend: String,
+
+    /// Keep the blank rows and trailing spaces figlet pads the art with
+    #[arg(long)]
+    pub no_trim: bool,
+
+    /// Blank rows between the banners of a multi-line text, at most 16
+    #[arg(
+        long,
+        value_name = "ROWS",
+        default_value = "1",
+        value_parser = RangedU64ValueParser::<usize>::new().range(0..=MAX_LINE_SPACING as u64)
+    )]
+    pub line_spacing: usize,
+
+    /// Mirror the art before animating it: none, horizontal, vertical or both
+    #[arg(long, value_name = "AXIS", default_value = "none")]
+    pub flip: String,
+
+    /// Turn the art clockwise by 0, 90, 180 or 270 degrees; 90 and 270 stand
+    /// a wide banner on end
+    #[arg(long, value_name = "DEGREES", default_value = "0")]
+    pub rotate: u16,
+
+    /// Re-read the installed fonts instead of using the cached list
+    #[arg(long)]
+    pub refresh_fonts: bool,
+
+    /// Columns figlet may use before wrapping, or auto for the terminal width
+    #[arg(long, value_name = "COLS", default_value = "auto")]
+    pub width: String,
+
     /// Additional figlet options (use after --)
     /// Example: piglet "Text" -- -w 200 -c
     #[arg(last = true)]
@@ -53,10 +216,105 @@ pub struct PigletCli {
     #[arg(short, long)]
     pub loop_animation: bool,
 
+    /// Play the animation exactly N times
+    #[arg(long, value_name = "N", conflicts_with = "loop_animation")]
+    pub loop_count: Option<u32>,
+
+    /// Keep the final frame on screen until q, Esc or Enter is pressed
+    #[arg(long)]
+    pub hold: bool,
+
+    /// Print a single colored frame to stdout and exit, without animation
+    #[arg(long)]
+    pub output_ansi: bool,
+
+    /// Animate even when stdout is not a terminal, instead of printing
+    /// the final frame
+    #[arg(long)]
+    pub force_animate: bool,
+
+    /// Export the animation to a file instead of playing it (.svg, .cast, .gif)
+    #[arg(long, value_name = "PATH")]
+    pub export: Option<PathBuf>,
+
+    /// Print every frame as a JSON array instead of playing the animation
+    #[arg(long)]
+    pub dump_json: bool,
+
+    /// Disable diff rendering and clear the screen every frame
+    #[arg(long)]
+    pub no_diff: bool,
+
+    /// Animate in place below the prompt instead of on the alternate screen
+    #[arg(long)]
+    pub inline: bool,
+
+    /// Debugging aid: skip raw mode and the alternate screen and print every
+    /// frame below the last, so they stay in scrollback (exit with Ctrl-C)
+    #[arg(long, conflicts_with = "inline")]
+    pub no_raw_mode: bool,
+
+    /// Log progress, effect output and frame timing for every frame to stderr
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Horizontal placement of the art: left, center or right
+    #[arg(long, value_name = "ALIGN", default_value = "center")]
+    pub align: String,
+
+    /// Vertical placement of the art: top, middle or bottom
+    #[arg(long, value_name = "VALIGN", default_value = "middle")]
+    pub valign: String,
+
+    /// Blank rows to keep above the art
+    #[arg(long, value_name = "ROWS", default_value = "0")]
+    pub margin_top: u16,
+
+    /// Draw a box around the art: single, double, rounded or ascii
+    #[arg(long, value_name = "STYLE")]
+    pub border: Option<String>,
+
+    /// Blank cells between the art and the border, at most 16
+    #[arg(
+        long,
+        value_name = "CELLS",
+        default_value = "1",
+        value_parser = RangedU64ValueParser::<usize>::new().range(0..=MAX_PADDING as u64)
+    )]
+    pub border_padding: usize,
+
+    /// Keep the border still while the art moves inside it
+    #[arg(long)]
+    pub border_fixed: bool,
+
+    /// Draw a drop shadow offset by DX,DY cells (at most 32 either way)
+    /// behind the art, optionally in COLOR (e.g. 1,1 or 2,1,#333333)
+    /// [default color: #444444]
+    #[arg(long, value_name = "DX,DY[,COLOR]", allow_hyphen_values = true)]
+    pub shadow: Option<String>,
+
     /// Frame rate (fps)
     #[arg(long, default_value = "30")]
     pub fps: u32,
 
+    /// Highest frame rate played, 1 to 1000; a higher --fps is lowered to
+    /// it with a warning
+    #[arg(
+        long,
+        value_name = "FPS",
+        default_value_t = DEFAULT_MAX_FPS,
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..=MAX_FPS_CAP as u64)
+    )]
+    pub max_fps: u32,
+
+    /// Play TEXT with every effect in turn, labeled with its name
+    /// (q skips to the next one, Q quits)
+    #[arg(
+        long,
+        conflicts_with_all = ["sequence", "export", "output_ansi", "dump_json"]
+    )]
+    pub gallery: bool,
+
     /// List all available effects
     #[arg(long)]
     pub list_effects: bool,
@@ -65,7 +323,165 @@ pub struct PigletCli {
     #[arg(long)]
     pub list_easing: bool,
 
+    /// Print an ASCII plot of an easing curve and exit
+    #[arg(long, value_name = "EASING", value_parser = EasingValueParser)]
+    pub plot_easing: Option<String>,
+
     /// List all available CSS4 colors
     #[arg(long)]
     pub list_colors: bool,
 }
+
+/// Accepts an effect name, or several joined with `+`, listing the valid
+/// names when one is wrong
+#[derive(Clone)]
+struct EffectValueParser;
+
+impl TypedValueParser for EffectValueParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let names = PossibleValuesParser::new(effects::list_effects());
+        let value = value.to_string_lossy();
+        for part in value.split('+') {
+            names.parse_ref(cmd, arg, OsStr::new(part.trim()))?;
+        }
+        Ok(value.to_string())
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            effects::list_effects().into_iter().map(PossibleValue::new),
+        ))
+    }
+}
+
+/// Accepts the named easing functions, offered for completion and listed on
+/// typos, plus the parameterized cubic-bezier(...), steps(...), spring(...)
+/// and mirror(...) forms
+#[derive(Clone)]
+struct EasingValueParser;
+
+impl TypedValueParser for EasingValueParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let name = value.to_string_lossy();
+        if ["cubic-bezier", "steps", "spring", "mirror"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            return easing::get_easing_function(&name)
+                .map(|_| name.to_string())
+                .map_err(|err| {
+                    clap::Error::raw(ErrorKind::ValueValidation, format!("{:#}\n", err))
+                        .with_cmd(cmd)
+                });
+        }
+
+        PossibleValuesParser::new(easing::list_easing_functions()).parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            easing::list_easing_functions()
+                .into_iter()
+                .map(PossibleValue::new),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<PigletCli, clap::Error> {
+        PigletCli::try_parse_from(["piglet", "Hi"].iter().chain(args))
+    }
+
+    #[test]
+    fn test_invalid_effect_lists_valid_values() {
+        let err = parse(&["--motion-effect", "xyz"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+        let message = err.to_string();
+        assert!(message.contains("fade-in"), "{message}");
+        assert!(message.contains("matrix-rain"), "{message}");
+    }
+
+    #[test]
+    fn test_composite_effect_values() {
+        assert!(parse(&["--motion-effect", "slide-in-left+fade-in"]).is_ok());
+        let message = parse(&["--motion-effect", "slide-in-left+fade-inn"])
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("fade-in"), "{message}");
+        assert!(parse(&["--motion-effect", "slide-in-left+"]).is_err());
+    }
+
+    #[test]
+    fn test_easing_values() {
+        assert!(parse(&["--motion-ease", "ease-out"]).is_ok());
+        assert!(parse(&["--motion-ease", "cubic-bezier(0.1, 0.7, 1, 0.1)"]).is_ok());
+        assert!(parse(&["--motion-ease", "steps(4, jump-end)"]).is_ok());
+        assert!(parse(&["--motion-ease", "spring"]).is_ok());
+        assert!(parse(&["--motion-ease", "steps(x)"]).is_err());
+
+        let message = parse(&["--motion-ease", "ease-ou"])
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("ease-out"), "{message}");
+    }
+
+    #[test]
+    fn test_color_depth_values() {
+        assert!(parse(&["--color-depth", "256"]).is_ok());
+        assert!(parse(&["--color-depth", "64"]).is_err());
+    }
+
+    #[test]
+    fn test_gradient_resolution_bounds() {
+        assert!(parse(&["--gradient-resolution", "2"]).is_ok());
+        assert!(parse(&["--gradient-resolution", "4096"]).is_ok());
+        assert!(parse(&["--gradient-resolution", "1"]).is_err());
+        assert!(parse(&["--gradient-resolution", "4097"]).is_err());
+    }
+
+    #[test]
+    fn test_border_padding_bounds() {
+        assert_eq!(parse(&[]).unwrap().border_padding, 1);
+        assert_eq!(
+            parse(&["--border-padding", "16"]).unwrap().border_padding,
+            16
+        );
+        assert!(parse(&["--border-padding", "17"]).is_err());
+        assert!(parse(&["--border-padding", "18446744073709551615"]).is_err());
+    }
+
+    #[test]
+    fn test_line_spacing_bounds() {
+        assert_eq!(parse(&[]).unwrap().line_spacing, 1);
+        assert_eq!(parse(&["--line-spacing", "0"]).unwrap().line_spacing, 0);
+        assert_eq!(parse(&["--line-spacing", "16"]).unwrap().line_spacing, 16);
+        assert!(parse(&["--line-spacing", "17"]).is_err());
+        assert!(parse(&["--line-spacing", "18446744073709551615"]).is_err());
+    }
+
+    #[test]
+    fn test_max_fps_bounds() {
+        assert_eq!(parse(&[]).unwrap().max_fps, DEFAULT_MAX_FPS);
+        assert_eq!(parse(&["--max-fps", "1000"]).unwrap().max_fps, 1000);
+        assert!(parse(&["--max-fps", "0"]).is_err());
+        assert!(parse(&["--max-fps", "1001"]).is_err());
+        assert!(parse(&["--max-fps", "4000000000", "--fps", "4000000000"]).is_err());
+    }
+}
@@ -1,63 +1,561 @@
+use super::{attributes::Attributes, depth::ColorDepth, GradientDirection};
 use crate::parser::color::Color;
-use crossterm::style::Color as CrosstermColor;
+use crate::utils::{
+    ansi,
+    ascii::{glyphs, is_blank_grapheme},
+    buffer,
+};
 
-pub fn apply_color_to_char(ch: char, color: Color) -> String {
-    use crossterm::style::Stylize;
+/// Reset the foreground color only, as crossterm does after styled content
+const RESET_FOREGROUND: &str = "\x1b[39m";
 
-    let crossterm_color = CrosstermColor::Rgb {
-        r: color.r,
-        g: color.g,
-        b: color.b,
-    };
+/// How much of a glyph's color is kept in its glow halo
+const GLOW_STRENGTH: f64 = 0.35;
 
-    format!("{}", ch.to_string().with(crossterm_color))
+/// Append `glyph` (one grapheme cluster, so an accent stays on its letter)
+/// in `color` to `out`, after the attribute sequences in `sgr`
+fn write_colored_glyph(out: &mut String, glyph: &str, color: Color, depth: ColorDepth, sgr: &str) {
+    out.push_str(sgr);
+    depth.write_foreground(out, color);
+    out.push_str(glyph);
+    out.push_str(RESET_FOREGROUND);
 }
 
-pub fn apply_color_to_line(line: &str, colors: &[Color]) -> String {
+/// The color drawn in cell (x, y), dithered when asked to
+fn cell_color(color: Color, depth: ColorDepth, dither: bool, x: usize, y: usize) -> Color {
+    if dither {
+        depth.dither(color, x, y)
+    } else {
+        color
+    }
+}
+
+/// Append `line` to `out`, cycling through `colors` glyph by glyph
+pub fn write_color_to_line(
+    out: &mut String,
+    line: &str,
+    colors: &[Color],
+    depth: ColorDepth,
+    attributes: Attributes,
+) {
     if colors.is_empty() {
-        return line.to_string();
+        out.push_str(line);
+        return;
     }
 
-    line.chars()
-        .enumerate()
-        .map(|(i, ch)| {
-            if ch.is_whitespace() {
-                ch.to_string()
-            } else {
-                let color = colors[i % colors.len()];
-                apply_color_to_char(ch, color)
-            }
-        })
-        .collect()
+    let sgr = attributes.sgr();
+    let mut has_glyphs = false;
+    for (i, glyph) in glyphs(line).enumerate() {
+        if is_blank_grapheme(glyph) {
+            out.push_str(glyph);
+        } else {
+            write_colored_glyph(out, glyph, colors[i % colors.len()], depth, &sgr);
+            has_glyphs = true;
+        }
+    }
+    // Turn the attributes off at the end of a line that has glyphs, so they
+    // don't bleed into whatever is printed after it
+    if has_glyphs {
+        out.push_str(&attributes.reset());
+    }
 }
 
-pub fn apply_gradient_to_text(text: &str, colors: &[Color]) -> String {
-    let lines: Vec<&str> = text.lines().collect();
-    let total_chars: usize = lines.iter().map(|l| l.chars().count()).sum();
+/// Spread `colors` over the glyphs of `text` in `direction`, appending to
+/// `out` so a caller coloring frame after frame can keep reusing one buffer
+pub fn write_gradient_to_text(
+    out: &mut String,
+    text: &str,
+    colors: &[Color],
+    direction: GradientDirection,
+    depth: ColorDepth,
+    attributes: Attributes,
+    dither: bool,
+) {
+    let height = text.lines().count();
+    let total_chars: usize = text.lines().map(|l| glyphs(l).count()).sum();
+    let width = text.lines().map(|l| glyphs(l).count()).max().unwrap_or(0);
+    // Position of a cell along the gradient, as a fraction of its length
+    let along = |i: usize, len: usize| i as f64 / len.saturating_sub(1).max(1) as f64;
 
     if total_chars == 0 || colors.is_empty() {
-        return text.to_string();
+        out.push_str(text);
+        return;
     }
 
-    let mut result = String::new();
+    let sgr = attributes.sgr();
+    let reset = attributes.reset();
     let mut char_index = 0;
 
-    for (line_idx, line) in lines.iter().enumerate() {
-        for ch in line.chars() {
-            if ch.is_whitespace() {
-                result.push(ch);
+    for (line_idx, line) in text.lines().enumerate() {
+        let mut has_glyphs = false;
+        for (x, glyph) in glyphs(line).enumerate() {
+            if is_blank_grapheme(glyph) {
+                out.push_str(glyph);
             } else {
-                let color_index = (char_index * colors.len()) / total_chars.max(1);
+                let color_index = match direction {
+                    GradientDirection::Horizontal => {
+                        (char_index * colors.len()) / total_chars.max(1)
+                    }
+                    GradientDirection::Vertical => {
+                        let t = along(line_idx, height);
+                        (t * (colors.len() - 1) as f64).round() as usize
+                    }
+                    GradientDirection::Diagonal => {
+                        let t = (along(x, width) + along(line_idx, height)) / 2.0;
+                        (t * (colors.len() - 1) as f64).round() as usize
+                    }
+                    GradientDirection::Conic { from_angle } => {
+                        let t = conic_position(x, line_idx, width, height, from_angle);
+                        (t * (colors.len() - 1) as f64).round() as usize
+                    }
+                };
                 let color = colors[color_index.min(colors.len() - 1)];
-                result.push_str(&apply_color_to_char(ch, color));
+                let color = cell_color(color, depth, dither, x, line_idx);
+                write_colored_glyph(out, glyph, color, depth, &sgr);
                 char_index += 1;
+                has_glyphs = true;
             }
         }
+        if has_glyphs {
+            out.push_str(&reset);
+        }
+
+        if line_idx < height - 1 {
+            out.push('\n');
+        }
+    }
+}
+
+/// Position (0.0-1.0) of cell (x, y) on a conic gradient centered on a
+/// `width` x `height` block, going clockwise from `from_angle` degrees
+fn conic_position(x: usize, y: usize, width: usize, height: usize, from_angle: f64) -> f64 {
+    let dx = x as f64 - width.saturating_sub(1) as f64 / 2.0;
+    // Cells are about twice as tall as they are wide
+    let dy = (y as f64 - height.saturating_sub(1) as f64 / 2.0) * 2.0;
+    // Clockwise from the top, as CSS measures it
+    let angle = dx.atan2(-dy).to_degrees();
+    (angle - from_angle).rem_euclid(360.0) / 360.0
+}
+
+/// Color each glyph by its column, so `colors[x]` is used for column `x`,
+/// appending to `out`
+pub fn write_colors_by_column(
+    out: &mut String,
+    text: &str,
+    colors: &[Color],
+    depth: ColorDepth,
+    attributes: Attributes,
+    dither: bool,
+) {
+    if colors.is_empty() {
+        out.push_str(text);
+        return;
+    }
+
+    let sgr = attributes.sgr();
+    let reset = attributes.reset();
+    for (y, line) in text.lines().enumerate() {
+        if y > 0 {
+            out.push('\n');
+        }
+        let mut has_glyphs = false;
+        for (x, glyph) in glyphs(line).enumerate() {
+            if is_blank_grapheme(glyph) {
+                out.push_str(glyph);
+            } else {
+                let color = colors[x.min(colors.len() - 1)];
+                let color = cell_color(color, depth, dither, x, y);
+                write_colored_glyph(out, glyph, color, depth, &sgr);
+                has_glyphs = true;
+            }
+        }
+        if has_glyphs {
+            out.push_str(&reset);
+        }
+    }
+}
+
+/// Give colored glyphs a neon halo: empty cells orthogonally next to a glyph
+/// light up in a darkened version of its color, screened over the
+/// terminal's `background`. Where the halos of several glyphs meet, their
+/// light adds up.
+pub fn apply_glow(text: &str, depth: ColorDepth, background: Color) -> String {
+    // Halos only land on existing cells so line widths, and with them the
+    // centering, stay the same
+    let mut grid: Vec<Vec<buffer::Cell>> = text.lines().map(buffer::parse_cells).collect();
+
+    let strength = Color::gray(GLOW_STRENGTH);
+    let mut light: Vec<Vec<Option<Color>>> = grid.iter().map(|row| vec![None; row.len()]).collect();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if cell.ch.is_whitespace() {
+                continue;
+            }
+            let Some(color) = ansi::foreground_rgb(&cell.style) else {
+                continue;
+            };
+            let halo = color.blend_multiply(&strength);
+            let neighbors = [
+                (x.checked_sub(1), Some(y)),
+                (Some(x + 1), Some(y)),
+                (Some(x), y.checked_sub(1)),
+                (Some(x), Some(y + 1)),
+            ];
+            for (nx, ny) in neighbors {
+                let (Some(nx), Some(ny)) = (nx, ny) else {
+                    continue;
+                };
+                let Some(cell_light) = light.get_mut(ny).and_then(|row| row.get_mut(nx)) else {
+                    continue;
+                };
+                *cell_light = Some(match cell_light {
+                    Some(lit) => lit.blend_add(&halo),
+                    None => halo,
+                });
+            }
+        }
+    }
+
+    for (row, row_light) in grid.iter_mut().zip(light) {
+        for (cell, halo) in row.iter_mut().zip(row_light) {
+            if let Some(halo) = halo {
+                if cell.ch.is_whitespace() && cell.style.is_empty() {
+                    cell.style = depth.background(background.blend_screen(&halo));
+                }
+            }
+        }
+    }
+
+    grid.iter()
+        .map(|row| buffer::cells_to_string(row))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if line_idx < lines.len() - 1 {
-            result.push('\n');
+    fn colored_glyph(
+        glyph: &str,
+        color: Color,
+        depth: ColorDepth,
+        attributes: Attributes,
+    ) -> String {
+        let mut out = String::new();
+        write_colored_glyph(&mut out, glyph, color, depth, &attributes.sgr());
+        out
+    }
+
+    fn colored_line(
+        line: &str,
+        colors: &[Color],
+        depth: ColorDepth,
+        attributes: Attributes,
+    ) -> String {
+        let mut out = String::new();
+        write_color_to_line(&mut out, line, colors, depth, attributes);
+        out
+    }
+
+    fn gradient(
+        text: &str,
+        colors: &[Color],
+        direction: GradientDirection,
+        depth: ColorDepth,
+        attributes: Attributes,
+        dither: bool,
+    ) -> String {
+        let mut out = String::new();
+        write_gradient_to_text(&mut out, text, colors, direction, depth, attributes, dither);
+        out
+    }
+
+    fn by_column(
+        text: &str,
+        colors: &[Color],
+        depth: ColorDepth,
+        attributes: Attributes,
+        dither: bool,
+    ) -> String {
+        let mut out = String::new();
+        write_colors_by_column(&mut out, text, colors, depth, attributes, dither);
+        out
+    }
+
+    fn background(style: &str) -> Option<(u8, u8, u8)> {
+        let params = style.strip_prefix("\x1b[48;2;")?.strip_suffix('m')?;
+        let channels: Vec<u8> = params.split(';').filter_map(|p| p.parse().ok()).collect();
+        Some((channels[0], channels[1], channels[2]))
+    }
+
+    #[test]
+    fn test_glow_dims_adjacent_cells() {
+        let glyph = colored_glyph(
+            "X",
+            Color::new(200, 100, 50),
+            ColorDepth::TrueColor,
+            Attributes::default(),
+        );
+        let text = format!(" {} \n   \n   ", glyph);
+
+        let glowing = apply_glow(&text, ColorDepth::TrueColor, Color::new(0, 0, 0));
+        let grid: Vec<Vec<buffer::Cell>> = glowing.lines().map(buffer::parse_cells).collect();
+
+        for (x, y) in [(0, 0), (2, 0), (1, 1)] {
+            let (r, g, b) = background(&grid[y][x].style).unwrap();
+            assert!(r < 200 && g < 100 && b < 50);
+            assert!(r > 0);
         }
+
+        // Diagonal and distant cells stay untouched
+        assert!(grid[1][0].style.is_empty());
+        assert!(grid[2][1].style.is_empty());
+        assert_eq!(ansi::strip_ansi(&glowing), ansi::strip_ansi(&text));
+    }
+
+    #[test]
+    fn test_glow_halos_add_up_over_the_background() {
+        let glyph = colored_glyph(
+            "X",
+            Color::new(200, 100, 50),
+            ColorDepth::TrueColor,
+            Attributes::default(),
+        );
+        let text = format!("{glyph} {glyph} ");
+
+        let glowing = apply_glow(&text, ColorDepth::TrueColor, Color::new(0, 0, 0));
+        let cells = buffer::parse_cells(&glowing);
+        // Between the two glyphs the halos meet; past them only one reaches
+        let single = background(&cells[3].style).unwrap();
+        assert_eq!(single, (70, 35, 17));
+        assert_eq!(background(&cells[1].style).unwrap(), (140, 70, 34));
+
+        // Over a lit background the halo brightens it
+        let gray = Color::new(100, 100, 100);
+        let glowing = apply_glow(&text, ColorDepth::TrueColor, gray);
+        let (r, g, b) = background(&buffer::parse_cells(&glowing)[3].style).unwrap();
+        assert!(r > 100 && g > 100 && b > 100);
+        assert!(r > g && g > b);
     }
 
-    result
+    #[test]
+    fn test_attributes_wrap_colored_glyphs() {
+        let red = Color::new(255, 0, 0);
+        let depth = ColorDepth::TrueColor;
+        let underline = Attributes {
+            underline: true,
+            ..Default::default()
+        };
+
+        let glyph = colored_glyph("X", red, depth, underline);
+        assert!(glyph.starts_with("\x1b[4m\x1b[38;2;255;0;0m"), "{glyph:?}");
+
+        let line = colored_line("ab c", &[red], depth, underline);
+        assert!(line.ends_with("c\x1b[39m\x1b[0m"), "{line:?}");
+        assert_eq!(line.matches("\x1b[0m").count(), 1);
+
+        // Every line is reset on its own, and blank lines stay untouched
+        let text = gradient(
+            "ab\n  \ncd",
+            &[red],
+            GradientDirection::Horizontal,
+            depth,
+            underline,
+            false,
+        );
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].ends_with("\x1b[0m"));
+        assert_eq!(lines[1], "  ");
+        assert!(lines[2].ends_with("\x1b[0m"));
+
+        let plain = colored_line("ab", &[red], depth, Attributes::default());
+        assert!(!plain.contains("\x1b[0m"));
+    }
+
+    fn foregrounds(line: &str) -> Vec<String> {
+        line.split("\x1b[38;2;")
+            .skip(1)
+            .map(|s| s.split('m').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_vertical_gradient_runs_top_to_bottom() {
+        let colors = [
+            Color::new(255, 0, 0),
+            Color::new(0, 255, 0),
+            Color::new(0, 0, 255),
+        ];
+        let text = gradient(
+            "abc\nde\nfgh",
+            &colors,
+            GradientDirection::Vertical,
+            ColorDepth::TrueColor,
+            Attributes::default(),
+            false,
+        );
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(foregrounds(lines[0]), ["255;0;0"; 3]);
+        assert_eq!(foregrounds(lines[1]), ["0;255;0"; 2]);
+        assert_eq!(foregrounds(lines[2]), ["0;0;255"; 3]);
+
+        let text = gradient(
+            "abc\ndef",
+            &colors,
+            GradientDirection::Diagonal,
+            ColorDepth::TrueColor,
+            Attributes::default(),
+            false,
+        );
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(foregrounds(lines[0])[0], "255;0;0");
+        assert_eq!(foregrounds(lines[1])[2], "0;0;255");
+    }
+
+    #[test]
+    fn test_conic_gradient_goes_around_the_center() {
+        assert_eq!(conic_position(2, 0, 5, 3, 0.0), 0.0);
+        assert_eq!(conic_position(4, 1, 5, 3, 0.0), 0.25);
+        assert_eq!(conic_position(2, 2, 5, 3, 0.0), 0.5);
+        assert_eq!(conic_position(0, 1, 5, 3, 0.0), 0.75);
+        assert_eq!(conic_position(4, 1, 5, 3, 90.0), 0.0);
+
+        let colors = [Color::new(255, 0, 0), Color::new(0, 0, 255)];
+        let text = gradient(
+            "  x  \nx   x\n  x  ",
+            &colors,
+            GradientDirection::Conic { from_angle: 0.0 },
+            ColorDepth::TrueColor,
+            Attributes::default(),
+            false,
+        );
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(foregrounds(lines[0]), ["255;0;0"]);
+        assert_eq!(foregrounds(lines[2]), ["0;0;255"]);
+    }
+
+    #[test]
+    fn test_dither_breaks_up_bands() {
+        // A gradient too shallow to cross a 256-color cube level on its own
+        let colors: Vec<Color> = (0..8).map(|i| Color::new(108 + i, 0, 0)).collect();
+        let codes = |dither: bool| {
+            let text = by_column(
+                "########",
+                &colors,
+                ColorDepth::Ansi256,
+                Attributes::default(),
+                dither,
+            );
+            text.split("\x1b[38;5;")
+                .skip(1)
+                .map(|s| s.split('m').next().unwrap().to_string())
+                .collect::<std::collections::HashSet<_>>()
+        };
+
+        assert_eq!(codes(false).len(), 1);
+        assert!(codes(true).len() >= 2);
+    }
+
+    #[test]
+    fn test_combining_accent_keeps_its_letter() {
+        // "é" spelled as 'e' followed by U+0301 COMBINING ACUTE ACCENT
+        let red = Color::new(255, 0, 0);
+        let blue = Color::new(0, 0, 255);
+        let text = gradient(
+            "e\u{301}x",
+            &[red, blue],
+            GradientDirection::Horizontal,
+            ColorDepth::TrueColor,
+            Attributes::default(),
+            false,
+        );
+        assert_eq!(
+            text,
+            "\x1b[38;2;255;0;0me\u{301}\x1b[39m\x1b[38;2;0;0;255mx\x1b[39m"
+        );
+        let text = by_column(
+            "e\u{301}x",
+            &[red, blue],
+            ColorDepth::TrueColor,
+            Attributes::default(),
+            false,
+        );
+        assert!(text.ends_with("\x1b[38;2;0;0;255mx\x1b[39m"), "{text:?}");
+    }
+
+    #[test]
+    fn test_recorded_output() {
+        // Byte for byte what was printed before the output was buffered
+        let red = Color::new(255, 0, 0);
+        let blue = Color::new(0, 0, 255);
+        let bold = Attributes {
+            bold: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            gradient(
+                "ab c\n\n de",
+                &[red, blue],
+                GradientDirection::Horizontal,
+                ColorDepth::TrueColor,
+                Attributes::default(),
+                false,
+            ),
+            "\x1b[38;2;255;0;0ma\x1b[39m\x1b[38;2;255;0;0mb\x1b[39m \x1b[38;2;255;0;0mc\x1b[39m\n\n \
+             \x1b[38;2;255;0;0md\x1b[39m\x1b[38;2;0;0;255me\x1b[39m"
+        );
+        assert_eq!(
+            gradient(
+                "ab\ncd",
+                &[red, blue],
+                GradientDirection::Vertical,
+                ColorDepth::Ansi256,
+                bold,
+                false,
+            ),
+            "\x1b[1m\x1b[38;5;196ma\x1b[39m\x1b[1m\x1b[38;5;196mb\x1b[39m\x1b[0m\n\
+             \x1b[1m\x1b[38;5;21mc\x1b[39m\x1b[1m\x1b[38;5;21md\x1b[39m\x1b[0m"
+        );
+        assert_eq!(
+            by_column(
+                "\u{e9}x \u{2588}",
+                &[red, blue, red, blue],
+                ColorDepth::Ansi16,
+                Attributes::default(),
+                false,
+            ),
+            "\x1b[91m\u{e9}\x1b[39m\x1b[34mx\x1b[39m \x1b[34m\u{2588}\x1b[39m"
+        );
+        assert_eq!(
+            colored_line("a b", &[red, blue], ColorDepth::TrueColor, bold),
+            "\x1b[1m\x1b[38;2;255;0;0ma\x1b[39m \x1b[1m\x1b[38;2;255;0;0mb\x1b[39m\x1b[0m"
+        );
+        // Without colors the text is left as it is
+        assert_eq!(
+            gradient(
+                " _ \n|_|",
+                &[],
+                GradientDirection::Diagonal,
+                ColorDepth::TrueColor,
+                bold,
+                true,
+            ),
+            " _ \n|_|"
+        );
+
+        // Appending leaves what is already in the buffer alone
+        let mut out = "kept".to_string();
+        write_gradient_to_text(
+            &mut out,
+            "ab",
+            &[red, blue],
+            GradientDirection::Horizontal,
+            ColorDepth::TrueColor,
+            Attributes::default(),
+            false,
+        );
+        assert!(out.starts_with("kept\x1b[38;2;"));
+    }
 }
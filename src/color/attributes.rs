@@ -0,0 +1,112 @@
+use crossterm::style::{Attribute, SetAttribute};
+
+/// Text styling applied to every glyph on top of its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Attributes {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Attributes {
+    pub fn is_empty(&self) -> bool {
+        !(self.bold || self.dim || self.italic || self.underline)
+    }
+
+    /// SGR sequences turning the attributes on, empty when there are none
+    pub fn sgr(&self) -> String {
+        [
+            (self.bold, Attribute::Bold),
+            (self.dim, Attribute::Dim),
+            (self.italic, Attribute::Italic),
+            (self.underline, Attribute::Underlined),
+        ]
+        .into_iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, attribute)| SetAttribute(attribute).to_string())
+        .collect()
+    }
+
+    /// SGR sequence turning every attribute off again, empty when none are set
+    pub fn reset(&self) -> String {
+        if self.is_empty() {
+            String::new()
+        } else {
+            SetAttribute(Attribute::Reset).to_string()
+        }
+    }
+
+    /// Style each line of uncolored text, resetting at every line end
+    pub fn apply_to_text(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+
+        text.lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.to_string()
+                } else {
+                    format!("{}{}{}", self.sgr(), line, self.reset())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_attribute_has_its_sgr() {
+        let cases = [
+            (
+                Attributes {
+                    bold: true,
+                    ..Default::default()
+                },
+                "\x1b[1m",
+            ),
+            (
+                Attributes {
+                    dim: true,
+                    ..Default::default()
+                },
+                "\x1b[2m",
+            ),
+            (
+                Attributes {
+                    italic: true,
+                    ..Default::default()
+                },
+                "\x1b[3m",
+            ),
+            (
+                Attributes {
+                    underline: true,
+                    ..Default::default()
+                },
+                "\x1b[4m",
+            ),
+        ];
+        for (attributes, sgr) in cases {
+            assert_eq!(attributes.sgr(), sgr);
+            assert_eq!(attributes.reset(), "\x1b[0m");
+            assert_eq!(
+                attributes.apply_to_text("ab\n  "),
+                format!("{sgr}ab\x1b[0m\n  ")
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_attributes_leave_text_alone() {
+        let attributes = Attributes::default();
+        assert!(attributes.sgr().is_empty());
+        assert!(attributes.reset().is_empty());
+        assert_eq!(attributes.apply_to_text("ab"), "ab");
+    }
+}
@@ -0,0 +1,110 @@
+use super::depth::palette_color;
+use crate::parser::color::Color;
+
+/// Highest contrast ratio two colors can have (black on white)
+pub const MAX_CONTRAST: f64 = 21.0;
+
+/// WCAG relative luminance, 0.0 for black to 1.0 for white
+pub fn relative_luminance(color: Color) -> f64 {
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+}
+
+/// WCAG contrast ratio between two colors, from 1.0 (same) to 21.0
+pub fn contrast_ratio(a: Color, b: Color) -> f64 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// `color` lightened (on a dark background) or darkened (on a light one)
+/// just enough to reach `min_ratio` against `background`. Colors that
+/// already stand out are returned unchanged.
+pub fn ensure_contrast(color: Color, background: Color, min_ratio: f64) -> Color {
+    if contrast_ratio(color, background) >= min_ratio {
+        return color;
+    }
+
+    // Whichever of white or black contrasts more with the background is
+    // the direction to move in
+    let direction = if relative_luminance(background) < 0.18 {
+        1.0
+    } else {
+        -1.0
+    };
+
+    // Smallest brightness step that meets the ratio
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..16 {
+        let mid = (low + high) / 2.0;
+        if contrast_ratio(color.adjust_brightness(direction * mid), background) >= min_ratio {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    color.adjust_brightness(direction * high)
+}
+
+/// Background color announced by the terminal in `$COLORFGBG`
+/// (e.g. `15;0`), if any
+pub fn detect_background() -> Option<Color> {
+    background_from_colorfgbg(&std::env::var("COLORFGBG").ok()?)
+}
+
+fn background_from_colorfgbg(value: &str) -> Option<Color> {
+    let index: u8 = value.rsplit(';').next()?.parse().ok()?;
+    (index < 16).then(|| palette_color(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        assert!((contrast_ratio(black, white) - MAX_CONTRAST).abs() < 1e-9);
+        assert!((contrast_ratio(white, black) - MAX_CONTRAST).abs() < 1e-9);
+        assert_eq!(contrast_ratio(white, white), 1.0);
+
+        // #777777 on white is the classic just-below-4.5 gray
+        let ratio = contrast_ratio(Color::new(0x77, 0x77, 0x77), white);
+        assert!((ratio - 4.48).abs() < 0.01, "{ratio}");
+    }
+
+    #[test]
+    fn test_low_contrast_is_lightened() {
+        let background = Color::new(0, 0, 0);
+        let navy = Color::new(20, 20, 90);
+        assert!(contrast_ratio(navy, background) < 4.5);
+
+        let adjusted = ensure_contrast(navy, background, 4.5);
+        assert!(contrast_ratio(adjusted, background) >= 4.5);
+        assert!(relative_luminance(adjusted) > relative_luminance(navy));
+        // Only as far as needed, not all the way to white
+        assert!(contrast_ratio(adjusted, background) < 5.5);
+
+        let white = Color::new(255, 255, 255);
+        assert_eq!(ensure_contrast(white, background, 4.5), white);
+        let on_light = ensure_contrast(Color::new(250, 250, 200), white, 3.0);
+        assert!(contrast_ratio(on_light, white) >= 3.0);
+    }
+
+    #[test]
+    fn test_background_from_colorfgbg() {
+        assert_eq!(background_from_colorfgbg("15;0"), Some(Color::new(0, 0, 0)));
+        assert_eq!(
+            background_from_colorfgbg("0;default;15"),
+            Some(Color::new(255, 255, 255))
+        );
+        assert_eq!(background_from_colorfgbg("default"), None);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::parser::color::Color;
+use anyhow::{bail, Result};
+use std::fmt::Write;
+
+/// Standard xterm values for the 16 basic ANSI colors
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels of the 6x6x6 cube in the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Ordered-dither thresholds (0..16) for a 4x4 tile of cells
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How many colors the output terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Parse `truecolor`, `256`, `16` or `auto` (detected from the environment)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            "256" => Ok(Self::Ansi256),
+            "16" => Ok(Self::Ansi16),
+            "auto" => Ok(Self::detect()),
+            _ => bail!(
+                "Unknown color depth: {} (expected truecolor, 256, 16 or auto)",
+                name
+            ),
+        }
+    }
+
+    /// Guess the depth from `$COLORTERM` and `$TERM`
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        Self::from_env_values(&colorterm, &term)
+    }
+
+    fn from_env_values(colorterm: &str, term: &str) -> Self {
+        if colorterm == "truecolor" || colorterm == "24bit" || term.contains("truecolor") {
+            Self::TrueColor
+        } else if term.contains("256") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Nudge `color` by the Bayer threshold of cell (x, y) before it is
+    /// quantized, so neighbouring cells of a smooth gradient alternate
+    /// between the nearest palette entries instead of forming bands
+    pub fn dither(&self, color: Color, x: usize, y: usize) -> Color {
+        // Roughly the gap between neighbouring levels of the palette
+        let spread = match self {
+            Self::TrueColor => return color,
+            Self::Ansi256 => 40.0,
+            Self::Ansi16 => 128.0,
+        };
+        let threshold = (BAYER_4X4[y % 4][x % 4] as f64 + 0.5) / 16.0 - 0.5;
+        let nudge = |c: u8| (c as f64 + threshold * spread).round().clamp(0.0, 255.0) as u8;
+        Color::new(nudge(color.r), nudge(color.g), nudge(color.b))
+    }
+
+    /// SGR sequence that sets `color` as the foreground at this depth
+    pub fn foreground(&self, color: Color) -> String {
+        let mut sgr = String::new();
+        self.write_foreground(&mut sgr, color);
+        sgr
+    }
+
+    /// Append the SGR sequence from `foreground` to `out`
+    pub fn write_foreground(&self, out: &mut String, color: Color) {
+        // Writing into a String can't fail
+        let _ = match self {
+            Self::TrueColor => write!(out, "\x1b[38;2;{};{};{}m", color.r, color.g, color.b),
+            Self::Ansi256 => write!(out, "\x1b[38;5;{}m", quantize_256(color)),
+            Self::Ansi16 => {
+                let index = quantize_16(color);
+                let code = if index < 8 {
+                    30 + index
+                } else {
+                    90 + index - 8
+                };
+                write!(out, "\x1b[{}m", code)
+            }
+        };
+    }
+
+    /// SGR sequence that sets `color` as the background at this depth
+    pub fn background(&self, color: Color) -> String {
+        match self {
+            Self::TrueColor => format!("\x1b[48;2;{};{};{}m", color.r, color.g, color.b),
+            Self::Ansi256 => format!("\x1b[48;5;{}m", quantize_256(color)),
+            Self::Ansi16 => {
+                let index = quantize_16(color);
+                let code = if index < 8 {
+                    40 + index
+                } else {
+                    100 + index - 8
+                };
+                format!("\x1b[{}m", code)
+            }
+        }
+    }
+}
+
+/// Nearest of the 16 basic ANSI colors
+pub fn quantize_16(color: Color) -> u8 {
+    (0..16u8)
+        .min_by_key(|&i| distance(color, palette_color(i)))
+        .unwrap_or(0)
+}
+
+/// Nearest entry of the 256-color palette's color cube or gray ramp
+pub fn quantize_256(color: Color) -> u8 {
+    let level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = 16 + 36 * r + 6 * g + b;
+
+    let average = (color.r as u32 + color.g as u32 + color.b as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(3)) / 10).min(23) as u8;
+
+    if distance(color, palette_color(gray)) < distance(color, palette_color(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// RGB value of a 256-color palette index
+pub fn palette_color(index: u8) -> Color {
+    match index {
+        0..=15 => {
+            let (r, g, b) = ANSI_16[index as usize];
+            Color::new(r, g, b)
+        }
+        16..=231 => {
+            let i = index - 16;
+            Color::new(
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (index - 232) * 10;
+            Color::new(v, v, v)
+        }
+    }
+}
+
+fn distance(a: Color, b: Color) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.r, b.r) + d(a.g, b.g) + d(a.b, b.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_red_quantizes_to_bright_red() {
+        let red = Color::new(255, 0, 0);
+        assert_eq!(quantize_16(red), 9);
+        assert_eq!(quantize_256(red), 196);
+        assert_eq!(ColorDepth::Ansi16.foreground(red), "\x1b[91m");
+        assert_eq!(ColorDepth::Ansi256.foreground(red), "\x1b[38;5;196m");
+    }
+
+    #[test]
+    fn test_dither_only_touches_reduced_depths() {
+        let color = Color::new(115, 0, 0);
+        let same = ColorDepth::TrueColor.dither(color, 1, 2);
+        assert_eq!((same.r, same.g, same.b), (115, 0, 0));
+
+        let levels: std::collections::HashSet<u8> = (0..4)
+            .map(|x| quantize_256(ColorDepth::Ansi256.dither(color, x, 0)))
+            .collect();
+        assert!(levels.len() >= 2);
+    }
+
+    #[test]
+    fn test_grays_use_gray_ramp() {
+        assert_eq!(quantize_256(Color::new(128, 128, 128)), 244);
+        assert_eq!(palette_color(244).r, 128);
+    }
+
+    #[test]
+    fn test_detect_from_env_values() {
+        assert_eq!(
+            ColorDepth::from_env_values("truecolor", "xterm"),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env_values("", "xterm-256color"),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(ColorDepth::from_env_values("", "xterm"), ColorDepth::Ansi16);
+    }
+}
@@ -1,15 +1,25 @@
 use crate::parser::color::Color;
-use crate::parser::gradient::Gradient;
-use anyhow::Result;
+use crate::parser::gradient::{Gradient, GradientKind, InterpolationSpace};
+use anyhow::{bail, Result};
 
+/// Entries in a gradient's lookup table unless `with_resolution` says otherwise
+pub const DEFAULT_RESOLUTION: usize = 256;
+/// Most entries a lookup table may have; with 8-bit channels, more could
+/// not tell any two neighbouring colors apart
+pub const MAX_RESOLUTION: usize = 4096;
+
+/// A gradient sampled through a lookup table, so coloring a frame doesn't
+/// search the stops again for every glyph
 #[derive(Debug, Clone)]
 pub struct GradientEngine {
     gradient: Gradient,
+    lut: Vec<Color>,
 }
 
 impl GradientEngine {
     pub fn new(gradient: Gradient) -> Self {
-        Self { gradient }
+        let lut = gradient.colors(DEFAULT_RESOLUTION);
+        Self { gradient, lut }
     }
 
     pub fn from_string(gradient_str: &str) -> Result<Self> {
@@ -17,11 +27,123 @@ impl GradientEngine {
         Ok(Self::new(gradient))
     }
 
+    pub fn with_interpolation(mut self, space: InterpolationSpace) -> Self {
+        self.gradient = self.gradient.with_interpolation(space);
+        self.rebuild()
+    }
+
+    /// Sample the gradient into `resolution` table entries; more entries
+    /// follow sharp transitions more closely
+    pub fn with_resolution(mut self, resolution: usize) -> Result<Self> {
+        check_resolution(resolution)?;
+        self.lut = self.gradient.colors(resolution);
+        Ok(self)
+    }
+
+    pub fn reverse(mut self) -> Self {
+        self.gradient = self.gradient.reverse();
+        self.rebuild()
+    }
+
+    /// Resample the table after the gradient changed, keeping its size
+    fn rebuild(mut self) -> Self {
+        self.lut = self.gradient.colors(self.lut.len());
+        self
+    }
+
+    pub fn kind(&self) -> GradientKind {
+        self.gradient.kind
+    }
+
+    #[allow(dead_code)]
+    pub fn resolution(&self) -> usize {
+        self.lut.len()
+    }
+
+    /// Color at `t` (0.0-1.0), blended from the two nearest table entries
     pub fn color_at(&self, t: f64) -> Color {
-        self.gradient.color_at(t)
+        let position = t.clamp(0.0, 1.0) * (self.lut.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.lut.len() - 2);
+        self.lut[index].interpolate(&self.lut[index + 1], position - index as f64)
     }
 
     pub fn colors(&self, steps: usize) -> Vec<Color> {
-        self.gradient.colors(steps)
+        (0..steps)
+            .map(|i| self.color_at(i as f64 / (steps - 1).max(1) as f64))
+            .collect()
+    }
+}
+
+/// A lookup table needs both ends of the gradient, and is kept to a size
+/// that is cheap to build
+pub fn check_resolution(resolution: usize) -> Result<()> {
+    if resolution < 2 {
+        bail!("Gradient resolution must be at least 2, got {}", resolution);
+    }
+    if resolution > MAX_RESOLUTION {
+        bail!(
+            "Gradient resolution must be at most {}, got {}",
+            MAX_RESOLUTION,
+            resolution
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: Color, b: Color) -> u8 {
+        [
+            a.r.abs_diff(b.r),
+            a.g.abs_diff(b.g),
+            a.b.abs_diff(b.b),
+            a.a.abs_diff(b.a),
+        ]
+        .into_iter()
+        .max()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lookup_table_matches_direct_sampling() {
+        for definition in [
+            "linear-gradient(90deg, #ff5733, #33ff57, #3357ff)",
+            "linear-gradient(90deg, red, 20%, blue 60%, rgba(255,255,0,0.5))",
+            "linear-gradient(in oklab, black, white)",
+        ] {
+            let gradient = Gradient::parse(definition).unwrap();
+            let engine = GradientEngine::new(gradient.clone());
+            for i in 0..=1000 {
+                let t = i as f64 / 1000.0;
+                let (lut, direct) = (engine.color_at(t), gradient.color_at(t));
+                assert!(
+                    distance(lut, direct) <= 3,
+                    "{definition} at {t}: {lut:?} {direct:?}"
+                );
+            }
+            // The ends are table entries, so they match exactly
+            assert_eq!(engine.color_at(0.0), gradient.color_at(0.0));
+            assert_eq!(engine.color_at(1.0), gradient.color_at(1.0));
+        }
+    }
+
+    #[test]
+    fn test_resolution() {
+        let engine = GradientEngine::from_string("linear-gradient(red, blue)").unwrap();
+        assert_eq!(engine.resolution(), DEFAULT_RESOLUTION);
+        assert_eq!(engine.clone().reverse().resolution(), DEFAULT_RESOLUTION);
+
+        let coarse = engine.with_resolution(2).unwrap();
+        assert_eq!(coarse.colors(3)[1], Color::new(127, 0, 127));
+        // Resampling after a change keeps the chosen size
+        assert_eq!(coarse.reverse().resolution(), 2);
+
+        let engine = GradientEngine::from_string("linear-gradient(red, blue)").unwrap();
+        assert!(engine.clone().with_resolution(1).is_err());
+        assert!(engine.clone().with_resolution(0).is_err());
+        assert!(engine.clone().with_resolution(MAX_RESOLUTION + 1).is_err());
+        assert!(engine.with_resolution(MAX_RESOLUTION).is_ok());
     }
 }
@@ -1,9 +1,15 @@
 pub mod apply;
+pub mod attributes;
+pub mod contrast;
+pub mod depth;
 pub mod gradient;
 pub mod palette;
 
 use crate::parser::color::Color;
-use anyhow::Result;
+use crate::parser::gradient::{GradientKind, InterpolationSpace};
+use anyhow::{bail, Result};
+pub use attributes::Attributes;
+pub use depth::ColorDepth;
 pub use gradient::GradientEngine;
 pub use palette::ColorPalette;
 
@@ -14,19 +20,105 @@ pub enum ColorMode {
     Gradient(GradientEngine),
 }
 
+/// How colors are spread over the glyphs of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMapping {
+    /// Let the effect decide (one color per frame for most effects)
+    #[default]
+    Auto,
+    /// Color every glyph by its column, whatever the effect
+    PerChar,
+}
+
+impl ColorMapping {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "auto" => Ok(Self::Auto),
+            "per-char" => Ok(Self::PerChar),
+            _ => bail!("Unknown color mode: {} (expected auto or per-char)", name),
+        }
+    }
+}
+
+/// Which way a gradient runs across the art
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GradientDirection {
+    /// Along the glyphs in reading order
+    #[default]
+    Horizontal,
+    /// From the top row to the bottom row
+    Vertical,
+    /// From the top-left corner to the bottom-right corner
+    Diagonal,
+    /// Clockwise around the center of the art, starting `from_angle`
+    /// degrees clockwise from the top; used by conic gradients
+    Conic { from_angle: f64 },
+}
+
+impl GradientDirection {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            "diagonal" => Ok(Self::Diagonal),
+            _ => bail!(
+                "Unknown gradient direction: {} (expected horizontal, vertical or diagonal)",
+                name
+            ),
+        }
+    }
+}
+
 pub struct ColorEngine {
     mode: ColorMode,
+    mapping: ColorMapping,
+    direction: GradientDirection,
+    depth: ColorDepth,
+    enabled: bool,
+    glow: bool,
+    dither: bool,
+    brightness: f64,
+    saturation: f64,
+    background: Color,
+    min_contrast: Option<f64>,
+    attributes: Attributes,
 }
 
 impl ColorEngine {
     pub fn new() -> Self {
         Self {
             mode: ColorMode::None,
+            mapping: ColorMapping::default(),
+            direction: GradientDirection::default(),
+            depth: ColorDepth::default(),
+            enabled: true,
+            glow: false,
+            dither: false,
+            brightness: 0.0,
+            saturation: 0.0,
+            background: Color::new(0, 0, 0),
+            min_contrast: None,
+            attributes: Attributes::default(),
         }
     }
 
+    /// Like `new`, but with color turned off when `NO_COLOR` is set to a
+    /// non-empty value (see https://no-color.org)
+    pub fn from_env() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let mut engine = Self::new();
+        engine.enabled = !no_color;
+        engine
+    }
+
+    /// Whether any color output is allowed, including the hue wheel of
+    /// color effects
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
     pub fn with_palette(mut self, palette: Option<&[String]>) -> Result<Self> {
-        if let Some(colors) = palette {
+        if let Some(colors) = palette.filter(|_| self.enabled) {
             if !colors.is_empty() {
                 let palette = ColorPalette::from_strings(colors)?;
                 self.mode = ColorMode::Palette(palette);
@@ -36,17 +128,183 @@ impl ColorEngine {
     }
 
     pub fn with_gradient(mut self, gradient: Option<&str>) -> Result<Self> {
-        if let Some(gradient_str) = gradient {
+        if let Some(gradient_str) = gradient.filter(|_| self.enabled) {
             let gradient = GradientEngine::from_string(gradient_str)?;
             self.mode = ColorMode::Gradient(gradient);
         }
         Ok(self)
     }
 
+    /// Override how gradient stops are blended; has no effect on palettes
+    pub fn with_interpolation(mut self, space: Option<&str>) -> Result<Self> {
+        if let Some(space) = space {
+            let space = InterpolationSpace::parse(space)?;
+            if let ColorMode::Gradient(gradient) = self.mode {
+                self.mode = ColorMode::Gradient(gradient.with_interpolation(space));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Number of entries in the gradient's lookup table; has no effect on
+    /// palettes
+    pub fn with_gradient_resolution(mut self, resolution: Option<usize>) -> Result<Self> {
+        if let Some(resolution) = resolution {
+            gradient::check_resolution(resolution)?;
+            if let ColorMode::Gradient(gradient) = self.mode {
+                self.mode = ColorMode::Gradient(gradient.with_resolution(resolution)?);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Run the gradient, or the palette, the other way round
+    pub fn with_reversed(mut self, reversed: bool) -> Self {
+        if reversed {
+            self.mode = match self.mode {
+                ColorMode::Palette(palette) => ColorMode::Palette(palette.reverse()),
+                ColorMode::Gradient(gradient) => ColorMode::Gradient(gradient.reverse()),
+                ColorMode::None => ColorMode::None,
+            };
+        }
+        self
+    }
+
+    pub fn with_mapping(mut self, mapping: &str) -> Result<Self> {
+        self.mapping = ColorMapping::parse(mapping)?;
+        Ok(self)
+    }
+
+    pub fn mapping(&self) -> ColorMapping {
+        self.mapping
+    }
+
+    pub fn with_direction(mut self, direction: &str) -> Result<Self> {
+        self.direction = GradientDirection::parse(direction)?;
+        Ok(self)
+    }
+
+    /// How gradients run across the art; conic gradients always go around
+    /// its center
+    pub fn direction(&self) -> GradientDirection {
+        match &self.mode {
+            ColorMode::Gradient(gradient) => match gradient.kind() {
+                GradientKind::Conic { from_angle } => GradientDirection::Conic { from_angle },
+                GradientKind::Linear => self.direction,
+            },
+            _ => self.direction,
+        }
+    }
+
+    pub fn with_depth(mut self, depth: ColorDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn depth(&self) -> ColorDepth {
+        self.depth
+    }
+
+    /// Surround colored glyphs with a dimmed halo
+    pub fn with_glow(mut self, glow: bool) -> Self {
+        self.glow = glow;
+        self
+    }
+
+    pub fn glow(&self) -> bool {
+        self.glow
+    }
+
+    /// Break up the bands a gradient shows once its colors are reduced to
+    /// a 256 or 16 color depth, by nudging each cell's color with an
+    /// ordered 4x4 pattern before it is quantized. No effect in true color.
+    pub fn with_dither(mut self, dither: bool) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    pub fn dither(&self) -> bool {
+        self.dither
+    }
+
+    /// Lighten (up to 1.0) or darken (down to -1.0) every color drawn
+    pub fn with_brightness(mut self, brightness: f64) -> Result<Self> {
+        self.brightness = adjustment("Brightness", brightness)?;
+        Ok(self)
+    }
+
+    /// Saturate (up to 1.0) or desaturate (down to -1.0) every color drawn
+    pub fn with_saturation(mut self, saturation: f64) -> Result<Self> {
+        self.saturation = adjustment("Saturation", saturation)?;
+        Ok(self)
+    }
+
+    /// The terminal background colors are drawn on, and translucent colors
+    /// are composited over: `background` when given, otherwise what
+    /// `$COLORFGBG` says, otherwise black
+    pub fn with_background(mut self, background: Option<&str>) -> Result<Self> {
+        self.background = match background {
+            Some(background) => Color::parse(background)?,
+            None => contrast::detect_background().unwrap_or(self.background),
+        };
+        Ok(self)
+    }
+
+    #[allow(dead_code)]
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Lighten or darken glyphs until they reach this WCAG contrast ratio
+    /// against the background
+    pub fn with_min_contrast(mut self, ratio: Option<f64>) -> Result<Self> {
+        if let Some(ratio) = ratio {
+            if !(1.0..=contrast::MAX_CONTRAST).contains(&ratio) {
+                bail!("Minimum contrast must be between 1 and 21, got {}", ratio);
+            }
+        }
+        self.min_contrast = ratio;
+        Ok(self)
+    }
+
+    /// `color` composited over the background, with the brightness,
+    /// saturation and contrast adjustments applied
+    pub fn adjust(&self, color: Color) -> Color {
+        let mut color = color.over(self.background);
+        if self.brightness != 0.0 {
+            color = color.adjust_brightness(self.brightness);
+        }
+        if self.saturation != 0.0 {
+            color = color.adjust_saturation(self.saturation);
+        }
+        if let Some(ratio) = self.min_contrast {
+            color = contrast::ensure_contrast(color, self.background, ratio);
+        }
+        color
+    }
+
+    /// Bold, dim, italic or underline every glyph, colored or not
+    pub fn with_attributes(mut self, attributes: Attributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    pub fn attributes(&self) -> Attributes {
+        self.attributes
+    }
+
     pub fn has_colors(&self) -> bool {
         !matches!(self.mode, ColorMode::None)
     }
 
+    /// The user palette, when colors were given as one
+    pub fn palette(&self) -> Option<&ColorPalette> {
+        match &self.mode {
+            ColorMode::Palette(palette) => Some(palette),
+            _ => None,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn get_color(&self, t: f64, index: usize) -> Option<Color> {
         match &self.mode {
@@ -68,14 +326,19 @@ impl ColorEngine {
     pub fn color_at(&self, t: f64) -> Option<Color> {
         match &self.mode {
             ColorMode::None => None,
-            ColorMode::Palette(palette) => {
-                Some(palette.get_color((t * palette.len() as f64) as usize))
-            }
+            ColorMode::Palette(palette) => Some(palette.get_color_interpolated(t)),
             ColorMode::Gradient(gradient) => Some(gradient.color_at(t)),
         }
     }
 }
 
+fn adjustment(name: &str, amount: f64) -> Result<f64> {
+    if !(-1.0..=1.0).contains(&amount) {
+        bail!("{} must be between -1.0 and 1.0, got {}", name, amount);
+    }
+    Ok(amount)
+}
+
 impl Default for ColorEngine {
     fn default() -> Self {
         Self::new()
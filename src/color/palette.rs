@@ -23,6 +23,27 @@ impl ColorPalette {
         self.colors[index % self.colors.len()]
     }
 
+    /// Blend between adjacent palette entries, with `t` spanning the whole
+    /// palette from the first color (0.0) to the last (1.0)
+    pub fn get_color_interpolated(&self, t: f64) -> Color {
+        if self.colors.len() < 2 {
+            return self.get_color(0);
+        }
+
+        let position = t.clamp(0.0, 1.0) * (self.colors.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.colors.len() - 2);
+        let local_t = position - index as f64;
+
+        self.colors[index].interpolate(&self.colors[index + 1], local_t)
+    }
+
+    /// The same colors in the opposite order
+    pub fn reverse(mut self) -> Self {
+        self.colors.reverse();
+        self
+    }
+
+    #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.colors.len()
     }
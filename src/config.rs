@@ -0,0 +1,313 @@
+use crate::cli::PigletCli;
+use anyhow::{bail, Context, Result};
+use clap::{parser::ValueSource, ArgMatches};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Project config file, looked up from the current directory upwards
+pub const PROJECT_CONFIG: &str = ".pigletrc";
+
+/// Named sets of options loaded from `~/.config/piglet/presets.toml`:
+///
+/// ```toml
+/// [neon]
+/// effect = "fade-in"
+/// easing = "ease-out"
+/// duration = "2s"
+/// palette = ["#ff00ff", "#00ffff"]
+/// fps = 60
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Presets {
+    presets: BTreeMap<String, Preset>,
+}
+
+/// Options a preset or a `.pigletrc` can set; any left out keep their usual
+/// defaults
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub effect: Option<String>,
+    pub easing: Option<String>,
+    pub duration: Option<String>,
+    pub palette: Option<Vec<String>>,
+    pub gradient: Option<String>,
+    pub fps: Option<u32>,
+    pub font: Option<String>,
+    pub figlet_args: Option<Vec<String>>,
+}
+
+impl Presets {
+    pub fn parse(source: &str) -> Result<Self> {
+        toml::from_str(source).context("Failed to parse presets")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read presets from {}", path.display()))?;
+        Self::parse(&source).with_context(|| format!("Invalid presets file {}", path.display()))
+    }
+
+    /// `$XDG_CONFIG_HOME/piglet/presets.toml`, falling back to
+    /// `~/.config/piglet/presets.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("piglet").join("presets.toml"))
+    }
+
+    /// Presets from the default path; none when the file doesn't exist
+    pub fn load_from_default_path() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.keys().map(String::as_str).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Preset> {
+        match self.presets.get(name) {
+            Some(preset) => Ok(preset),
+            None if self.presets.is_empty() => bail!(
+                "Unknown preset '{}'. No presets are defined{}",
+                name,
+                Self::default_path()
+                    .map(|path| format!(" in {}", path.display()))
+                    .unwrap_or_default()
+            ),
+            None => bail!(
+                "Unknown preset '{}'. Available presets: {}",
+                name,
+                self.names().join(", ")
+            ),
+        }
+    }
+}
+
+impl Preset {
+    /// Parse a `.pigletrc`: the same keys as a preset, at the top level
+    pub fn parse(source: &str) -> Result<Self> {
+        toml::from_str(source).context("Failed to parse config")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config from {}", path.display()))?;
+        Self::parse(&source).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// The `.pigletrc` closest to `dir`, in it or one of its parents, so a
+    /// project's defaults also apply in its subdirectories
+    pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG))
+            .find(|path| path.is_file())
+    }
+
+    /// The `.pigletrc` for the current directory; empty when there is none
+    pub fn load_project_config() -> Result<Self> {
+        let dir = std::env::current_dir().context("Failed to read the current directory")?;
+        match Self::find_project_config(&dir) {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// These options, with any left out taken from `fallback`. Colors are
+    /// taken as a whole: a palette here drops the fallback's gradient too.
+    pub fn or(self, fallback: Preset) -> Preset {
+        let (palette, gradient) = if self.palette.is_some() || self.gradient.is_some() {
+            (self.palette, self.gradient)
+        } else {
+            (fallback.palette, fallback.gradient)
+        };
+        Preset {
+            effect: self.effect.or(fallback.effect),
+            easing: self.easing.or(fallback.easing),
+            duration: self.duration.or(fallback.duration),
+            palette,
+            gradient,
+            fps: self.fps.or(fallback.fps),
+            font: self.font.or(fallback.font),
+            figlet_args: self.figlet_args.or(fallback.figlet_args),
+        }
+    }
+
+    /// Fill in every option the user didn't pass on the command line
+    pub fn apply(&self, args: &mut PigletCli, matches: &ArgMatches) {
+        let unset = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+        if let Some(effect) = self.effect.as_ref().filter(|_| unset("motion_effect")) {
+            args.motion_effect = effect.clone();
+        }
+        if let Some(easing) = self.easing.as_ref().filter(|_| unset("motion_ease")) {
+            args.motion_ease = Some(easing.clone());
+        }
+        if let Some(duration) = self.duration.as_ref().filter(|_| unset("duration")) {
+            args.duration = duration.clone();
+        }
+        // Palette and gradient are one setting: colors chosen on the command
+        // line, either way, replace the preset's
+        if unset("color_palette") && unset("color_gradient") {
+            if let Some(palette) = &self.palette {
+                args.color_palette = Some(palette.clone());
+            }
+            if let Some(gradient) = &self.gradient {
+                args.color_gradient = Some(gradient.clone());
+            }
+        }
+        if let Some(fps) = self.fps.filter(|_| unset("fps")) {
+            args.fps = fps;
+        }
+        if let Some(font) = self.font.as_ref().filter(|_| unset("font")) {
+            args.font = Some(font.clone());
+        }
+        if let Some(figlet_args) = self.figlet_args.as_ref().filter(|_| unset("figlet_args")) {
+            args.figlet_args = figlet_args.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    const SAMPLE: &str = r##"
+[neon]
+effect = "glitch"
+easing = "linear"
+duration = "2s"
+palette = ["#ff00ff", "cyan"]
+fps = 60
+
+[calm]
+gradient = "linear-gradient(90deg, navy, teal)"
+"##;
+
+    #[test]
+    fn test_parse_presets() {
+        let presets = Presets::parse(SAMPLE).unwrap();
+        assert_eq!(presets.names(), vec!["calm", "neon"]);
+
+        let neon = presets.get("neon").unwrap();
+        assert_eq!(neon.effect.as_deref(), Some("glitch"));
+        assert_eq!(neon.fps, Some(60));
+        assert_eq!(
+            neon.palette,
+            Some(vec!["#ff00ff".to_string(), "cyan".to_string()])
+        );
+        assert_eq!(presets.get("calm").unwrap().effect, None);
+
+        let err = presets.get("loud").unwrap_err().to_string();
+        assert!(err.contains("calm, neon"), "{err}");
+        assert!(Presets::parse("[neon]\ncolour = \"red\"").is_err());
+    }
+
+    #[test]
+    fn test_cli_flags_override_preset() {
+        let preset = Presets::parse(SAMPLE).unwrap().get("neon").unwrap().clone();
+        let matches = PigletCli::command()
+            .try_get_matches_from(["piglet", "Hi", "--fps", "24", "--motion-effect", "wave"])
+            .unwrap();
+        let mut args = PigletCli::from_arg_matches(&matches).unwrap();
+
+        preset.apply(&mut args, &matches);
+
+        assert_eq!(args.fps, 24);
+        assert_eq!(args.motion_effect, "wave");
+        assert_eq!(args.duration, "2s");
+        assert_eq!(args.motion_ease.as_deref(), Some("linear"));
+        assert_eq!(args.color_palette.map(|p| p.len()), Some(2));
+    }
+
+    #[test]
+    fn test_cli_colors_replace_preset_colors() {
+        let preset = Preset::parse("gradient = \"linear-gradient(navy, teal)\"").unwrap();
+        let matches = PigletCli::command()
+            .try_get_matches_from(["piglet", "Hi", "--color-palette", "red,blue"])
+            .unwrap();
+        let mut args = PigletCli::from_arg_matches(&matches).unwrap();
+
+        preset.apply(&mut args, &matches);
+
+        assert_eq!(args.color_palette.map(|p| p.len()), Some(2));
+        assert_eq!(args.color_gradient, None);
+
+        // And the other way around
+        let preset = Presets::parse(SAMPLE).unwrap().get("neon").unwrap().clone();
+        let matches = PigletCli::command()
+            .try_get_matches_from([
+                "piglet",
+                "Hi",
+                "--color-gradient",
+                "linear-gradient(red, blue)",
+            ])
+            .unwrap();
+        let mut args = PigletCli::from_arg_matches(&matches).unwrap();
+
+        preset.apply(&mut args, &matches);
+
+        assert_eq!(args.color_palette, None);
+        assert!(args.color_gradient.is_some());
+    }
+
+    #[test]
+    fn test_find_project_config_in_parents() {
+        let root = std::env::temp_dir().join(format!("piglet-rc-{}", std::process::id()));
+        let nested = root.join("src").join("bin");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(Preset::find_project_config(&nested), None);
+
+        let rc = root.join(PROJECT_CONFIG);
+        std::fs::write(&rc, "font = \"slant\"\nfiglet_args = [\"-w\", \"120\"]\n").unwrap();
+        assert_eq!(Preset::find_project_config(&nested), Some(rc.clone()));
+
+        let config = Preset::load(&rc).unwrap();
+        assert_eq!(config.font.as_deref(), Some("slant"));
+        assert_eq!(config.figlet_args.map(|a| a.len()), Some(2));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_cli_then_project_config_then_preset() {
+        let project = Preset::parse("font = \"slant\"\nfps = 24\neffect = \"wave\"").unwrap();
+        let preset = Presets::parse(SAMPLE).unwrap().get("neon").unwrap().clone();
+        let matches = PigletCli::command()
+            .try_get_matches_from(["piglet", "Hi", "--motion-effect", "typewriter"])
+            .unwrap();
+        let mut args = PigletCli::from_arg_matches(&matches).unwrap();
+
+        project.or(preset).apply(&mut args, &matches);
+
+        assert_eq!(args.motion_effect, "typewriter");
+        assert_eq!(args.font.as_deref(), Some("slant"));
+        assert_eq!(args.fps, 24);
+        assert_eq!(args.duration, "2s");
+        assert!(args.figlet_args.is_empty());
+    }
+
+    #[test]
+    fn test_project_colors_replace_preset_colors() {
+        let project = Preset::parse("palette = [\"red\", \"blue\"]").unwrap();
+        let preset = Preset::parse("gradient = \"linear-gradient(navy, teal)\"").unwrap();
+        let merged = project.or(preset.clone());
+        assert_eq!(merged.palette.map(|p| p.len()), Some(2));
+        assert_eq!(merged.gradient, None);
+
+        // Without colors of its own the project keeps the preset's
+        let merged = Preset::parse("fps = 24").unwrap().or(preset);
+        assert!(merged.gradient.is_some());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::animation::{AnimationEngine, Frame};
+use crate::utils::buffer::FrameBuffer;
+use anyhow::{Context, Result};
+use crossterm::terminal;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Size used when the current terminal size can't be queried
+const FALLBACK_SIZE: (u16, u16) = (80, 24);
+/// Clear the screen and hide the cursor before the first frame
+const PREAMBLE: &str = "\x1b[2J\x1b[?25l";
+
+/// Write the animation as an asciinema v2 cast sized to the current terminal
+pub fn export_asciicast(engine: &AnimationEngine, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let (width, height) = terminal::size().unwrap_or(FALLBACK_SIZE);
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create cast at {}", path.display()))?;
+
+    let mut writer = BufWriter::new(file);
+    write_asciicast(engine, width, height, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a header line followed by one `[time, "o", data]` event per frame,
+/// where `data` is exactly what the diff renderer would emit for that frame
+pub fn write_asciicast<W: Write>(
+    engine: &AnimationEngine,
+    width: u16,
+    height: u16,
+    writer: &mut W,
+) -> Result<()> {
+    let header = json!({
+        "version": 2,
+        "width": width,
+        "height": height,
+        "env": { "TERM": "xterm-256color" },
+    });
+    writeln!(writer, "{}", header)?;
+
+    let renderer = engine.renderer();
+    let frame_seconds = 1.0 / engine.fps().max(1) as f64;
+    let mut previous: Option<FrameBuffer> = None;
+
+    for Frame {
+        index: frame_index,
+        effect: effect_result,
+        text: colored_text,
+        ..
+    } in engine.frames()
+    {
+        let frame = renderer.compose(&effect_result, &colored_text, width, height);
+
+        let mut data = Vec::new();
+        if previous.is_none() {
+            data.extend_from_slice(PREAMBLE.as_bytes());
+        }
+        frame.diff(previous.as_ref(), &mut data)?;
+        previous = Some(frame);
+
+        let timestamp = frame_index as f64 * frame_seconds;
+        let event = json!([timestamp, "o", String::from_utf8_lossy(&data)]);
+        writeln!(writer, "{}", event)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cast_header_and_timestamps() {
+        let engine = AnimationEngine::new(" _ \n|_|".to_string(), Duration::from_millis(1000), 10)
+            .with_effect("slide-in-left")
+            .unwrap();
+
+        let mut output = Vec::new();
+        write_asciicast(&engine, 100, 30, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+
+        let header: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 100);
+        assert_eq!(header["height"], 30);
+
+        let events: Vec<Value> = lines.map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(events.len(), 11);
+
+        for pair in events.windows(2) {
+            let delta = pair[1][0].as_f64().unwrap() - pair[0][0].as_f64().unwrap();
+            assert!((delta - 0.1).abs() < 1e-6);
+            assert_eq!(pair[1][1], "o");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::animation::{AnimationEngine, Frame};
+use crate::parser::color::Color;
+use crate::utils::{ansi, buffer::FrameBuffer};
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Pixel size of a glyph pixel, so the 5x7 font stays legible
+const PIXEL_SCALE: usize = 2;
+/// One blank pixel column/row between glyph cells
+const CELL_WIDTH: usize = (GLYPH_WIDTH + 1) * PIXEL_SCALE;
+const CELL_HEIGHT: usize = (GLYPH_HEIGHT + 1) * PIXEL_SCALE;
+const PADDING_CELLS: usize = 4;
+/// NeuQuant speed used when reducing frames to a 256-color palette
+const QUANTIZE_SPEED: i32 = 10;
+/// Shortest frame delay browsers honor, in hundredths of a second
+const MIN_DELAY: u16 = 2;
+const FOREGROUND: Color = Color::new(255, 255, 255);
+const BACKGROUND: Color = Color::new(0, 0, 0);
+
+/// Write the animation as an animated GIF file
+pub fn export_gif(engine: &AnimationEngine, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create GIF at {}", path.display()))?;
+    write_gif(engine, file)
+}
+
+/// Rasterize every timeline frame and encode them as a looping GIF
+pub fn write_gif<W: Write>(engine: &AnimationEngine, writer: W) -> Result<()> {
+    let art = engine.ascii_art();
+    let cols = (art.width() + PADDING_CELLS * 2) as u16;
+    let rows = (art.height() + PADDING_CELLS * 2) as u16;
+    let width = cols as usize * CELL_WIDTH;
+    let height = rows as usize * CELL_HEIGHT;
+
+    let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &[])
+        .context("Failed to start GIF encoder")?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .context("Failed to set GIF looping")?;
+
+    let mut write_frame = |frame: &Frame, delay: u16| -> Result<()> {
+        let mut grid = FrameBuffer::new(cols, rows);
+        grid.draw_frame(&frame.text, frame.effect.offset_x, frame.effect.offset_y);
+
+        let pixels = rasterize(&grid, width, height);
+        let mut gif_frame =
+            gif::Frame::from_rgb_speed(width as u16, height as u16, &pixels, QUANTIZE_SPEED);
+        gif_frame.delay = delay;
+        encoder
+            .write_frame(&gif_frame)
+            .context("Failed to write GIF frame")
+    };
+
+    // GIF delays are whole hundredths of a second, so each frame is placed
+    // at its rounded start time rather than given a rounded delay, which
+    // would drift. Frames starting less than MIN_DELAY after the one on
+    // screen are dropped: browsers slow shorter delays down to 10cs.
+    let fps = engine.fps().max(1) as u64;
+    let start_of = |index: usize| ((index as u64 * 100 + fps / 2) / fps) as u16;
+    let mut shown: Option<(Frame, u16)> = None;
+
+    for frame in engine.frames() {
+        let start = start_of(frame.index);
+        match shown.take() {
+            Some((previous, previous_start)) if start < previous_start + MIN_DELAY => {
+                shown = Some((previous, previous_start));
+            }
+            Some((previous, previous_start)) => {
+                write_frame(&previous, start - previous_start)?;
+                shown = Some((frame, start));
+            }
+            None => shown = Some((frame, start)),
+        }
+    }
+
+    // The last frame stays up for one frame's time, like the others
+    if let Some((last, start)) = shown {
+        write_frame(&last, (start_of(last.index + 1) - start).max(MIN_DELAY))?;
+    }
+
+    Ok(())
+}
+
+/// Draw every cell of the grid into an RGB pixel buffer
+fn rasterize(grid: &FrameBuffer, width: usize, height: usize) -> Vec<u8> {
+    let mut pixels = [BACKGROUND.r, BACKGROUND.g, BACKGROUND.b].repeat(width * height);
+
+    for row in 0..grid.height() {
+        for col in 0..grid.width() {
+            let Some(cell) = grid.get(col, row) else {
+                continue;
+            };
+            if cell.ch.is_whitespace() {
+                continue;
+            }
+
+            let color = ansi::foreground_rgb(&cell.style).unwrap_or(FOREGROUND);
+            let origin_x = col as usize * CELL_WIDTH;
+            let origin_y = row as usize * CELL_HEIGHT;
+
+            for gy in 0..GLYPH_HEIGHT {
+                for gx in 0..GLYPH_WIDTH {
+                    if !font::pixel(cell.ch, gx, gy) {
+                        continue;
+                    }
+                    for sy in 0..PIXEL_SCALE {
+                        for sx in 0..PIXEL_SCALE {
+                            let x = origin_x + gx * PIXEL_SCALE + sx;
+                            let y = origin_y + gy * PIXEL_SCALE + sy;
+                            let offset = (y * width + x) * 3;
+                            pixels[offset] = color.r;
+                            pixels[offset + 1] = color.g;
+                            pixels[offset + 2] = color.b;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorEngine;
+    use std::time::Duration;
+
+    fn engine(duration_ms: u64, fps: u32) -> AnimationEngine {
+        let colors = ColorEngine::new()
+            .with_palette(Some(&["red".to_string(), "blue".to_string()]))
+            .unwrap();
+        AnimationEngine::new(
+            " _ \n|_|".to_string(),
+            Duration::from_millis(duration_ms),
+            fps,
+        )
+        .with_effect("slide-in-left")
+        .unwrap()
+        .with_color_engine(colors)
+    }
+
+    /// The delay of every frame of the encoded GIF
+    fn delays(engine: &AnimationEngine) -> Vec<u16> {
+        let mut bytes = Vec::new();
+        write_gif(engine, &mut bytes).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(bytes.as_slice())
+            .unwrap();
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        delays
+    }
+
+    #[test]
+    fn test_gif_has_one_frame_per_timeline_frame() {
+        let engine = engine(500, 10);
+        let delays = delays(&engine);
+
+        assert_eq!(delays.len(), engine.frames().count());
+        assert_eq!(
+            delays.len(),
+            engine.renderer().timeline().total_frames() + 1
+        );
+        assert!(delays.iter().all(|&delay| delay == 10), "{delays:?}");
+    }
+
+    #[test]
+    fn test_gif_delays_keep_time_at_high_fps() {
+        let delays = delays(&engine(1000, 60));
+
+        assert!(delays.iter().all(|&delay| delay >= MIN_DELAY), "{delays:?}");
+        // All but the last frame span the whole second, without drift
+        let (last, played) = delays.split_last().unwrap();
+        assert_eq!(played.iter().sum::<u16>(), 100);
+        assert_eq!(*last, MIN_DELAY);
+    }
+
+    #[test]
+    fn test_gif_delays_do_not_drift() {
+        // 30fps is 3.33cs a frame; rounding each delay would lose a second
+        // every 30
+        let delays = delays(&engine(3000, 30));
+        let (_, played) = delays.split_last().unwrap();
+        assert_eq!(played.iter().sum::<u16>(), 300);
+        assert!(delays.iter().all(|&delay| delay == 3 || delay == 4));
+    }
+}
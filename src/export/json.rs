@@ -0,0 +1,95 @@
+use crate::animation::AnimationEngine;
+use crate::utils::{ansi, buffer};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+/// One animation frame as produced by the effect and color engines
+#[derive(Debug, Serialize)]
+pub struct FrameDump {
+    pub progress: f64,
+    pub opacity: f64,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub scale: f64,
+    pub text: String,
+    /// `#rrggbb` foreground of every cell of the colored frame, row by row,
+    /// or null for uncolored cells
+    pub colors: Vec<Vec<Option<String>>>,
+}
+
+/// Every timeline frame, without touching the terminal
+pub fn frames(engine: &AnimationEngine) -> Vec<FrameDump> {
+    engine
+        .frames()
+        .map(|frame| {
+            let colors = frame
+                .text
+                .lines()
+                .map(|line| {
+                    buffer::parse_cells(line)
+                        .iter()
+                        .map(|cell| {
+                            ansi::foreground_rgb(&cell.style)
+                                .map(|c| format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b))
+                        })
+                        .collect()
+                })
+                .collect();
+
+            FrameDump {
+                progress: frame.progress,
+                opacity: frame.effect.opacity,
+                offset_x: frame.effect.offset_x,
+                offset_y: frame.effect.offset_y,
+                scale: frame.effect.scale,
+                text: frame.effect.text,
+                colors,
+            }
+        })
+        .collect()
+}
+
+/// Write every frame as a JSON array
+pub fn dump_json<W: Write>(engine: &AnimationEngine, writer: &mut W) -> Result<()> {
+    serde_json::to_writer(&mut *writer, &frames(engine))?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorEngine;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    #[test]
+    fn test_one_entry_per_timeline_frame() {
+        let engine = AnimationEngine::new("Hi".to_string(), Duration::from_millis(1000), 10)
+            .with_effect("fade-in")
+            .unwrap()
+            .with_color_engine(
+                ColorEngine::new()
+                    .with_palette(Some(&["red".to_string(), "blue".to_string()]))
+                    .unwrap(),
+            );
+
+        let mut out = Vec::new();
+        dump_json(&engine, &mut out).unwrap();
+        let frames: Value = serde_json::from_slice(&out).unwrap();
+        let frames = frames.as_array().unwrap();
+
+        assert_eq!(
+            frames.len(),
+            engine.renderer().timeline().total_frames() + 1
+        );
+        assert_eq!(frames[0]["progress"], 0.0);
+        // One frame every 100ms, the same steps the terminal plays
+        assert_eq!(frames[1]["progress"], 0.1);
+        assert_eq!(frames[frames.len() - 1]["progress"], 1.0);
+        assert_eq!(frames[frames.len() - 1]["text"], "Hi");
+        let color = frames[frames.len() - 1]["colors"][0][0].as_str().unwrap();
+        assert!(["#ff0000", "#0000ff"].contains(&color), "{color}");
+    }
+}
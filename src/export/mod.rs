@@ -0,0 +1,35 @@
+pub mod asciicast;
+#[cfg(feature = "gif")]
+mod font;
+#[cfg(feature = "gif")]
+pub mod gif;
+pub mod json;
+pub mod svg;
+
+use crate::animation::AnimationEngine;
+use anyhow::{bail, Result};
+use std::path::Path;
+
+#[cfg(feature = "gif")]
+pub use self::gif::export_gif;
+pub use asciicast::export_asciicast;
+pub use json::dump_json;
+pub use svg::export_svg;
+
+/// Export the animation, picking the format from the file extension
+pub fn export(engine: &AnimationEngine, path: &Path) -> Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("svg") => export_svg(engine, path),
+        Some("cast") => export_asciicast(engine, path),
+        #[cfg(feature = "gif")]
+        Some("gif") => export_gif(engine, path),
+        #[cfg(not(feature = "gif"))]
+        Some("gif") => bail!("GIF export requires building piglet with the `gif` feature"),
+        _ => bail!("Unsupported export format: {}", path.display()),
+    }
+}
@@ -0,0 +1,195 @@
+use crate::animation::AnimationEngine;
+use crate::utils::{ansi, buffer};
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+
+const CELL_WIDTH: f64 = 9.6;
+const LINE_HEIGHT: f64 = 18.0;
+const FONT_SIZE: f64 = 16.0;
+const PADDING_CELLS: usize = 4;
+const DEFAULT_FILL: &str = "#ffffff";
+const BACKGROUND: &str = "#000000";
+
+/// Write the animation as an animated SVG file
+pub fn export_svg(engine: &AnimationEngine, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, render_svg(engine))
+        .with_context(|| format!("Failed to write SVG to {}", path.display()))
+}
+
+/// Render the animation as an SVG document, one group per timeline frame.
+/// Each group is only visible during its own frame slot (SMIL `animate`),
+/// with the frame's opacity and offset applied to the group. Scale is
+/// already baked into the frame text by the effect.
+pub fn render_svg(engine: &AnimationEngine) -> String {
+    let frame_count = engine.renderer().timeline().total_frames() + 1;
+    let frame_seconds = 1.0 / engine.fps().max(1) as f64;
+    let total_seconds = frame_count as f64 * frame_seconds;
+
+    let art = engine.ascii_art();
+    let cols = art.width() + PADDING_CELLS * 2;
+    let rows = art.height() + PADDING_CELLS * 2;
+    let width = cols as f64 * CELL_WIDTH;
+    let height = rows as f64 * LINE_HEIGHT;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.1}" height="{h:.1}" viewBox="0 0 {w:.1} {h:.1}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        BACKGROUND
+    );
+    let _ = writeln!(
+        svg,
+        r#"<g font-family="monospace" font-size="{}" xml:space="preserve">"#,
+        FONT_SIZE
+    );
+
+    for frame in engine.frames() {
+        let effect_result = &frame.effect;
+        let colored_text = &frame.text;
+
+        let _ = writeln!(
+            svg,
+            r#"<g visibility="hidden" opacity="{:.3}" transform="translate({:.1} {:.1})">"#,
+            effect_result.opacity.clamp(0.0, 1.0),
+            effect_result.offset_x as f64 * CELL_WIDTH,
+            effect_result.offset_y as f64 * LINE_HEIGHT
+        );
+        svg.push_str(&visibility_animation(
+            frame.index,
+            frame_count,
+            total_seconds,
+        ));
+
+        let lines: Vec<&str> = colored_text.lines().collect();
+        let block_width = lines
+            .iter()
+            .map(|l| ansi::visual_width(l))
+            .max()
+            .unwrap_or(0);
+        let start_row = rows.saturating_sub(lines.len()) / 2;
+        let start_col = cols.saturating_sub(block_width) / 2;
+
+        for (i, line) in lines.iter().enumerate() {
+            let col = start_col + block_width.saturating_sub(ansi::visual_width(line)) / 2;
+            let x = col as f64 * CELL_WIDTH;
+            let y = (start_row + i + 1) as f64 * LINE_HEIGHT;
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}">{}</text>"#,
+                x,
+                y,
+                line_spans(line)
+            );
+        }
+
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// SMIL animation showing a frame group only during its own time slot
+fn visibility_animation(frame: usize, frame_count: usize, total_seconds: f64) -> String {
+    let start = frame as f64 / frame_count as f64;
+    let end = (frame + 1) as f64 / frame_count as f64;
+    let is_last = frame + 1 == frame_count;
+
+    let (values, key_times) = match (frame == 0, is_last) {
+        (true, true) => ("visible".to_string(), "0".to_string()),
+        (true, false) => ("visible;hidden".to_string(), format!("0;{:.6}", end)),
+        (false, true) => ("hidden;visible".to_string(), format!("0;{:.6}", start)),
+        (false, false) => (
+            "hidden;visible;hidden".to_string(),
+            format!("0;{:.6};{:.6}", start, end),
+        ),
+    };
+
+    format!(
+        "<animate attributeName=\"visibility\" values=\"{}\" keyTimes=\"{}\" dur=\"{:.3}s\" calcMode=\"discrete\" fill=\"freeze\"/>\n",
+        values, key_times, total_seconds
+    )
+}
+
+/// Convert an ANSI-colored line into `<tspan>` runs sharing a fill color
+fn line_spans(line: &str) -> String {
+    let mut spans = String::new();
+    let mut run = String::new();
+    let mut run_fill: Option<String> = None;
+
+    for cell in buffer::parse_cells(line) {
+        let fill = ansi::foreground_rgb(&cell.style)
+            .map(|c| format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b))
+            .unwrap_or_else(|| DEFAULT_FILL.to_string());
+
+        if run_fill.as_deref() != Some(fill.as_str()) {
+            push_span(&mut spans, run_fill.as_deref(), &run);
+            run.clear();
+            run_fill = Some(fill);
+        }
+        push_escaped(&mut run, cell.ch);
+    }
+    push_span(&mut spans, run_fill.as_deref(), &run);
+
+    spans
+}
+
+fn push_span(spans: &mut String, fill: Option<&str>, text: &str) {
+    if let Some(fill) = fill {
+        if !text.is_empty() {
+            let _ = write!(spans, r#"<tspan fill="{}">{}</tspan>"#, fill, text);
+        }
+    }
+}
+
+fn push_escaped(out: &mut String, ch: char) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(ch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorEngine;
+    use std::time::Duration;
+
+    #[test]
+    fn test_svg_is_well_formed_with_one_step_per_frame() {
+        let colors = ColorEngine::new()
+            .with_palette(Some(&["red".to_string(), "blue".to_string()]))
+            .unwrap();
+        let engine =
+            AnimationEngine::new("<a&b>\n |_|".to_string(), Duration::from_millis(500), 10)
+                .with_effect("typewriter")
+                .unwrap()
+                .with_color_engine(colors);
+
+        let svg = render_svg(&engine);
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed XML");
+
+        let steps = doc
+            .descendants()
+            .filter(|n| n.has_tag_name("animate"))
+            .count();
+        assert_eq!(steps, engine.renderer().timeline().total_frames() + 1);
+
+        let filled = doc
+            .descendants()
+            .filter(|n| n.has_tag_name("tspan") && n.attribute("fill") != Some(DEFAULT_FILL))
+            .count();
+        assert!(filled > 0);
+    }
+}
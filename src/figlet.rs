@@ -1,48 +1,269 @@
 use anyhow::{bail, Context, Result};
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use which::which;
 
+use crate::utils::{ansi, ascii::AsciiArt, suggest};
+
+/// Blank rows between stacked banners unless `with_line_spacing` says
+/// otherwise
+pub const DEFAULT_LINE_SPACING: usize = 1;
+/// Most blank rows `--line-spacing` puts between stacked banners
+pub const MAX_LINE_SPACING: usize = 16;
+
+/// Most close matches listed for an unknown font
+const MAX_FONT_SUGGESTIONS: usize = 3;
+
+/// How long a listed set of fonts is trusted before figlet is asked again
+pub const DEFAULT_FONT_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Runs an external program to completion and collects its output
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, program: &str, args: &[String]) -> Result<Output>;
+}
+
+/// Runs programs as real child processes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessRunner;
+
+impl CommandRunner for ProcessRunner {
+    fn run(&self, program: &str, args: &[String]) -> Result<Output> {
+        Command::new(program)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to execute {}", program))
+    }
+}
+
+/// Installed fonts and when they were listed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CachedFonts {
+    listed_at: SystemTime,
+    fonts: Vec<String>,
+}
+
+impl CachedFonts {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.listed_at.elapsed().is_ok_and(|age| age < ttl)
+    }
+
+    /// Read a cache file: the listing time in seconds since the epoch on
+    /// the first line, then one font per line
+    fn load(path: &Path) -> Option<Self> {
+        let source = std::fs::read_to_string(path).ok()?;
+        let mut lines = source.lines();
+        let secs = lines.next()?.parse().ok()?;
+        Some(Self {
+            listed_at: UNIX_EPOCH + Duration::from_secs(secs),
+            fonts: lines.map(str::to_string).collect(),
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let secs = self
+            .listed_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut contents = secs.to_string();
+        for font in &self.fonts {
+            contents.push('\n');
+            contents.push_str(font);
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write font cache {}", path.display()))
+    }
+}
+
+/// Program that draws the banners. Both take figlet's `-f`, `-w` and `-I2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Figlet,
+    /// figlet-compatible, with extra fonts such as `future` and `mono12`
+    Toilet,
+}
+
+impl Backend {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "figlet" => Ok(Self::Figlet),
+            "toilet" => Ok(Self::Toilet),
+            _ => bail!("Unknown backend: {} (expected figlet or toilet)", name),
+        }
+    }
+
+    pub fn program(&self) -> &'static str {
+        match self {
+            Self::Figlet => "figlet",
+            Self::Toilet => "toilet",
+        }
+    }
+
+    pub fn check_installed(&self) -> Result<()> {
+        let program = self.program();
+        which(program).with_context(|| {
+            format!(
+                "{0} not found. Please install {0} first.\n\
+                 On Ubuntu/Debian: sudo apt-get install {0}\n\
+                 On macOS: brew install {0}\n\
+                 On Arch: sudo pacman -S {0}",
+                program
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Names of the fonts in the backend's font directory, sorted
+    pub fn list_fonts(&self, runner: &dyn CommandRunner) -> Result<Vec<String>> {
+        // `-I2` prints the default font directory
+        let output = runner
+            .run(self.program(), &["-I2".to_string()])
+            .with_context(|| format!("Failed to list {} fonts", self.program()))?;
+
+        if !output.status.success() {
+            bail!("Failed to list fonts");
+        }
+
+        let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let mut fonts: Vec<String> = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read font directory {}", dir))?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                font_name(Path::new(&name)).map(str::to_string)
+            })
+            .collect();
+        fonts.sort();
+
+        Ok(fonts)
+    }
+
+    /// `$XDG_CACHE_HOME/piglet/<backend>-fonts`, falling back to
+    /// `~/.cache/piglet/<backend>-fonts`
+    pub fn font_cache_path(&self) -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(
+            cache_home
+                .join("piglet")
+                .join(format!("{}-fonts", self.program())),
+        )
+    }
+}
+
 pub struct FigletWrapper {
+    backend: Backend,
     font: Option<String>,
+    width: Option<u16>,
     args: Vec<String>,
+    line_spacing: usize,
+    runner: Box<dyn CommandRunner>,
+    font_cache_ttl: Duration,
+    font_cache_file: Option<PathBuf>,
+    fonts: Mutex<Option<CachedFonts>>,
 }
 
 impl FigletWrapper {
     pub fn new() -> Self {
         Self {
+            backend: Backend::default(),
             font: None,
+            width: None,
             args: Vec::new(),
+            line_spacing: DEFAULT_LINE_SPACING,
+            runner: Box::new(ProcessRunner),
+            font_cache_ttl: DEFAULT_FONT_CACHE_TTL,
+            font_cache_file: None,
+            fonts: Mutex::new(None),
         }
     }
 
+    /// Run figlet through `runner` instead of spawning it directly,
+    /// e.g. to stub it out in tests
+    #[allow(dead_code)]
+    pub fn with_runner(mut self, runner: impl CommandRunner + 'static) -> Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    /// Re-list the installed fonts once the cached list is older than `ttl`
+    #[allow(dead_code)]
+    pub fn with_font_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.font_cache_ttl = ttl;
+        self
+    }
+
+    /// Also keep the font list in `path`, so it survives between runs
+    pub fn with_font_cache_file(mut self, path: Option<PathBuf>) -> Self {
+        self.font_cache_file = path;
+        self
+    }
+
+    /// Draw with `backend` instead of figlet
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
     pub fn with_font(mut self, font: Option<&str>) -> Self {
         self.font = font.map(|s| s.to_string());
         self
     }
 
+    /// Wrap output at `width` columns (figlet's `-w`); figlet's own default
+    /// of 80 when `None`. A `-w` among the extra args still wins.
+    pub fn with_width(mut self, width: Option<u16>) -> Self {
+        self.width = width;
+        self
+    }
+
     pub fn with_args(mut self, args: Vec<String>) -> Self {
         self.args = args;
         self
     }
 
-    pub fn render(&self, text: &str) -> Result<String> {
-        let mut cmd = Command::new("figlet");
+    /// Blank rows between the banners of a multi-line text
+    pub fn with_line_spacing(mut self, line_spacing: usize) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    /// Full argv passed to the backend for `text`: font, width, extra args
+    /// and then the text itself
+    pub fn build_args(&self, text: &str) -> Vec<String> {
+        let mut args = Vec::new();
 
         // Add font if specified
         if let Some(font) = &self.font {
-            cmd.arg("-f").arg(font);
+            args.extend(["-f".to_string(), font.clone()]);
         }
 
-        // Add additional arguments
-        for arg in &self.args {
-            cmd.arg(arg);
+        if let Some(width) = self.width {
+            args.extend(["-w".to_string(), width.to_string()]);
         }
 
+        // Add additional arguments
+        args.extend(self.args.iter().cloned());
+
         // Add the text
-        cmd.arg(text);
+        args.push(text.to_string());
+        args
+    }
+
+    pub fn render(&self, text: &str) -> Result<String> {
+        self.check_font()?;
 
         // Execute and capture output
-        let output = cmd.output().context("Failed to execute figlet")?;
+        let output = self
+            .runner
+            .run(self.backend.program(), &self.build_args(text))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -52,39 +273,147 @@ impl FigletWrapper {
         let result =
             String::from_utf8(output.stdout).context("Figlet output is not valid UTF-8")?;
 
-        Ok(result)
+        // toilet may color its output; piglet does its own coloring
+        match self.backend {
+            Backend::Figlet => Ok(result),
+            Backend::Toilet => Ok(ansi::strip_ansi(&result)),
+        }
     }
 
-    pub fn check_installed() -> Result<()> {
-        which("figlet").context(
-            "figlet not found. Please install figlet first.\n\
-                     On Ubuntu/Debian: sudo apt-get install figlet\n\
-                     On macOS: brew install figlet\n\
-                     On Arch: sudo pacman -S figlet",
-        )?;
-        Ok(())
+    /// Render each line of `text` (split on newlines or a literal `\n`)
+    /// as its own banner and stack them, `line_spacing` blank rows apart
+    pub fn render_stacked(&self, text: &str) -> Result<String> {
+        let segments = split_segments(text);
+        if segments.len() <= 1 {
+            return self.render(text);
+        }
+
+        let arts = segments
+            .iter()
+            .map(|segment| self.render(segment).map(AsciiArt::new))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(AsciiArt::stack(&arts, self.line_spacing).render())
     }
 
     #[allow(dead_code)]
-    pub fn list_fonts() -> Result<Vec<String>> {
-        let output = Command::new("figlet")
-            .arg("-l")
-            .output()
-            .context("Failed to list figlet fonts")?;
+    pub fn check_installed() -> Result<()> {
+        Backend::Figlet.check_installed()
+    }
 
-        if !output.status.success() {
-            bail!("Failed to list fonts");
+    /// Fail early, with suggestions, when the chosen font isn't installed.
+    /// Font paths are left to figlet, as are fonts from a directory given
+    /// with `-d` and everything when the installed fonts can't be listed.
+    fn check_font(&self) -> Result<()> {
+        let Some(font) = &self.font else {
+            return Ok(());
+        };
+        if font.contains(['/', std::path::MAIN_SEPARATOR]) {
+            return Ok(());
+        }
+        // `-d ./fonts` or `-d./fonts`: not the directory that was listed
+        if self.args.iter().any(|arg| arg.starts_with("-d")) {
+            return Ok(());
         }
+        match self.fonts_cached() {
+            Ok(fonts) => check_font(font, &fonts),
+            Err(_) => Ok(()),
+        }
+    }
 
-        let result = String::from_utf8_lossy(&output.stdout);
-        let fonts: Vec<String> = result
-            .lines()
-            .skip(1) // Skip header
-            .filter_map(|line| line.split_whitespace().next().map(|s| s.to_string()))
-            .collect();
+    /// Installed fonts, from memory or the cache file while they are fresh,
+    /// otherwise listed again and cached
+    pub fn fonts_cached(&self) -> Result<Vec<String>> {
+        let mut cached = self.fonts.lock().unwrap();
+        if let Some(entry) = cached.as_ref().filter(|c| c.is_fresh(self.font_cache_ttl)) {
+            return Ok(entry.fonts.clone());
+        }
+
+        let from_file = self
+            .font_cache_file
+            .as_deref()
+            .and_then(CachedFonts::load)
+            .filter(|c| c.is_fresh(self.font_cache_ttl));
+        let entry = match from_file {
+            Some(entry) => entry,
+            None => {
+                let entry = CachedFonts {
+                    listed_at: SystemTime::now(),
+                    fonts: self.backend.list_fonts(self.runner.as_ref())?,
+                };
+                // Failing to write the cache only costs a re-list next run
+                if let Some(path) = &self.font_cache_file {
+                    let _ = entry.save(path);
+                }
+                entry
+            }
+        };
 
+        let fonts = entry.fonts.clone();
+        *cached = Some(entry);
         Ok(fonts)
     }
+
+    /// Forget the cached font list, in memory and on disk
+    pub fn refresh_fonts(&self) {
+        *self.fonts.lock().unwrap() = None;
+        if let Some(path) = &self.font_cache_file {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Font name of a figlet (.flf) or toilet (.tlf) font file
+fn font_name(file: &Path) -> Option<&str> {
+    match file.extension()?.to_str()? {
+        "flf" | "tlf" => file.file_stem()?.to_str(),
+        _ => None,
+    }
+}
+
+/// Error listing the closest installed fonts when `font` isn't in `fonts`.
+/// The list may be a cached one from before the font was installed, so the
+/// error says how to list them again.
+fn check_font(font: &str, fonts: &[String]) -> Result<()> {
+    let name = font_name(Path::new(font)).unwrap_or(font);
+    if fonts.iter().any(|f| f == name) {
+        return Ok(());
+    }
+
+    let refresh = "If it was just installed, run again with --refresh-fonts.";
+    let matches =
+        suggest::close_matches(name, fonts.iter().map(String::as_str), MAX_FONT_SUGGESTIONS);
+    if matches.is_empty() {
+        bail!("Unknown font '{}'. {}", font, refresh);
+    }
+    bail!(
+        "Unknown font '{}'. Did you mean: {}? {}",
+        font,
+        matches.join(", "),
+        refresh
+    )
+}
+
+/// Parse `--width`: a column count, or `auto` for the terminal's width
+/// (figlet's default when there is no terminal)
+pub fn parse_width(value: &str) -> Result<Option<u16>> {
+    if value == "auto" {
+        return Ok(crossterm::terminal::size().ok().map(|(width, _)| width));
+    }
+    match value.parse::<u16>() {
+        Ok(width) if width > 0 => Ok(Some(width)),
+        _ => bail!(
+            "Invalid width: {} (expected a number of columns or auto)",
+            value
+        ),
+    }
+}
+
+/// Split input text into banner lines on real newlines or a typed `\n`
+pub fn split_segments(text: &str) -> Vec<&str> {
+    text.split('\n')
+        .flat_map(|line| line.split("\\n"))
+        .collect()
 }
 
 impl Default for FigletWrapper {
@@ -93,8 +422,37 @@ impl Default for FigletWrapper {
     }
 }
 
+/// Stand-ins for figlet, for tests that shouldn't need it installed
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// What a successful run printing `stdout` returns
+    pub fn success(stdout: &str) -> Output {
+        #[cfg(unix)]
+        use std::os::unix::process::ExitStatusExt;
+        #[cfg(windows)]
+        use std::os::windows::process::ExitStatusExt;
+        Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        }
+    }
+
+    /// Prints canned art in place of figlet's
+    pub struct CannedRunner(pub &'static str);
+
+    impl CommandRunner for CannedRunner {
+        fn run(&self, _program: &str, _args: &[String]) -> Result<Output> {
+            Ok(success(self.0))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::testing::{success, CannedRunner};
     use super::*;
 
     #[test]
@@ -103,6 +461,235 @@ mod tests {
         assert!(FigletWrapper::check_installed().is_ok());
     }
 
+    #[test]
+    fn test_split_segments() {
+        assert_eq!(split_segments("Hello"), vec!["Hello"]);
+        assert_eq!(split_segments("Hello\\nWorld"), vec!["Hello", "World"]);
+        assert_eq!(split_segments("Hello\nWorld"), vec!["Hello", "World"]);
+    }
+
+    #[test]
+    fn test_width_is_passed_to_figlet() {
+        let figlet = FigletWrapper::new().with_width(Some(80));
+        assert_eq!(figlet.build_args("Hi"), vec!["-w", "80", "Hi"]);
+        let figlet = FigletWrapper::new().with_width(None);
+        assert_eq!(figlet.build_args("Hi"), vec!["Hi"]);
+    }
+
+    #[test]
+    fn test_build_args() {
+        assert_eq!(FigletWrapper::new().build_args("Hi"), vec!["Hi"]);
+
+        let figlet = FigletWrapper::new().with_font(Some("slant"));
+        assert_eq!(figlet.build_args("Hi"), vec!["-f", "slant", "Hi"]);
+
+        // Extra args follow the builder options so they can override them,
+        // and the text always comes last
+        let figlet = FigletWrapper::new()
+            .with_font(Some("slant"))
+            .with_width(Some(120))
+            .with_args(vec!["-c".to_string(), "-k".to_string()]);
+        assert_eq!(
+            figlet.build_args("Hi"),
+            vec!["-f", "slant", "-w", "120", "-c", "-k", "Hi"]
+        );
+    }
+
+    #[test]
+    fn test_toilet_backend() {
+        assert_eq!(Backend::parse("toilet").unwrap(), Backend::Toilet);
+        assert!(Backend::parse("banner").is_err());
+        assert_eq!(Backend::default().program(), "figlet");
+        assert_eq!(Backend::Toilet.program(), "toilet");
+
+        // toilet takes the same flags as figlet
+        let toilet = FigletWrapper::new()
+            .with_backend(Backend::Toilet)
+            .with_font(Some("future"))
+            .with_width(Some(100))
+            .with_args(vec!["-F".to_string(), "border".to_string()]);
+        assert_eq!(
+            toilet.build_args("Hi"),
+            vec!["-f", "future", "-w", "100", "-F", "border", "Hi"]
+        );
+    }
+
+    /// Answers `-I2` with `dir` and counts how often it was run
+    struct CountingRunner {
+        dir: PathBuf,
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl CommandRunner for CountingRunner {
+        fn run(&self, _program: &str, _args: &[String]) -> Result<Output> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(success(&self.dir.display().to_string()))
+        }
+    }
+
+    /// Prints the program it was asked to run and its arguments
+    struct EchoRunner;
+
+    impl CommandRunner for EchoRunner {
+        fn run(&self, program: &str, args: &[String]) -> Result<Output> {
+            Ok(success(&format!("{} {}", program, args.join(" "))))
+        }
+    }
+
+    #[test]
+    fn test_render_through_runner() {
+        let art = " _   _ _ \n| |_| (_)\n|_| |_|_|\n";
+        let figlet = FigletWrapper::new().with_runner(CannedRunner(art));
+        assert_eq!(figlet.render("Hi").unwrap(), art);
+
+        let toilet = FigletWrapper::new()
+            .with_runner(EchoRunner)
+            .with_backend(Backend::Toilet);
+        assert_eq!(toilet.render("Hi").unwrap(), "toilet Hi");
+
+        // toilet's own colors are dropped
+        let toilet = FigletWrapper::new()
+            .with_runner(CannedRunner("\x1b[31mHi\x1b[0m"))
+            .with_backend(Backend::Toilet);
+        assert_eq!(toilet.render("Hi").unwrap(), "Hi");
+    }
+
+    #[test]
+    fn test_line_spacing_between_banners() {
+        let art = "ab\ncd";
+        let height = |spacing: usize, text: &str| {
+            FigletWrapper::new()
+                .with_runner(CannedRunner(art))
+                .with_line_spacing(spacing)
+                .render_stacked(text)
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        assert_eq!(height(0, "1\n2\n3"), 6);
+        for spacing in [1, 3] {
+            // n rows between each of the 3 banners
+            assert_eq!(height(spacing, "1\n2\n3"), 6 + spacing * 2);
+        }
+        // A single line has nothing to space out
+        assert_eq!(height(3, "1"), 2);
+
+        let stacked = FigletWrapper::new()
+            .with_runner(CannedRunner(art))
+            .with_line_spacing(2)
+            .render_stacked("1\\n2")
+            .unwrap();
+        assert_eq!(stacked, "ab\ncd\n\n\nab\ncd");
+    }
+
+    #[test]
+    fn test_fonts_are_listed_once() {
+        let dir = std::env::temp_dir().join(format!("piglet-fonts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("standard.flf"), "").unwrap();
+        std::fs::write(dir.join("slant.flf"), "").unwrap();
+        let cache_file = dir.join("cache").join("figlet-fonts");
+
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runner = || CountingRunner {
+            dir: dir.clone(),
+            calls: calls.clone(),
+        };
+        let count = || calls.load(std::sync::atomic::Ordering::SeqCst);
+
+        let figlet = FigletWrapper::new()
+            .with_runner(runner())
+            .with_font_cache_file(Some(cache_file.clone()));
+        assert_eq!(figlet.fonts_cached().unwrap(), vec!["slant", "standard"]);
+        assert_eq!(figlet.fonts_cached().unwrap(), vec!["slant", "standard"]);
+        assert_eq!(count(), 1);
+
+        // A new run reads the file instead of listing again
+        let figlet = FigletWrapper::new()
+            .with_runner(runner())
+            .with_font_cache_file(Some(cache_file.clone()));
+        assert_eq!(figlet.fonts_cached().unwrap(), vec!["slant", "standard"]);
+        assert_eq!(count(), 1);
+
+        figlet.refresh_fonts();
+        assert!(!cache_file.exists());
+        figlet.fonts_cached().unwrap();
+        assert_eq!(count(), 2);
+
+        // Without a TTL every call is stale
+        let figlet = FigletWrapper::new()
+            .with_runner(runner())
+            .with_font_cache_ttl(Duration::ZERO);
+        figlet.fonts_cached().unwrap();
+        figlet.fonts_cached().unwrap();
+        assert_eq!(count(), 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_font_is_checked_without_figlet() {
+        let dir = std::env::temp_dir().join(format!("piglet-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("standard.flf"), "").unwrap();
+        let runner = || CountingRunner {
+            dir: dir.clone(),
+            calls: Default::default(),
+        };
+
+        let figlet = FigletWrapper::new()
+            .with_runner(runner())
+            .with_font(Some("standart"));
+        let err = figlet.render("Hi").unwrap_err().to_string();
+        assert!(err.contains("Did you mean: standard?"), "{}", err);
+
+        let figlet = FigletWrapper::new()
+            .with_runner(runner())
+            .with_font(Some("standard"));
+        assert!(figlet.render("Hi").is_ok());
+
+        // Fonts in a directory of their own are for figlet to find
+        for args in [vec!["-d", "./fonts"], vec!["-d./fonts"]] {
+            let figlet = FigletWrapper::new()
+                .with_runner(runner())
+                .with_font(Some("mine"))
+                .with_args(args.into_iter().map(String::from).collect());
+            assert!(figlet.render("Hi").is_ok());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_width() {
+        assert_eq!(parse_width("120").unwrap(), Some(120));
+        assert!(parse_width("0").is_err());
+        assert!(parse_width("wide").is_err());
+    }
+
+    #[test]
+    fn test_unknown_font_suggests_close_matches() {
+        let fonts: Vec<String> = ["banner", "shadow", "slant", "small", "standard"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+
+        assert!(check_font("slant", &fonts).is_ok());
+        assert!(check_font("slant.flf", &fonts).is_ok());
+
+        let message = check_font("slnt", &fonts).unwrap_err().to_string();
+        assert!(message.contains("'slnt'"), "{message}");
+        assert!(message.contains("slant"), "{message}");
+        assert!(!message.contains("banner"), "{message}");
+
+        let message = check_font("gothic", &fonts).unwrap_err().to_string();
+        assert_eq!(
+            message,
+            "Unknown font 'gothic'. If it was just installed, run again with --refresh-fonts."
+        );
+    }
+
     #[test]
     fn test_basic_render() {
         let figlet = FigletWrapper::new();
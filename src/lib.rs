@@ -1,8 +1,12 @@
 pub mod animation;
 pub mod cli;
 pub mod color;
+pub mod config;
+pub mod export;
 pub mod figlet;
 pub mod parser;
+pub mod render;
 pub mod utils;
 
 pub use cli::PigletCli;
+pub use render::{render_to_string, Options};
@@ -1,27 +1,72 @@
 mod animation;
 mod cli;
 mod color;
+mod config;
+mod export;
 mod figlet;
 mod parser;
 mod utils;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
+use clap::{error::ErrorKind, CommandFactory, FromArgMatches};
 use cli::PigletCli;
+use std::io::IsTerminal;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse CLI arguments
-    let args = PigletCli::parse();
+    let matches = PigletCli::command().get_matches();
+    let mut args = PigletCli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    // Show banner on first run
-    if std::env::args().len() == 1 {
+    // Plot an easing curve instead of animating, before any config is read
+    // so a broken .pigletrc can't get in the way
+    if let Some(name) = &args.plot_easing {
+        println!("{}", utils::plot::plot_easing(name, 40, 15)?);
+        return Ok(());
+    }
+
+    // Options the command line leaves out come from the project's
+    // .pigletrc, then from the named preset
+    let project = if args.no_config {
+        config::Preset::default()
+    } else {
+        config::Preset::load_project_config()?
+    };
+    let preset = match &args.preset {
+        Some(name) => config::Presets::load_from_default_path()?
+            .get(name)?
+            .clone(),
+        None => config::Preset::default(),
+    };
+    project.or(preset).apply(&mut args, &matches);
+
+    // Frame rates the terminal can't keep up with only burn CPU
+    args.fps = animation::timeline::check_fps(args.fps, args.max_fps, &mut std::io::stderr())?;
+
+    // Show banner on first run, unless text is being piped in
+    let stdin_is_terminal = std::io::stdin().is_terminal();
+    if std::env::args().len() == 1 && stdin_is_terminal {
         show_welcome();
         return Ok(());
     }
 
-    // Verify figlet is installed
-    figlet::FigletWrapper::check_installed()?;
+    // Read the text from stdin for `-` or when it is piped without TEXT
+    if args.sequence.is_none() {
+        let stdin = std::io::stdin().lock();
+        args.text = utils::input::resolve_text(args.text.as_deref(), stdin, stdin_is_terminal)?;
+        if args.text.is_none() {
+            PigletCli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "the following required arguments were not provided:\n  <TEXT>",
+                )
+                .exit();
+        }
+    }
+
+    // Verify figlet (or the chosen backend) is installed
+    figlet::Backend::parse(&args.backend)?.check_installed()?;
 
     // Run the piglet magic
     run_piglet(args).await?;
@@ -30,46 +75,96 @@ async fn main() -> Result<()> {
 }
 
 async fn run_piglet(args: PigletCli) -> Result<()> {
-    use crate::animation::AnimationEngine;
-    use crate::color::ColorEngine;
+    use crate::animation::sequence::{Repeat, Sequence};
     use crate::utils::terminal::TerminalManager;
+    use rand::{rngs::StdRng, SeedableRng};
 
-    // Parse duration
-    let duration_ms = parser::duration::parse_duration(&args.duration)?;
+    // Parse duration; a range is sampled again for every pass
+    let duration = parser::duration::parse_duration_spec(&args.duration)?;
+    let mut rng = args
+        .seed
+        .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let first_duration = duration.sample(&mut rng);
+    let repeat = Repeat::from_args(args.loop_animation, args.loop_count)?;
 
-    // Create figlet wrapper and render base ASCII art
-    let figlet = figlet::FigletWrapper::new()
-        .with_font(args.font.as_deref())
-        .with_args(args.figlet_args);
+    // Build one animation per text; a plain TEXT is a sequence of one
+    let texts = match &args.sequence {
+        Some(texts) => texts.clone(),
+        None => vec![args.text.clone().unwrap_or_default()],
+    };
+    let engines = texts
+        .iter()
+        .map(|text| build_engine(&args, text, first_duration))
+        .collect::<Result<Vec<_>>>()?;
+    let animation_engine = &engines[0];
+
+    // Export to a file instead of playing
+    if let Some(path) = &args.export {
+        return export::export(animation_engine, path);
+    }
 
-    let ascii_art = figlet.render(&args.text)?;
+    // Dump the frames for other tools instead of playing
+    if args.dump_json {
+        return export::dump_json(animation_engine, &mut std::io::stdout().lock());
+    }
 
-    // Setup color engine
-    let color_engine = ColorEngine::new()
-        .with_palette(args.color_palette.as_deref())?
-        .with_gradient(args.color_gradient.as_deref())?;
+    // Print a static frame without taking over the terminal
+    if args.output_ansi {
+        return animation_engine.write_static(&mut std::io::stdout().lock(), true);
+    }
 
-    // Setup animation engine
-    let animation_engine = AnimationEngine::new(ascii_art, duration_ms, args.fps)
-        .with_effect(&args.motion_effect)?
-        .with_easing(&args.motion_ease)?
-        .with_color_engine(color_engine);
+    // Piped or redirected output can't animate: print each final frame,
+    // keeping colors only when they were asked for
+    if !std::io::stdout().is_terminal() && !args.force_animate {
+        let colored = args.color_palette.is_some() || args.color_gradient.is_some();
+        let mut out = std::io::stdout().lock();
+        for engine in &engines {
+            engine.write_static(&mut out, colored)?;
+        }
+        return Ok(());
+    }
+
+    // Fade each text out before the next comes in
+    let mut sequence = Sequence::new(engines).with_transition(first_duration / 2);
+
+    // SIGTERM and SIGINT tear down like `q`, SIGWINCH triggers a redraw
+    #[cfg(unix)]
+    utils::signals::install()?;
 
     // Setup terminal
-    let mut terminal = TerminalManager::new()?;
+    let mut terminal = if args.inline {
+        TerminalManager::new_inline()?
+    } else if args.no_raw_mode {
+        TerminalManager::new_plain()?
+    } else {
+        TerminalManager::new()?.with_diff_rendering(!args.no_diff)
+    };
     terminal.setup()?;
 
-    // Run animation
-    loop {
-        let user_exited = animation_engine.run(&mut terminal).await?;
+    // Browse every effect instead of playing the chosen one
+    if args.gallery {
+        let mut events = animation::renderer::playback_events();
+        let text = &texts[0];
+        animation::gallery::run_gallery(&mut terminal, &mut events, |name| {
+            let args = PigletCli {
+                motion_effect: name.to_string(),
+                ..args.clone()
+            };
+            build_engine(&args, text, animation::gallery::EFFECT_DURATION)
+        })
+        .await?;
+        return terminal.cleanup();
+    }
 
-        // If user pressed exit key, stop looping
-        if user_exited {
-            break;
+    // Run animation once, N times, or until the user exits
+    for (pass, last) in repeat.passes().enumerate() {
+        if pass > 0 {
+            sequence = sequence.with_duration(duration.sample(&mut rng));
         }
+        let user_exited = sequence.run(&mut terminal, args.hold && last).await?;
 
-        // If not looping, stop after one animation
-        if !args.loop_animation {
+        // If user pressed exit key, stop looping
+        if user_exited {
             break;
         }
     }
@@ -80,6 +175,116 @@ async fn run_piglet(args: PigletCli) -> Result<()> {
     Ok(())
 }
 
+/// Render `text` with figlet and wrap it in an animation configured by `args`
+fn build_engine(
+    args: &PigletCli,
+    text: &str,
+    duration: Duration,
+) -> Result<animation::AnimationEngine> {
+    use crate::animation::AnimationEngine;
+    use crate::color::{Attributes, ColorDepth, ColorEngine};
+    use crate::utils::ascii::{quarter_turns, AsciiArt, Flip};
+    use crate::utils::border::{Border, BorderStyle};
+    use crate::utils::layout::{Alignment, VerticalAlignment, VerticalPlacement};
+    use crate::utils::shadow::Shadow;
+
+    // Create figlet wrapper and render base ASCII art
+    let backend = figlet::Backend::parse(&args.backend)?;
+    let figlet = figlet::FigletWrapper::new()
+        .with_backend(backend)
+        .with_font_cache_file(backend.font_cache_path())
+        .with_font(args.font.as_deref())
+        .with_width(figlet::parse_width(&args.width)?)
+        .with_args(args.figlet_args.clone())
+        .with_line_spacing(args.line_spacing);
+    if args.refresh_fonts {
+        figlet.refresh_fonts();
+    }
+
+    let ascii_art = figlet.render_stacked(text)?;
+    let ascii_art = if args.no_trim {
+        ascii_art
+    } else {
+        AsciiArt::trimmed(&ascii_art).render()
+    };
+    let ascii_art = AsciiArt::new(ascii_art)
+        .flip(Flip::parse(&args.flip)?)
+        .rotate(quarter_turns(args.rotate)?)
+        .render();
+    // Empty text or only characters the font lacks leave nothing to animate
+    if AsciiArt::new(ascii_art.clone()).is_blank() {
+        bail!(
+            "Nothing to render: figlet drew no visible characters for '{}'",
+            text
+        );
+    }
+
+    // Setup color engine
+    let color_depth = match &args.color_depth {
+        Some(depth) => ColorDepth::parse(depth)?,
+        None => ColorDepth::TrueColor,
+    };
+    // NO_COLOR turns color off unless a color depth was asked for explicitly
+    let color_engine = if args.color_depth.is_some() {
+        ColorEngine::new()
+    } else {
+        ColorEngine::from_env()
+    };
+    let color_engine = color_engine
+        .with_palette(args.color_palette.as_deref())?
+        .with_gradient(args.color_gradient.as_deref())?
+        .with_interpolation(args.interpolate.as_deref())?
+        .with_gradient_resolution(args.gradient_resolution)?
+        .with_reversed(args.reverse_colors)
+        .with_mapping(&args.color_mode)?
+        .with_direction(&args.gradient_direction)?
+        .with_depth(color_depth)
+        .with_dither(args.dither)
+        .with_brightness(args.brightness)?
+        .with_saturation(args.saturation)?
+        .with_background(args.bg_color.as_deref())?
+        .with_min_contrast(args.min_contrast)?
+        .with_glow(args.glow)
+        .with_attributes(Attributes {
+            bold: args.bold,
+            dim: args.dim,
+            italic: args.italic,
+            underline: args.underline,
+        });
+
+    // Setup border
+    let border = match &args.border {
+        Some(style) => Some(
+            Border::new(BorderStyle::parse(style)?)
+                .with_padding(args.border_padding)
+                .with_fixed(args.border_fixed),
+        ),
+        None => None,
+    };
+
+    let shadow = args.shadow.as_deref().map(Shadow::parse).transpose()?;
+
+    // Setup animation engine
+    let mut engine = AnimationEngine::new(ascii_art, duration, args.fps)
+        .with_effect(&args.motion_effect)?
+        .with_easing_or_default(args.motion_ease.as_deref())?
+        .with_frequency(args.frequency)?
+        .with_verbose(args.verbose)
+        .with_color_engine(color_engine)
+        .with_border(border)
+        .with_shadow(shadow)
+        .with_alignment(Alignment::parse(&args.align)?)
+        .with_vertical(VerticalPlacement::new(
+            VerticalAlignment::parse(&args.valign)?,
+            args.margin_top,
+        ));
+    if let Some(seed) = args.seed {
+        engine = engine.with_seed(seed);
+    }
+
+    Ok(engine)
+}
+
 fn show_welcome() {
     println!(
         r"
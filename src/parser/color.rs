@@ -1,46 +1,382 @@
 use anyhow::{Context, Result};
 use csscolorparser::Color as CssColor;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    /// Opacity, 255 being fully opaque
+    pub a: u8,
 }
 
 impl Color {
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Self { r, g, b }
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
     }
 
-    pub fn from_hex(hex: &str) -> Result<Self> {
-        let color = hex
-            .parse::<CssColor>()
-            .context(format!("Failed to parse hex color: {}", hex))?;
+    /// Like `new`, with an alpha channel
+    #[allow(dead_code)]
+    pub const fn new_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
 
-        Ok(Self {
-            r: (color.r * 255.0) as u8,
-            g: (color.g * 255.0) as u8,
-            b: (color.b * 255.0) as u8,
-        })
+    /// Build a color from hue (degrees), saturation and lightness (0.0-1.0)
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        Self::from_css(&CssColor::from_hsla(h, s, l, 1.0))
     }
 
+    /// Build a color from hue (degrees), saturation and value (0.0-1.0)
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
+        let h = h.rem_euclid(360.0);
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+
+        let c = v * s;
+        let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+        let m = v - c;
+
+        let (r, g, b) = match (h / 60.0) as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        Self::new(
+            ((r + m) * 255.0).round() as u8,
+            ((g + m) * 255.0).round() as u8,
+            ((b + m) * 255.0).round() as u8,
+        )
+    }
+
+    /// Parse any CSS color: hex (`#ff5733`), named (`red`), `rgb(...)`,
+    /// `hsl(...)`, `hsv(...)` or `hwb(...)`
     pub fn parse(color_str: &str) -> Result<Self> {
-        Self::from_hex(color_str)
+        let color = color_str
+            .parse::<CssColor>()
+            .context(format!("Failed to parse color: {}", color_str))?;
+
+        Ok(Self::from_css(&color))
+    }
+
+    fn from_css(color: &CssColor) -> Self {
+        let [r, g, b, a] = color.to_rgba8();
+        Self { r, g, b, a }
+    }
+
+    /// Hue (degrees), saturation and value (0.0-1.0) of this color
+    #[allow(dead_code)]
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (h, s, v, _) = CssColor::from_rgba8(self.r, self.g, self.b, 255).to_hsva();
+        (if h.is_nan() { 0.0 } else { h }, s, v)
     }
 
     pub fn interpolate(&self, other: &Color, t: f64) -> Color {
         let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t) as u8;
+        Color {
+            r: lerp(self.r, other.r),
+            g: lerp(self.g, other.g),
+            b: lerp(self.b, other.b),
+            a: lerp(self.a, other.a),
+        }
+    }
+
+    /// Hue (degrees), saturation and lightness (0.0-1.0) of this color
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let (h, s, l, _) = CssColor::from_rgba8(self.r, self.g, self.b, 255).to_hsla();
+        (if h.is_nan() { 0.0 } else { h }, s, l)
+    }
+
+    /// Move the HSL lightness toward white (`amount` > 0) or black (< 0);
+    /// 1.0 and -1.0 go all the way. Alpha is kept.
+    pub fn adjust_brightness(&self, amount: f64) -> Color {
+        let (h, s, l) = self.to_hsl();
+        let l = scale_toward_bound(l, amount);
+        Color {
+            a: self.a,
+            ..Color::from_hsl(h, s, l)
+        }
+    }
+
+    /// Move the HSL saturation toward full color (`amount` > 0) or gray
+    /// (< 0); -1.0 leaves a gray of the same lightness. Alpha is kept.
+    pub fn adjust_saturation(&self, amount: f64) -> Color {
+        let (h, s, l) = self.to_hsl();
+        let s = scale_toward_bound(s, amount);
+        Color {
+            a: self.a,
+            ..Color::from_hsl(h, s, l)
+        }
+    }
+
+    /// This color composited over an opaque `background`, by its alpha
+    pub fn over(&self, background: Color) -> Color {
+        if self.a == 255 {
+            return *self;
+        }
+        let alpha = self.a as f64 / 255.0;
+        let blend = |fg: u8, bg: u8| (fg as f64 * alpha + bg as f64 * (1.0 - alpha)).round() as u8;
+        Color::new(
+            blend(self.r, background.r),
+            blend(self.g, background.g),
+            blend(self.b, background.b),
+        )
+    }
+
+    /// Apply a blend mode channel by channel, with this color as the base
+    /// layer and `other` drawn on top; `mode` works on 0.0-1.0 values. The
+    /// base's alpha is kept.
+    fn blend(&self, other: &Color, mode: impl Fn(f64, f64) -> f64) -> Color {
+        let channel = |base: u8, top: u8| {
+            let value = mode(base as f64 / 255.0, top as f64 / 255.0);
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: self.a,
+        }
+    }
+
+    /// Multiply blend: always darker, white leaves the other color as is
+    pub fn blend_multiply(&self, other: &Color) -> Color {
+        self.blend(other, |base, top| base * top)
+    }
+
+    /// Screen blend: always lighter, black leaves the other color as is
+    pub fn blend_screen(&self, other: &Color) -> Color {
+        self.blend(other, |base, top| 1.0 - (1.0 - base) * (1.0 - top))
+    }
+
+    /// Additive blend, as light adds up; channels saturate at 255
+    pub fn blend_add(&self, other: &Color) -> Color {
+        self.blend(other, |base, top| base + top)
+    }
+
+    /// A gray `level` of the way from black (0.0) to white (1.0); multiply
+    /// a color by it to keep that much of its light
+    pub fn gray(level: f64) -> Color {
+        let value = (level.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(value, value, value)
+    }
+
+    /// Scale every channel by `opacity`, fading the color toward black
+    pub fn dim(&self, opacity: f64) -> Color {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let scale = |channel: u8| (channel as f64 * opacity).round() as u8;
+        Color::new(scale(self.r), scale(self.g), scale(self.b))
+    }
+
+    /// Blend in Oklab space, which keeps perceived lightness even instead
+    /// of dipping through gray like plain RGB blending
+    pub fn interpolate_oklab(&self, other: &Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let (l1, a1, b1) = self.to_oklab();
+        let (l2, a2, b2) = other.to_oklab();
+        let alpha = self.a as f64 + (other.a as f64 - self.a as f64) * t;
         Color {
-            r: (self.r as f64 + (other.r as f64 - self.r as f64) * t) as u8,
-            g: (self.g as f64 + (other.g as f64 - self.g as f64) * t) as u8,
-            b: (self.b as f64 + (other.b as f64 - self.b as f64) * t) as u8,
+            a: alpha.round() as u8,
+            ..Color::from_oklab(l1 + (l2 - l1) * t, a1 + (a2 - a1) * t, b1 + (b2 - b1) * t)
         }
     }
 
+    /// Convert to Oklab (L, a, b)
+    pub fn to_oklab(self) -> (f64, f64, f64) {
+        let r = srgb_to_linear(self.r);
+        let g = srgb_to_linear(self.g);
+        let b = srgb_to_linear(self.b);
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        (
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        )
+    }
+
+    /// Build a color from Oklab (L, a, b), clamping to the sRGB gamut
+    pub fn from_oklab(l: f64, a: f64, b: f64) -> Self {
+        let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+        Self::new(
+            linear_to_srgb(4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_),
+            linear_to_srgb(-1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_),
+            linear_to_srgb(-0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_),
+        )
+    }
+
     #[allow(dead_code)]
-    #[allow(clippy::wrong_self_convention)]
-    pub fn to_ansi(&self) -> String {
+    pub fn to_ansi(self) -> String {
         format!("\x1b[38;2;{};{};{}m", self.r, self.g, self.b)
     }
+
+    /// `#rrggbb`, or `#rrggbbaa` when the color isn't fully opaque
+    pub fn to_hex(self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// Move `value` (0.0-1.0) toward 1.0 by `amount` of the remaining distance,
+/// or toward 0.0 for a negative `amount`
+fn scale_toward_bound(value: f64, amount: f64) -> f64 {
+    let amount = amount.clamp(-1.0, 1.0);
+    let value = if amount >= 0.0 {
+        value + (1.0 - value) * amount
+    } else {
+        value * (1.0 + amount)
+    };
+    value.clamp(0.0, 1.0)
+}
+
+fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(color: Color) -> (u8, u8, u8) {
+        (color.r, color.g, color.b)
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(Color::new(255, 87, 51).to_hex(), "#ff5733");
+        assert_eq!(Color::new_rgba(255, 87, 51, 128).to_hex(), "#ff573380");
+        assert_eq!(Color::new(0, 10, 255).to_string(), "#000aff");
+
+        for hex in ["#ff5733", "#000000", "#0a0b0c80", "#ffffff00"] {
+            assert_eq!(Color::parse(hex).unwrap().to_hex(), hex);
+        }
+    }
+
+    #[test]
+    fn test_adjust_brightness() {
+        let color = Color::new(255, 87, 51);
+        assert_eq!(rgb(color.adjust_brightness(-1.0)), (0, 0, 0));
+        assert_eq!(rgb(color.adjust_brightness(1.0)), (255, 255, 255));
+        assert_eq!(rgb(color.adjust_brightness(0.0)), (255, 87, 51));
+        assert!(color.adjust_brightness(-0.5).g < color.g);
+    }
+
+    #[test]
+    fn test_adjust_saturation() {
+        let color = Color::new(200, 80, 40);
+        let (_, _, lightness) = color.to_hsl();
+
+        let gray = color.adjust_saturation(-1.0);
+        assert_eq!(gray.r, gray.g);
+        assert_eq!(gray.g, gray.b);
+        assert_eq!(gray.r, (lightness * 255.0).round() as u8);
+
+        assert_eq!(rgb(color.adjust_saturation(0.0)), (200, 80, 40));
+        let (_, vivid, _) = color.adjust_saturation(1.0).to_hsl();
+        assert!((vivid - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_parse_hsl() {
+        let color = Color::parse("hsl(120,100%,50%)").unwrap();
+        assert_eq!(rgb(color), (0, 255, 0));
+        assert_eq!(rgb(Color::from_hsl(120.0, 1.0, 0.5)), (0, 255, 0));
+    }
+
+    #[test]
+    fn test_parse_rgb_and_hsv() {
+        assert_eq!(
+            rgb(Color::parse("rgb(255, 87, 51)").unwrap()),
+            (255, 87, 51)
+        );
+        assert_eq!(
+            rgb(Color::parse("hsv(240, 100%, 100%)").unwrap()),
+            (0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        for (r, g, b) in [(255, 87, 51), (12, 200, 99), (128, 128, 128), (0, 0, 0)] {
+            let (h, s, v) = Color::new(r, g, b).to_hsv();
+            assert_eq!(rgb(Color::from_hsv(h, s, v)), (r, g, b));
+        }
+    }
+
+    #[test]
+    fn test_oklab_round_trip() {
+        for (r, g, b) in [(255, 0, 0), (0, 0, 255), (255, 87, 51), (255, 255, 255)] {
+            let (l, a, b2) = Color::new(r, g, b).to_oklab();
+            assert_eq!(rgb(Color::from_oklab(l, a, b2)), (r, g, b));
+        }
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let white = Color::new(255, 255, 255);
+        let black = Color::new(0, 0, 0);
+        let gray = Color::new(128, 128, 128);
+        let color = Color::new(255, 87, 51);
+
+        assert_eq!(white.blend_multiply(&color), color);
+        assert_eq!(color.blend_multiply(&black), black);
+        assert_eq!(rgb(gray.blend_multiply(&gray)), (64, 64, 64));
+        assert_eq!(Color::gray(0.5), gray);
+        assert_eq!(rgb(color.blend_multiply(&Color::gray(0.0))), (0, 0, 0));
+
+        assert_eq!(black.blend_screen(&color), color);
+        assert_eq!(color.blend_screen(&white), white);
+        assert_eq!(rgb(gray.blend_screen(&gray)), (192, 192, 192));
+
+        assert_eq!(black.blend_add(&color), color);
+        assert_eq!(rgb(color.blend_add(&gray)), (255, 215, 179));
+
+        // The top layer's alpha doesn't change the base's
+        let translucent = Color::new_rgba(10, 20, 30, 100);
+        assert_eq!(translucent.blend_add(&white).a, 100);
+        assert_eq!(color.blend_multiply(&translucent).a, 255);
+    }
+
+    #[test]
+    fn test_oklab_midpoint_differs_from_rgb() {
+        let red = Color::new(255, 0, 0);
+        let blue = Color::new(0, 0, 255);
+        assert_eq!(rgb(red.interpolate(&blue, 0.5)), (127, 0, 127));
+        assert_ne!(rgb(red.interpolate_oklab(&blue, 0.5)), (127, 0, 127));
+    }
 }
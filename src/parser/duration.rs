@@ -1,66 +1,181 @@
 use anyhow::{bail, Result};
 use lazy_static::lazy_static;
+use rand::Rng;
 use regex::Regex;
+use std::time::Duration;
 
 lazy_static! {
-    static ref DURATION_REGEX: Regex = Regex::new(r"^(\d+(?:\.\d+)?)(ms|s|m|h)$").unwrap();
+    static ref DURATION_REGEX: Regex =
+        Regex::new(r"^(?:\d+(?:\.\d+)?(?:ms|us|µs|ns|s|m|h))+$").unwrap();
+    static ref SEGMENT_REGEX: Regex = Regex::new(r"(\d+(?:\.\d+)?)(ms|us|µs|ns|s|m|h)").unwrap();
 }
 
-/// Parse duration string to milliseconds
-/// Supports: 3000ms, 0.3s, 5m, 0.5h
-pub fn parse_duration(duration: &str) -> Result<u64> {
-    let caps = DURATION_REGEX
-        .captures(duration.trim())
-        .ok_or_else(|| anyhow::anyhow!("Invalid duration format: {}", duration))?;
-
-    let value: f64 = caps[1]
-        .parse()
-        .map_err(|_| anyhow::anyhow!("Invalid numeric value in duration"))?;
-
-    let unit = &caps[2];
-
-    let milliseconds = match unit {
-        "ms" => value,
-        "s" => value * 1000.0,
-        "m" => value * 60.0 * 1000.0,
-        "h" => value * 60.0 * 60.0 * 1000.0,
-        _ => bail!("Unknown time unit: {}", unit),
-    };
+/// Parse duration string, adding up compound durations
+/// Supports: 3000ms, 0.3s, 5m, 0.5h, 1m30s, 1h5m, 500us, 500µs, 250ns
+pub fn parse_duration(duration: &str) -> Result<Duration> {
+    let duration = duration.trim();
+    if !DURATION_REGEX.is_match(duration) {
+        bail!("Invalid duration format: {}", duration);
+    }
+
+    let mut nanoseconds = 0.0;
+    for caps in SEGMENT_REGEX.captures_iter(duration) {
+        let value: f64 = caps[1]
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid numeric value in duration"))?;
+
+        let unit = &caps[2];
 
-    if milliseconds < 0.0 {
+        nanoseconds += match unit {
+            "ns" => value,
+            "us" | "µs" => value * 1e3,
+            "ms" => value * 1e6,
+            "s" => value * 1e9,
+            "m" => value * 60.0 * 1e9,
+            "h" => value * 60.0 * 60.0 * 1e9,
+            _ => bail!("Unknown time unit: {}", unit),
+        };
+    }
+
+    if nanoseconds < 0.0 {
         bail!("Duration cannot be negative");
     }
 
-    Ok(milliseconds as u64)
+    Ok(Duration::from_nanos(nanoseconds.round() as u64))
+}
+
+/// A `--duration`: one length, or a range to pick a length from each loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationSpec {
+    Fixed(Duration),
+    Range(Duration, Duration),
+}
+
+impl DurationSpec {
+    /// Length of one play, picked uniformly from a range
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        match *self {
+            Self::Fixed(duration) => duration,
+            Self::Range(low, high) => rng.gen_range(low..=high),
+        }
+    }
+}
+
+/// Parse a duration, or a range of two joined by `-`
+/// Supports: 3s, 1s-3s, 500ms-2s
+pub fn parse_duration_spec(spec: &str) -> Result<DurationSpec> {
+    let Some((low, high)) = spec.split_once('-') else {
+        return Ok(DurationSpec::Fixed(parse_duration(spec)?));
+    };
+
+    let (low, high) = (parse_duration(low)?, parse_duration(high)?);
+    if low > high {
+        bail!(
+            "Invalid duration range: {} (the shorter duration comes first)",
+            spec
+        );
+    }
+    Ok(DurationSpec::Range(low, high))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
     #[test]
     fn test_parse_milliseconds() {
-        assert_eq!(parse_duration("3000ms").unwrap(), 3000);
-        assert_eq!(parse_duration("500ms").unwrap(), 500);
+        assert_eq!(parse_duration("3000ms").unwrap(), ms(3000));
+        assert_eq!(parse_duration("500ms").unwrap(), ms(500));
     }
 
     #[test]
     fn test_parse_seconds() {
-        assert_eq!(parse_duration("3s").unwrap(), 3000);
-        assert_eq!(parse_duration("0.5s").unwrap(), 500);
-        assert_eq!(parse_duration("1.5s").unwrap(), 1500);
+        assert_eq!(parse_duration("3s").unwrap(), ms(3000));
+        assert_eq!(parse_duration("0.5s").unwrap(), ms(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), ms(1500));
+        assert_eq!(parse_duration("0.3s").unwrap(), ms(300));
     }
 
     #[test]
     fn test_parse_minutes() {
-        assert_eq!(parse_duration("1m").unwrap(), 60000);
-        assert_eq!(parse_duration("0.5m").unwrap(), 30000);
+        assert_eq!(parse_duration("1m").unwrap(), ms(60000));
+        assert_eq!(parse_duration("0.5m").unwrap(), ms(30000));
     }
 
     #[test]
     fn test_parse_hours() {
-        assert_eq!(parse_duration("1h").unwrap(), 3600000);
-        assert_eq!(parse_duration("0.5h").unwrap(), 1800000);
+        assert_eq!(parse_duration("1h").unwrap(), ms(3600000));
+        assert_eq!(parse_duration("0.5h").unwrap(), ms(1800000));
+    }
+
+    #[test]
+    fn test_parse_compound() {
+        assert_eq!(parse_duration("1m30s").unwrap(), ms(90000));
+        assert_eq!(parse_duration("1h5m").unwrap(), ms(3900000));
+        assert_eq!(parse_duration("1s500ms").unwrap(), ms(1500));
+        assert_eq!(parse_duration("1m0.5s").unwrap(), ms(60500));
+        assert!(parse_duration("1x2s").is_err());
+        assert!(parse_duration("1m30").is_err());
+        assert!(parse_duration("1m 30s").is_err());
+        assert_eq!(
+            parse_duration_spec("1m-1m30s").unwrap(),
+            DurationSpec::Range(ms(60000), ms(90000))
+        );
+    }
+
+    #[test]
+    fn test_parse_sub_millisecond() {
+        assert_eq!(parse_duration("500us").unwrap(), Duration::from_micros(500));
+        assert_eq!(parse_duration("500µs").unwrap(), Duration::from_micros(500));
+        assert_eq!(parse_duration("250ns").unwrap(), Duration::from_nanos(250));
+        assert_eq!(
+            parse_duration("1ms500us").unwrap(),
+            Duration::from_micros(1500)
+        );
+        // `us` and `ns` don't shadow `s` and `ms`
+        assert_eq!(parse_duration("5s").unwrap(), ms(5000));
+        assert_eq!(parse_duration("5ms").unwrap(), ms(5));
+        assert!(parse_duration("5u").is_err());
+        assert!(parse_duration("5n").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_range() {
+        assert_eq!(
+            parse_duration_spec("2s").unwrap(),
+            DurationSpec::Fixed(ms(2000))
+        );
+        assert_eq!(
+            parse_duration_spec("1s-3s").unwrap(),
+            DurationSpec::Range(ms(1000), ms(3000))
+        );
+        assert_eq!(
+            parse_duration_spec("500ms-0.5s").unwrap(),
+            DurationSpec::Range(ms(500), ms(500))
+        );
+        assert!(parse_duration_spec("3s-1s").is_err());
+        assert!(parse_duration_spec("1s-").is_err());
+        assert!(parse_duration_spec("1s-2s-3s").is_err());
+    }
+
+    #[test]
+    fn test_range_samples_stay_in_bounds() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let spec = DurationSpec::Range(ms(1000), ms(3000));
+        let samples: Vec<Duration> = (0..50).map(|_| spec.sample(&mut rng)).collect();
+        assert!(samples.iter().all(|d| (ms(1000)..=ms(3000)).contains(d)));
+        assert!(samples.iter().any(|d| *d != samples[0]));
+
+        // The same seed picks the same lengths
+        let mut again = StdRng::seed_from_u64(7);
+        assert_eq!(spec.sample(&mut again), samples[0]);
+        assert_eq!(DurationSpec::Fixed(ms(5)).sample(&mut rng), ms(5));
     }
 
     #[test]
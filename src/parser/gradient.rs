@@ -5,6 +5,43 @@ use anyhow::{bail, Result};
 pub struct ColorStop {
     pub color: Color,
     pub position: f64,
+    /// Where the blend toward the next stop is half done, as a gradient
+    /// position; halfway between the two stops when `None`. As in CSS, a
+    /// hint close to this stop moves the even mix toward it, leaving more
+    /// of the span to the next stop's color.
+    pub hint: Option<f64>,
+}
+
+/// Color space used to blend between gradient stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterpolationSpace {
+    #[default]
+    Rgb,
+    Oklab,
+}
+
+impl InterpolationSpace {
+    /// Parse a space name; `oklch` is accepted and blended in Oklab
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rgb" | "srgb" => Ok(Self::Rgb),
+            "oklab" | "oklch" => Ok(Self::Oklab),
+            _ => bail!(
+                "Unknown interpolation space: {} (expected rgb or oklab)",
+                name
+            ),
+        }
+    }
+}
+
+/// The CSS gradient function a gradient came from
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GradientKind {
+    #[default]
+    Linear,
+    /// Stops run clockwise around a center, starting `from_angle` degrees
+    /// clockwise from the top
+    Conic { from_angle: f64 },
 }
 
 #[derive(Debug, Clone)]
@@ -12,36 +49,76 @@ pub struct Gradient {
     pub stops: Vec<ColorStop>,
     #[allow(dead_code)]
     pub angle: f64,
+    pub space: InterpolationSpace,
+    pub kind: GradientKind,
 }
 
 impl Gradient {
     pub fn new(stops: Vec<ColorStop>, angle: f64) -> Self {
-        Self { stops, angle }
+        Self {
+            stops,
+            angle,
+            space: InterpolationSpace::default(),
+            kind: GradientKind::default(),
+        }
+    }
+
+    pub fn with_kind(mut self, kind: GradientKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_interpolation(mut self, space: InterpolationSpace) -> Self {
+        self.space = space;
+        self
     }
 
     pub fn parse(gradient_str: &str) -> Result<Self> {
         let gradient_str = gradient_str.trim();
 
-        if !gradient_str.starts_with("linear-gradient(") {
-            bail!("Only linear-gradient is supported");
-        }
-
-        let content = gradient_str
-            .strip_prefix("linear-gradient(")
-            .and_then(|s| s.strip_suffix(")"))
+        let (function, content) = gradient_str
+            .split_once('(')
+            .ok_or_else(|| anyhow::anyhow!("Invalid gradient syntax"))?;
+        let mut kind = match function.trim() {
+            "linear-gradient" => GradientKind::Linear,
+            "conic-gradient" => GradientKind::Conic { from_angle: 0.0 },
+            _ => bail!("Only linear-gradient and conic-gradient are supported"),
+        };
+        let content = content
+            .strip_suffix(")")
             .ok_or_else(|| anyhow::anyhow!("Invalid gradient syntax"))?;
 
-        let parts: Vec<&str> = content.split(',').map(|s| s.trim()).collect();
+        let parts = split_top_level(content, |c| c == ',');
 
         if parts.is_empty() {
             bail!("Gradient must have at least one color");
         }
 
         let mut angle = 180.0;
+        let mut space = InterpolationSpace::default();
         let mut color_parts = parts.as_slice();
 
         if let Some(first) = parts.first() {
-            if first.ends_with("deg") {
+            // A colorspace hint ("in oklch") picks the interpolation space and
+            // may share the leading part with a direction
+            let (first, hint) = split_colorspace(first);
+            if let Some(hint) = hint {
+                space = InterpolationSpace::parse(hint).unwrap_or_default();
+            }
+            if let GradientKind::Conic { from_angle } = &mut kind {
+                // "from <angle>" and "at <position>"; the center is always
+                // the middle of the art
+                let words: Vec<&str> = first.split_whitespace().collect();
+                if let Some(i) = words.iter().position(|w| *w == "from") {
+                    *from_angle = words
+                        .get(i + 1)
+                        .and_then(|a| a.trim_end_matches("deg").parse().ok())
+                        .ok_or_else(|| anyhow::anyhow!("Invalid conic angle: {}", first))?;
+                }
+                if first.is_empty() || words.first().is_some_and(|w| matches!(*w, "from" | "at")) {
+                    color_parts = &parts[1..];
+                }
+            } else if first.ends_with("deg") {
                 angle = first
                     .trim_end_matches("deg")
                     .trim()
@@ -57,34 +134,85 @@ impl Gradient {
                     _ => 180.0,
                 };
                 color_parts = &parts[1..];
+            } else if first.is_empty() {
+                color_parts = &parts[1..];
             }
         }
 
-        let mut stops = Vec::new();
-        let count = color_parts.len();
-
+        // Each part is normally "color [pct]", but comma-free lists such as
+        // "red 0% blue 100%" put several stops in one part. A part that is
+        // only a percentage is a midpoint hint for the colors around it.
+        let mut entries: Vec<(&str, Option<f64>)> = Vec::new();
+        let mut hints: Vec<(usize, f64)> = Vec::new();
         for (i, part) in color_parts.iter().enumerate() {
-            let part = part.trim();
-            let mut color_str = part;
-            let mut position = i as f64 / (count - 1).max(1) as f64;
-
-            // Check if there's a percentage (e.g., "#FF5733 50%" or "red 50%")
-            if let Some(percent_pos) = part.rfind('%') {
-                // Find the last space before the percentage
-                if let Some(space_pos) = part[..percent_pos].rfind(|c: char| c.is_whitespace()) {
-                    color_str = part[..space_pos].trim();
-                    let percent_str = part[space_pos + 1..percent_pos].trim();
-                    if let Ok(p) = percent_str.parse::<f64>() {
-                        position = p / 100.0;
+            if let Some(hint) = parse_hint(part) {
+                let follows_hint = hints.last().is_some_and(|&(at, _)| at + 1 == entries.len());
+                if entries.is_empty() || i + 1 == color_parts.len() || follows_hint {
+                    bail!("A midpoint hint must sit between two colors: {}", part);
+                }
+                hints.push((entries.len() - 1, hint));
+                continue;
+            }
+            for token in split_top_level(part, char::is_whitespace) {
+                match token.strip_suffix('%') {
+                    Some(percent) if !entries.is_empty() => {
+                        if let Ok(p) = percent.trim().parse::<f64>() {
+                            entries.last_mut().unwrap().1 = Some(p / 100.0);
+                        }
                     }
+                    _ => entries.push((token, None)),
                 }
             }
+        }
 
+        if entries.is_empty() {
+            bail!("Gradient must have at least one color");
+        }
+
+        let mut stops = Vec::new();
+        let count = entries.len();
+
+        for (i, (color_str, position)) in entries.into_iter().enumerate() {
+            let position = position.unwrap_or(i as f64 / (count - 1).max(1) as f64);
             let color = Color::parse(color_str)?;
-            stops.push(ColorStop { color, position });
+            stops.push(ColorStop {
+                color,
+                position,
+                hint: None,
+            });
+        }
+        for (index, hint) in hints {
+            stops[index].hint = Some(hint);
         }
 
-        Ok(Self::new(stops, angle))
+        Ok(Self::new(stops, angle)
+            .with_interpolation(space)
+            .with_kind(kind))
+    }
+
+    /// The same gradient running the other way: every stop mirrored to
+    /// `1.0 - position`, in reverse order
+    pub fn reverse(&self) -> Gradient {
+        // A hint belongs to the stop before it, which comes after it once
+        // the order is flipped
+        let stops = self
+            .stops
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, stop)| ColorStop {
+                color: stop.color,
+                position: 1.0 - stop.position,
+                hint: i
+                    .checked_sub(1)
+                    .and_then(|before| self.stops[before].hint)
+                    .map(|hint| 1.0 - hint),
+            })
+            .collect();
+        Gradient {
+            stops,
+            ..self.clone()
+        }
     }
 
     pub fn color_at(&self, t: f64) -> Color {
@@ -103,8 +231,18 @@ impl Gradient {
             let stop2 = &self.stops[i + 1];
 
             if t >= stop1.position && t <= stop2.position {
-                let local_t = (t - stop1.position) / (stop2.position - stop1.position);
-                return stop1.color.interpolate(&stop2.color, local_t);
+                let span = stop2.position - stop1.position;
+                let local_t = (t - stop1.position) / span;
+                let local_t = match stop1.hint {
+                    Some(hint) => apply_hint(local_t, (hint - stop1.position) / span),
+                    None => local_t,
+                };
+                return match self.space {
+                    InterpolationSpace::Rgb => stop1.color.interpolate(&stop2.color, local_t),
+                    InterpolationSpace::Oklab => {
+                        stop1.color.interpolate_oklab(&stop2.color, local_t)
+                    }
+                };
             }
         }
 
@@ -120,3 +258,189 @@ impl Gradient {
             .collect()
     }
 }
+
+/// A part that is nothing but a percentage, as a 0.0-1.0 position
+fn parse_hint(part: &str) -> Option<f64> {
+    let percent: f64 = part.trim().strip_suffix('%')?.trim().parse().ok()?;
+    Some(percent / 100.0)
+}
+
+/// Remap `t` (0.0-1.0 between two stops) so that `hint`, given the same
+/// way, maps to the halfway point, as CSS transition hints do
+fn apply_hint(t: f64, hint: f64) -> f64 {
+    if hint <= 0.0 {
+        return 1.0;
+    }
+    if hint >= 1.0 {
+        return 0.0;
+    }
+    t.powf(0.5f64.ln() / hint.ln())
+}
+
+/// Separate an `in <colorspace>` hint from a gradient's leading part,
+/// returning the remaining text and the colorspace name
+fn split_colorspace(part: &str) -> (String, Option<&str>) {
+    let tokens: Vec<&str> = part.split_whitespace().collect();
+    match tokens.iter().position(|t| *t == "in") {
+        Some(i) => {
+            let mut rest = tokens[..i].to_vec();
+            rest.extend(tokens.iter().skip(i + 2));
+            (rest.join(" "), tokens.get(i + 1).copied())
+        }
+        None => (tokens.join(" "), None),
+    }
+}
+
+/// Split on separators that are not nested inside parentheses, so that
+/// `rgb(0, 0, 0)` and `hsl(...)` stay in one piece
+fn split_top_level(s: &str, is_separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && is_separator(c) => {
+                parts.push(s[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colorspace_prefix_is_ignored() {
+        let gradient = Gradient::parse("linear-gradient(in oklab, red, blue)").unwrap();
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.stops[1].position, 1.0);
+        assert_eq!(gradient.space, InterpolationSpace::Oklab);
+
+        let gradient = Gradient::parse("linear-gradient(90deg in oklch, red, blue)").unwrap();
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.angle, 90.0);
+    }
+
+    #[test]
+    fn test_space_separated_stops() {
+        let gradient = Gradient::parse("linear-gradient(red 0% blue 100%)").unwrap();
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.stops[0].color.r, 255);
+        assert_eq!(gradient.stops[1].color.b, 255);
+        assert_eq!(gradient.stops[1].position, 1.0);
+    }
+
+    #[test]
+    fn test_functional_colors_in_stops() {
+        let gradient =
+            Gradient::parse("linear-gradient(to right, rgb(255, 0, 0) 0%, hsl(240, 100%, 50%))")
+                .unwrap();
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.stops[1].color.b, 255);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let gradient = Gradient::parse("linear-gradient(red, blue)").unwrap();
+        let reversed = gradient.reverse();
+        assert_eq!(reversed.color_at(0.0), Color::new(0, 0, 255));
+        assert_eq!(reversed.color_at(1.0), Color::new(255, 0, 0));
+
+        let gradient = Gradient::parse("linear-gradient(red 0%, lime 20%, blue 100%)").unwrap();
+        let positions: Vec<f64> = gradient
+            .reverse()
+            .stops
+            .iter()
+            .map(|s| s.position)
+            .collect();
+        assert_eq!(positions, [0.0, 0.8, 1.0]);
+        assert_eq!(gradient.reverse().color_at(0.8), Color::new(0, 255, 0));
+    }
+
+    #[test]
+    fn test_midpoint_hint() {
+        let plain = Gradient::parse("linear-gradient(red, blue)").unwrap();
+        let hinted = Gradient::parse("linear-gradient(red, 25%, blue)").unwrap();
+        assert_eq!(hinted.stops.len(), 2);
+        assert_eq!(hinted.stops[0].hint, Some(0.25));
+
+        // A 25% hint moves the even mix of red and blue from 50% toward
+        // the first stop, to 25%, as CSS does; the middle of the gradient
+        // is then already mostly blue
+        let mid = hinted.color_at(0.25);
+        assert_eq!(mid, plain.color_at(0.5));
+        assert!(hinted.color_at(0.25).r < plain.color_at(0.25).r);
+        assert!(hinted.color_at(0.5).b > plain.color_at(0.5).b);
+        assert_eq!(hinted.color_at(0.0), Color::new(255, 0, 0));
+        assert_eq!(hinted.color_at(1.0), Color::new(0, 0, 255));
+
+        let reversed = hinted.reverse();
+        assert_eq!(reversed.stops[0].hint, Some(0.75));
+        assert_eq!(reversed.color_at(0.75), mid);
+
+        assert!(Gradient::parse("linear-gradient(red, blue, 25%)").is_err());
+        assert!(Gradient::parse("linear-gradient(25%, red, blue)").is_err());
+        assert!(Gradient::parse("linear-gradient(red, 25%, 75%, blue)").is_err());
+        assert!(Gradient::parse("linear-gradient(red, 25%, green, 75%, blue)").is_ok());
+    }
+
+    #[test]
+    fn test_transparent_stop_composites_over_background() {
+        let gradient = Gradient::parse("linear-gradient(rgba(255,0,0,0), blue)").unwrap();
+        let background = Color::new(16, 32, 48);
+
+        let start = gradient.color_at(0.0);
+        assert_eq!(start.a, 0);
+        assert_eq!(start.over(background), background);
+
+        let middle = gradient.color_at(0.5);
+        assert_eq!(middle.a, 127);
+        assert_eq!(
+            gradient.color_at(1.0).over(background),
+            Color::new(0, 0, 255)
+        );
+
+        let gradient = gradient.with_interpolation(InterpolationSpace::Oklab);
+        assert_eq!(gradient.color_at(0.5).a, 128);
+    }
+
+    #[test]
+    fn test_conic_gradient() {
+        let gradient = Gradient::parse("conic-gradient(from 90deg, red, blue, red)").unwrap();
+        assert_eq!(gradient.kind, GradientKind::Conic { from_angle: 90.0 });
+        assert_eq!(gradient.stops.len(), 3);
+        assert_eq!(gradient.color_at(0.0), Color::new(255, 0, 0));
+        assert_eq!(gradient.color_at(0.5), Color::new(0, 0, 255));
+        assert_eq!(gradient.color_at(1.0), Color::new(255, 0, 0));
+
+        let gradient = Gradient::parse("conic-gradient(red, blue)").unwrap();
+        assert_eq!(gradient.kind, GradientKind::Conic { from_angle: 0.0 });
+        assert_eq!(gradient.stops.len(), 2);
+
+        assert!(Gradient::parse("conic-gradient(from sideways, red, blue)").is_err());
+        assert!(Gradient::parse("radial-gradient(red, blue)").is_err());
+    }
+
+    #[test]
+    fn test_oklab_gradient_midpoint() {
+        let gradient = Gradient::parse("linear-gradient(red, blue)").unwrap();
+        let rgb_mid = gradient.color_at(0.5);
+        let oklab_mid = gradient
+            .with_interpolation(InterpolationSpace::Oklab)
+            .color_at(0.5);
+        assert_ne!(
+            (rgb_mid.r, rgb_mid.g, rgb_mid.b),
+            (oklab_mid.r, oklab_mid.g, oklab_mid.b)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{bail, Result};
+use std::time::Duration;
+
+use crate::animation::AnimationEngine;
+use crate::color::{Attributes, ColorDepth, ColorEngine};
+use crate::figlet::{Backend, FigletWrapper, DEFAULT_LINE_SPACING};
+use crate::utils::ascii::{quarter_turns, AsciiArt, Flip};
+use crate::utils::border::{Border, BorderStyle};
+use crate::utils::shadow::Shadow;
+
+/// How `render_to_string` draws a banner; the CLI options that matter for
+/// a single, final frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Figlet font name or path
+    pub font: Option<String>,
+    pub backend: Backend,
+    /// Wrap column for figlet's `-w`
+    pub width: Option<u16>,
+    /// Extra arguments passed through to figlet
+    pub figlet_args: Vec<String>,
+    /// Blank rows between the banners of a multi-line text
+    pub line_spacing: usize,
+    /// Motion effect whose final frame is drawn
+    pub effect: String,
+    /// Palette colors (hex or CSS4 names)
+    pub palette: Option<Vec<String>>,
+    /// Gradient definition, e.g. "linear-gradient(90deg, red, blue)"
+    pub gradient: Option<String>,
+    /// auto or per-char, as for `--color-mode`
+    pub color_mode: String,
+    /// Flip the gradient, or the palette order
+    pub reverse_colors: bool,
+    /// horizontal, vertical or diagonal, as for `--gradient-direction`
+    pub gradient_direction: String,
+    pub depth: ColorDepth,
+    /// Lighten (up to 1.0) or darken (down to -1.0) every color
+    pub brightness: f64,
+    /// Saturate (up to 1.0) or desaturate (down to -1.0) every color
+    pub saturation: f64,
+    /// Minimum WCAG contrast ratio of glyphs against `bg_color`
+    pub min_contrast: Option<f64>,
+    /// Terminal background color; `$COLORFGBG` or black when unset
+    pub bg_color: Option<String>,
+    /// Dither gradients when `depth` is 256 or 16 colors
+    pub dither: bool,
+    pub attributes: Attributes,
+    /// Box style drawn around the art: single, double, rounded or ascii
+    pub border: Option<String>,
+    /// Drop shadow behind the art, as `dx,dy` or `dx,dy,color`
+    pub shadow: Option<String>,
+    /// Keep figlet's blank padding rows and trailing spaces
+    pub no_trim: bool,
+    /// Mirror the art before drawing it
+    pub flip: Flip,
+    /// Clockwise rotation in degrees: 0, 90, 180 or 270
+    pub rotate: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            font: None,
+            backend: Backend::default(),
+            width: None,
+            figlet_args: Vec::new(),
+            line_spacing: DEFAULT_LINE_SPACING,
+            effect: "fade-in".to_string(),
+            palette: None,
+            gradient: None,
+            color_mode: "auto".to_string(),
+            reverse_colors: false,
+            gradient_direction: "horizontal".to_string(),
+            depth: ColorDepth::TrueColor,
+            brightness: 0.0,
+            saturation: 0.0,
+            min_contrast: None,
+            bg_color: None,
+            dither: false,
+            attributes: Attributes::default(),
+            border: None,
+            shadow: None,
+            no_trim: false,
+            flip: Flip::None,
+            rotate: 0,
+        }
+    }
+}
+
+/// Render `text` with figlet and return the final frame of the animation
+/// as ANSI-colored text, without touching the terminal.
+///
+/// Needs the chosen backend (figlet by default) to be installed.
+pub fn render_to_string(text: &str, options: &Options) -> Result<String> {
+    let figlet = FigletWrapper::new()
+        .with_backend(options.backend)
+        .with_font(options.font.as_deref())
+        .with_width(options.width)
+        .with_args(options.figlet_args.clone())
+        .with_line_spacing(options.line_spacing);
+    render_with(&figlet, text, options)
+}
+
+fn render_with(figlet: &FigletWrapper, text: &str, options: &Options) -> Result<String> {
+    let art = figlet.render_stacked(text)?;
+    let art = if options.no_trim {
+        art
+    } else {
+        AsciiArt::trimmed(&art).render()
+    };
+    let art = AsciiArt::new(art)
+        .flip(options.flip)
+        .rotate(quarter_turns(options.rotate)?)
+        .render();
+    if AsciiArt::new(art.clone()).is_blank() {
+        bail!(
+            "Nothing to render: figlet drew no visible characters for '{}'",
+            text
+        );
+    }
+
+    let color_engine = ColorEngine::new()
+        .with_palette(options.palette.as_deref())?
+        .with_gradient(options.gradient.as_deref())?
+        .with_reversed(options.reverse_colors)
+        .with_mapping(&options.color_mode)?
+        .with_direction(&options.gradient_direction)?
+        .with_depth(options.depth)
+        .with_dither(options.dither)
+        .with_brightness(options.brightness)?
+        .with_saturation(options.saturation)?
+        .with_background(options.bg_color.as_deref())?
+        .with_min_contrast(options.min_contrast)?
+        .with_attributes(options.attributes);
+    let border = match &options.border {
+        Some(style) => Some(Border::new(BorderStyle::parse(style)?)),
+        None => None,
+    };
+    let shadow = options.shadow.as_deref().map(Shadow::parse).transpose()?;
+
+    // Only the final frame is drawn, so the length doesn't matter
+    let engine = AnimationEngine::new(art, Duration::from_secs(1), 1)
+        .with_effect(&options.effect)?
+        .with_color_engine(color_engine)
+        .with_border(border)
+        .with_shadow(shadow);
+    Ok(engine.render_static())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::figlet::testing::CannedRunner;
+
+    fn figlet(art: &'static str) -> FigletWrapper {
+        FigletWrapper::new().with_runner(CannedRunner(art))
+    }
+
+    #[test]
+    fn test_render_plain_banner() {
+        let options = Options::default();
+        let banner = render_with(&figlet(" _ \n|_|\n\n"), "Hi", &options).unwrap();
+        assert_eq!(banner, " _ \n|_|");
+    }
+
+    #[test]
+    fn test_render_with_palette_is_colored() {
+        let options = Options {
+            palette: Some(vec!["red".to_string(), "blue".to_string()]),
+            ..Default::default()
+        };
+        let banner = render_with(&figlet(" _ \n|_|"), "Hi", &options).unwrap();
+        assert!(!banner.is_empty());
+        assert!(banner.contains("\x1b[38;2;"));
+    }
+
+    #[test]
+    fn test_render_flipped() {
+        let options = Options {
+            flip: Flip::Both,
+            ..Default::default()
+        };
+        let banner = render_with(&figlet("/|\n(_"), "Hi", &options).unwrap();
+        // Both ways is half a turn, so the slash keeps its slant
+        assert_eq!(banner, "_)\n|/");
+    }
+
+    #[test]
+    fn test_render_rejects_blank_art() {
+        let options = Options::default();
+        assert!(render_with(&figlet("  \n"), "", &options).is_err());
+
+        let options = Options {
+            effect: "no-such-effect".to_string(),
+            ..Default::default()
+        };
+        assert!(render_with(&figlet("x"), "x", &options).is_err());
+    }
+}
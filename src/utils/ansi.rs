@@ -1,3 +1,8 @@
+use crate::color::depth;
+use crate::parser::color::Color;
+
+const RESET: &str = "\x1b[0m";
+
 /// Strip ANSI escape sequences from a string to get visual width
 pub fn strip_ansi(text: &str) -> String {
     let mut result = String::new();
@@ -29,6 +34,86 @@ pub fn visual_width(text: &str) -> usize {
     strip_ansi(text).chars().count()
 }
 
+/// The `width` visible columns of `text` starting at column `start`. Escape
+/// sequences up to the cut are kept, so the slice starts in the style that
+/// was active at `start`; a line cut short ends with a reset so no style
+/// leaks past it.
+pub fn slice_columns(text: &str, start: usize, width: usize) -> String {
+    let end = start.saturating_add(width);
+    let mut result = String::new();
+    let mut styled = false;
+    let mut column = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            styled = true;
+            result.push(ch);
+            for c in chars.by_ref() {
+                result.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if column >= end {
+            if styled {
+                result.push_str(RESET);
+            }
+            break;
+        } else {
+            if column >= start {
+                result.push(ch);
+            }
+            column += 1;
+        }
+    }
+
+    result
+}
+
+/// Extract the last foreground color set by an SGR sequence, whether 24-bit
+/// (`38;2;r;g;b`), 256-color (`38;5;n`) or one of the 16 basic colors
+pub fn foreground_rgb(style: &str) -> Option<Color> {
+    let mut color = None;
+
+    for sequence in style.split('\x1b').filter(|s| s.starts_with('[')) {
+        let params: Vec<&str> = sequence
+            .trim_start_matches('[')
+            .trim_end_matches('m')
+            .split(';')
+            .collect();
+
+        let mut i = 0;
+        while i < params.len() {
+            if params[i] == "38" && params.get(i + 1) == Some(&"2") && i + 4 < params.len() {
+                let channel = |p: &str| p.parse::<u8>().ok();
+                if let (Some(r), Some(g), Some(b)) = (
+                    channel(params[i + 2]),
+                    channel(params[i + 3]),
+                    channel(params[i + 4]),
+                ) {
+                    color = Some(Color::new(r, g, b));
+                }
+                i += 5;
+            } else if params[i] == "38" && params.get(i + 1) == Some(&"5") && i + 2 < params.len() {
+                if let Ok(index) = params[i + 2].parse::<u8>() {
+                    color = Some(depth::palette_color(index));
+                }
+                i += 3;
+            } else {
+                match params[i].parse::<u8>() {
+                    Ok(code @ 30..=37) => color = Some(depth::palette_color(code - 30)),
+                    Ok(code @ 90..=97) => color = Some(depth::palette_color(code - 90 + 8)),
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+    }
+
+    color
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,4 +136,44 @@ mod tests {
         assert_eq!(strip_ansi(text), "Plain text");
         assert_eq!(visual_width(text), 10);
     }
+
+    #[test]
+    fn test_slice_columns() {
+        let text = "\x1b[31mab\x1b[0m\x1b[32mcd\x1b[0m";
+        assert_eq!(
+            slice_columns(text, 1, 2),
+            "\x1b[31mb\x1b[0m\x1b[32mc\x1b[0m"
+        );
+        assert_eq!(visual_width(&slice_columns(text, 3, 10)), 1);
+        assert_eq!(strip_ansi(&slice_columns(text, 4, 2)), "");
+        assert_eq!(slice_columns("plain", 2, 2), "ai");
+    }
+
+    #[test]
+    fn test_clip_colored_line() {
+        let line: String = (0..100)
+            .map(|i| format!("\x1b[38;5;{}m{}\x1b[39m", 16 + i, i % 10))
+            .collect();
+        let clipped = slice_columns(&line, 0, 20);
+
+        assert_eq!(visual_width(&clipped), 20);
+        assert_eq!(strip_ansi(&clipped), strip_ansi(&line)[..20]);
+        // Every color set is reset again by the end of the line
+        assert_eq!(clipped.matches("\x1b[39m").count(), 20);
+        assert!(clipped.ends_with(RESET));
+
+        assert_eq!(slice_columns(&line, 0, 100), line);
+    }
+
+    #[test]
+    fn test_foreground_rgb() {
+        let color = foreground_rgb("\x1b[38;2;255;87;51m").unwrap();
+        assert_eq!((color.r, color.g, color.b), (255, 87, 51));
+        assert!(foreground_rgb("\x1b[1m").is_none());
+
+        let color = foreground_rgb("\x1b[38;5;196m").unwrap();
+        assert_eq!((color.r, color.g, color.b), (255, 0, 0));
+        let color = foreground_rgb("\x1b[91m").unwrap();
+        assert_eq!((color.r, color.g, color.b), (255, 0, 0));
+    }
 }
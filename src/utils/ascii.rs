@@ -1,3 +1,41 @@
+use anyhow::{bail, Result};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Glyphs that turn into each other when mirrored left to right
+const MIRRORED_HORIZONTALLY: [(&str, &str); 5] =
+    [("/", "\\"), ("(", ")"), ("<", ">"), ("[", "]"), ("{", "}")];
+
+/// Glyphs that turn into each other when mirrored top to bottom
+const MIRRORED_VERTICALLY: [(&str, &str); 1] = [("/", "\\")];
+
+/// Which way `--flip` mirrors the art
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flip {
+    #[default]
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl Flip {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "none" => Ok(Self::None),
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            "both" => Ok(Self::Both),
+            _ => bail!(
+                "Unknown flip: {} (expected none, horizontal, vertical or both)",
+                name
+            ),
+        }
+    }
+}
+
+/// Art is laid out in grapheme clusters, so an accented letter written as a
+/// base character plus combining marks takes one cell like any other glyph
 #[derive(Debug, Clone)]
 pub struct AsciiArt {
     lines: Vec<String>,
@@ -8,7 +46,11 @@ pub struct AsciiArt {
 impl AsciiArt {
     pub fn new(text: String) -> Self {
         let lines: Vec<String> = text.lines().map(|s| s.to_string()).collect();
-        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let width = lines
+            .iter()
+            .map(|l| l.graphemes(true).count())
+            .max()
+            .unwrap_or(0);
         let height = lines.len();
 
         Self {
@@ -18,6 +60,57 @@ impl AsciiArt {
         }
     }
 
+    /// Like `new`, without the blank rows figlet fonts pad the art with and
+    /// without the trailing whitespace every line shares. Leading spaces and
+    /// the spacing inside the art are kept, so the lines still line up.
+    pub fn trimmed(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let is_blank = |line: &&str| line.trim().is_empty();
+        let start = lines.iter().position(|l| !is_blank(l)).unwrap_or(0);
+        let end = lines
+            .iter()
+            .rposition(|l| !is_blank(l))
+            .map_or(0, |last| last + 1);
+        let lines = &lines[start.min(end)..end];
+
+        // Counted in chars: fonts may pad with NBSP or ideographic spaces
+        let trailing = |line: &str| line[line.trim_end().len()..].chars().count();
+        let common = lines
+            .iter()
+            .filter(|l| !is_blank(l))
+            .map(|l| trailing(l))
+            .min()
+            .unwrap_or(0);
+
+        let text = lines
+            .iter()
+            .map(|line| {
+                let cut = line
+                    .char_indices()
+                    .rev()
+                    .take(trailing(line).min(common))
+                    .last()
+                    .map_or(line.len(), |(i, _)| i);
+                &line[..cut]
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self::new(text)
+    }
+
+    /// Stack arts vertically with `spacing` blank rows between them
+    pub fn stack(arts: &[AsciiArt], spacing: usize) -> Self {
+        let mut lines: Vec<String> = Vec::new();
+        for (i, art) in arts.iter().enumerate() {
+            if i > 0 {
+                lines.extend(std::iter::repeat_n(String::new(), spacing));
+            }
+            lines.extend(art.lines.iter().cloned());
+        }
+
+        Self::new(lines.join("\n"))
+    }
+
     pub fn get_lines(&self) -> &[String] {
         &self.lines
     }
@@ -34,29 +127,53 @@ impl AsciiArt {
         self.lines.join("\n")
     }
 
-    /// Get character at position
+    /// Get the grapheme cluster at position
     #[allow(dead_code)]
-    pub fn char_at(&self, x: usize, y: usize) -> Option<char> {
-        self.lines.get(y)?.chars().nth(x)
+    pub fn char_at(&self, x: usize, y: usize) -> Option<&str> {
+        self.lines.get(y)?.graphemes(true).nth(x)
+    }
+
+    /// No visible characters at all, e.g. figlet output for an empty string
+    pub fn is_blank(&self) -> bool {
+        self.char_count() == 0
     }
 
-    /// Count non-whitespace characters
+    /// Count visible grapheme clusters
     pub fn char_count(&self) -> usize {
         self.lines
             .iter()
-            .flat_map(|line| line.chars())
-            .filter(|c| !c.is_whitespace())
+            .flat_map(|line| line.graphemes(true))
+            .filter(|g| !is_blank_grapheme(g))
             .count()
     }
 
-    /// Get all character positions
-    pub fn char_positions(&self) -> Vec<(usize, usize, char)> {
+    /// Column ranges holding glyphs, split wherever at least `min_gap`
+    /// columns in a row are blank on every line. With a gap wider than the
+    /// spacing inside letters, these are the art's words.
+    pub fn column_groups(&self, min_gap: usize) -> Vec<Range<usize>> {
+        let mut filled = vec![false; self.width];
+        for (x, _, _) in self.grapheme_positions() {
+            filled[x] = true;
+        }
+
+        let mut groups: Vec<Range<usize>> = Vec::new();
+        for x in (0..self.width).filter(|&x| filled[x]) {
+            match groups.last_mut() {
+                Some(group) if x - group.end < min_gap.max(1) => group.end = x + 1,
+                _ => groups.push(x..x + 1),
+            }
+        }
+        groups
+    }
+
+    /// Get all visible grapheme clusters with their cell positions
+    pub fn grapheme_positions(&self) -> Vec<(usize, usize, &str)> {
         let mut positions = Vec::new();
 
         for (y, line) in self.lines.iter().enumerate() {
-            for (x, ch) in line.chars().enumerate() {
-                if !ch.is_whitespace() {
-                    positions.push((x, y, ch));
+            for (x, grapheme) in line.graphemes(true).enumerate() {
+                if !is_blank_grapheme(grapheme) {
+                    positions.push((x, y, grapheme));
                 }
             }
         }
@@ -71,7 +188,13 @@ impl AsciiArt {
         }
 
         if opacity <= 0.0 {
-            return " ".repeat(self.width).repeat(self.height);
+            // Blank, but with the same shape so layout doesn't jump
+            return self
+                .lines
+                .iter()
+                .map(|line| " ".repeat(line.graphemes(true).count()))
+                .collect::<Vec<_>>()
+                .join("\n");
         }
 
         // For ASCII, we can simulate fade by replacing chars with lighter ones
@@ -82,44 +205,524 @@ impl AsciiArt {
         self.lines
             .iter()
             .map(|line| {
-                line.chars()
-                    .map(|ch| if ch.is_whitespace() { ch } else { fade_char })
+                line.graphemes(true)
+                    .map(|g| {
+                        if is_blank_grapheme(g) {
+                            g.to_string()
+                        } else {
+                            fade_char.to_string()
+                        }
+                    })
                     .collect::<String>()
             })
             .collect::<Vec<_>>()
             .join("\n")
     }
 
+    /// Every line right-padded with spaces to the full width, for column
+    /// work that expects each line to have a cell in every column. `new`
+    /// leaves lines ragged since centered lines are placed by their own
+    /// width, which padding would change.
+    pub fn to_rectangular(&self) -> Self {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|line| {
+                let padding = self.width - line.graphemes(true).count();
+                format!("{}{}", line, " ".repeat(padding))
+            })
+            .collect();
+        Self {
+            lines,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Mirror the art left to right. Lines are padded to the full width
+    /// first so they keep their place in the block, and glyphs such as `/`
+    /// and `(` turn into their mirror images. The padding that ends up on
+    /// the right is trimmed again while the width is kept, so flipping
+    /// twice gives back the original, short lines and all. Trailing
+    /// whitespace the art had of its own is trimmed along with it.
+    pub fn flip_horizontal(&self) -> Self {
+        let lines: Vec<String> = self
+            .to_rectangular()
+            .lines
+            .iter()
+            .map(|line| {
+                let flipped: String = line
+                    .graphemes(true)
+                    .rev()
+                    .map(|g| mirror(g, &MIRRORED_HORIZONTALLY))
+                    .collect();
+                flipped.trim_end().to_string()
+            })
+            .collect();
+        Self {
+            lines,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Mirror the art top to bottom, turning `/` into `\` and back
+    pub fn flip_vertical(&self) -> Self {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .rev()
+            .map(|line| {
+                line.graphemes(true)
+                    .map(|g| mirror(g, &MIRRORED_VERTICALLY))
+                    .collect()
+            })
+            .collect();
+        Self::new(lines.join("\n"))
+    }
+
+    pub fn flip(&self, flip: Flip) -> Self {
+        match flip {
+            Flip::None => self.clone(),
+            Flip::Horizontal => self.flip_horizontal(),
+            Flip::Vertical => self.flip_vertical(),
+            Flip::Both => self.flip_horizontal().flip_vertical(),
+        }
+    }
+
+    /// Turn the art clockwise by `quarters` quarter turns, so 1 stands a
+    /// wide banner on end. Ragged lines are padded to a rectangle first;
+    /// glyphs move to their new cells but are drawn as they were.
+    pub fn rotate(&self, quarters: u8) -> Self {
+        let quarters = quarters % 4;
+        if quarters == 0 {
+            return self.clone();
+        }
+
+        let rectangular = self.to_rectangular();
+        let grid: Vec<Vec<&str>> = rectangular
+            .lines
+            .iter()
+            .map(|line| line.graphemes(true).collect())
+            .collect();
+        let (width, height) = (self.width, self.height);
+
+        let lines: Vec<String> = match quarters {
+            1 => (0..width)
+                .map(|x| (0..height).rev().map(|y| grid[y][x]).collect())
+                .collect(),
+            2 => grid
+                .iter()
+                .rev()
+                .map(|row| row.iter().rev().copied().collect())
+                .collect(),
+            _ => (0..width)
+                .rev()
+                .map(|x| (0..height).map(|y| grid[y][x]).collect())
+                .collect(),
+        };
+        Self::new(lines.join("\n"))
+    }
+
     /// Scale the ASCII art
     pub fn scale(&self, factor: f64) -> Self {
-        if factor <= 0.0 {
+        if !factor.is_finite() || factor <= 0.0 {
             return Self::new(String::new());
         }
 
-        if (factor - 1.0).abs() < 0.01 {
+        if (factor - 1.0).abs() < 0.01 || self.height == 0 {
             return self.clone();
         }
 
-        // Simple scaling by character repetition
+        // Simple scaling by character repetition; both the repeat count and
+        // the step are kept at 1 or more so no factor can make step_by panic
         let lines: Vec<String> = if factor > 1.0 {
+            let repeat = (factor as usize).max(1);
             self.lines
                 .iter()
                 .flat_map(|line| {
                     let scaled_line: String = line
-                        .chars()
-                        .flat_map(|ch| std::iter::repeat_n(ch, factor as usize))
+                        .graphemes(true)
+                        .flat_map(|g| std::iter::repeat_n(g, repeat))
                         .collect();
-                    std::iter::repeat_n(scaled_line, factor as usize)
+                    std::iter::repeat_n(scaled_line, repeat)
                 })
                 .collect()
         } else {
+            let step = ((1.0 / factor) as usize).max(1);
             self.lines
                 .iter()
-                .step_by((1.0 / factor) as usize)
-                .map(|line| line.chars().step_by((1.0 / factor) as usize).collect())
+                .step_by(step)
+                .map(|line| line.graphemes(true).step_by(step).collect())
                 .collect()
         };
 
         Self::new(lines.join("\n"))
     }
+
+    /// Scale the ASCII art around its center. Upscaling repeats cells
+    /// symmetrically already; downscaling samples rows and columns with a
+    /// phase that leaves equal margins on both sides instead of always
+    /// keeping the first row and column, so the art shrinks toward its center
+    pub fn scale_centered(&self, factor: f64) -> Self {
+        if !factor.is_finite() || factor <= 0.0 || factor >= 1.0 {
+            return self.scale(factor);
+        }
+        if (factor - 1.0).abs() < 0.01 {
+            return self.clone();
+        }
+
+        let step = ((1.0 / factor) as usize).max(1);
+        let phase = |len: usize| len.saturating_sub(1) % step / 2;
+        // One phase for every line, so ragged lines keep the same columns
+        let column_phase = phase(self.width);
+
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .skip(phase(self.height))
+            .step_by(step)
+            .map(|line| {
+                line.graphemes(true)
+                    .skip(column_phase)
+                    .step_by(step)
+                    .collect()
+            })
+            .collect();
+
+        Self::new(lines.join("\n"))
+    }
+}
+
+/// Quarter turns for a `--rotate` angle of 0, 90, 180 or 270 degrees
+pub fn quarter_turns(degrees: u16) -> Result<u8> {
+    match degrees {
+        0 | 90 | 180 | 270 => Ok((degrees / 90) as u8),
+        _ => bail!(
+            "Unsupported rotation: {} (expected 0, 90, 180 or 270)",
+            degrees
+        ),
+    }
+}
+
+/// `grapheme`'s partner in `pairs`, or the grapheme itself
+fn mirror<'a>(grapheme: &'a str, pairs: &[(&'static str, &'static str)]) -> &'a str {
+    for &(a, b) in pairs {
+        if grapheme == a {
+            return b;
+        }
+        if grapheme == b {
+            return a;
+        }
+    }
+    grapheme
+}
+
+/// The grapheme clusters of one line of art. Figlet fonts are nearly all
+/// ASCII, where every byte is a cluster of its own, so such lines skip the
+/// segmentation tables.
+pub fn glyphs(line: &str) -> Glyphs<'_> {
+    if line.is_ascii() {
+        Glyphs::Ascii(line)
+    } else {
+        Glyphs::Graphemes(line.graphemes(true))
+    }
+}
+
+/// Iterator returned by [`glyphs`]
+pub enum Glyphs<'a> {
+    Ascii(&'a str),
+    Graphemes(unicode_segmentation::Graphemes<'a>),
+}
+
+impl<'a> Iterator for Glyphs<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        match self {
+            Glyphs::Ascii(rest) => {
+                if rest.is_empty() {
+                    return None;
+                }
+                let (glyph, tail) = rest.split_at(1);
+                *rest = tail;
+                Some(glyph)
+            }
+            Glyphs::Graphemes(graphemes) => graphemes.next(),
+        }
+    }
+
+    fn count(self) -> usize {
+        match self {
+            Glyphs::Ascii(rest) => rest.len(),
+            Glyphs::Graphemes(graphemes) => graphemes.count(),
+        }
+    }
+}
+
+/// Whether a grapheme cluster is blank: whitespace only. A combining mark
+/// at the start of a line, with nothing to sit on, still takes a cell of
+/// its own, so it is a glyph like any other.
+pub fn is_blank_grapheme(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::buffer::FrameBuffer;
+    use crate::utils::layout::Alignment;
+
+    fn sample() -> AsciiArt {
+        AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string())
+    }
+
+    #[test]
+    fn test_scale_does_not_panic() {
+        let art = sample();
+        for factor in [
+            0.1,
+            0.3,
+            0.5,
+            0.99,
+            1.005,
+            1.9,
+            2.0,
+            0.0,
+            -1.0,
+            f64::NAN,
+            f64::INFINITY,
+        ] {
+            let _ = art.scale(factor);
+        }
+        assert!(art.scale(0.1).height() >= 1);
+        assert_eq!(art.scale(1.9).height(), art.height());
+        assert_eq!(art.scale(2.0).height(), art.height() * 2);
+    }
+
+    #[test]
+    fn test_glyphs_match_graphemes() {
+        for line in ["| |_| |", "", "caf\u{e9} e\u{301}", "\u{301}x"] {
+            let expected: Vec<&str> = line.graphemes(true).collect();
+            assert_eq!(glyphs(line).collect::<Vec<_>>(), expected, "{line:?}");
+            assert_eq!(glyphs(line).count(), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_flip_horizontal() {
+        let art = AsciiArt::new("/|\n(_".to_string());
+        assert_eq!(art.flip_horizontal().render(), "|\\\n_)");
+        // Short lines are padded so they stay in place
+        let ragged = AsciiArt::new("ab\nabcd".to_string());
+        assert_eq!(ragged.flip_horizontal().render(), "  ba\ndcba");
+    }
+
+    #[test]
+    fn test_flip_vertical() {
+        let art = AsciiArt::new(" /\n/_".to_string());
+        assert_eq!(art.flip_vertical().render(), "\\_\n \\");
+    }
+
+    /// The art as it shows on screen, without trailing whitespace
+    fn visible(art: &AsciiArt) -> Vec<String> {
+        art.render()
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_flipping_twice_returns_the_original() {
+        let art = sample();
+        for flip in [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both] {
+            assert_eq!(
+                visible(&art.flip(flip).flip(flip)),
+                visible(&art),
+                "{flip:?}"
+            );
+        }
+        assert_eq!(
+            visible(&art.flip_horizontal().flip_horizontal()),
+            visible(&art)
+        );
+        assert_eq!(art.flip_vertical().flip_vertical().render(), art.render());
+
+        // Ragged lines come back without the padding flipping added
+        let ragged = AsciiArt::new("ab\nabcd\n  c".to_string());
+        for flip in [Flip::Horizontal, Flip::Both] {
+            let twice = ragged.flip(flip).flip(flip);
+            assert_eq!(twice.render(), ragged.render(), "{flip:?}");
+            assert_eq!(twice.width(), ragged.width());
+        }
+        // A blank left column survives the trip as the art's width
+        let indented = AsciiArt::new("  a\n  b".to_string());
+        assert_eq!(indented.flip_horizontal().render(), "a\nb");
+        assert_eq!(
+            indented.flip_horizontal().flip_horizontal().render(),
+            "  a\n  b"
+        );
+
+        let empty = AsciiArt::new(String::new());
+        assert_eq!(empty.flip(Flip::Both).render(), "");
+    }
+
+    #[test]
+    fn test_to_rectangular() {
+        let art = AsciiArt::new(" _\n|_|\n\n\u{e9}".to_string());
+        let rectangular = art.to_rectangular();
+        assert_eq!((rectangular.width(), rectangular.height()), (3, 4));
+        for line in rectangular.get_lines() {
+            assert_eq!(line.graphemes(true).count(), art.width(), "{line:?}");
+        }
+        assert_eq!(rectangular.char_at(2, 0), Some(" "));
+
+        // Padding is blank cells, so every glyph keeps its column
+        let draw = |art: &AsciiArt| {
+            let mut buffer = FrameBuffer::new(9, 6);
+            buffer.draw_aligned(&art.render(), Alignment::Left, Default::default());
+            buffer.to_text()
+        };
+        assert_eq!(draw(&rectangular), draw(&art));
+        assert_eq!(sample().to_rectangular().render(), sample().render());
+    }
+
+    #[test]
+    fn test_rotate_quarter_turns() {
+        let art = AsciiArt::new("abc\ndef".to_string());
+        assert_eq!(art.rotate(1).render(), "da\neb\nfc");
+        assert_eq!((art.rotate(1).width(), art.rotate(1).height()), (2, 3));
+        assert_eq!(art.rotate(2).render(), "fed\ncba");
+        assert_eq!(art.rotate(3).render(), "cf\nbe\nad");
+        assert_eq!(art.rotate(4).render(), art.render());
+
+        // Ragged lines are padded before turning
+        let ragged = AsciiArt::new("ab\nc".to_string());
+        assert_eq!(ragged.rotate(1).render(), "ca\n b");
+        assert_eq!(ragged.rotate(2).render(), " c\nba");
+
+        let full_turn = (0..4).fold(sample(), |art, _| art.rotate(1));
+        assert_eq!(full_turn.render(), sample().render());
+        assert_eq!(AsciiArt::new(String::new()).rotate(1).render(), "");
+    }
+
+    #[test]
+    fn test_quarter_turns() {
+        assert_eq!(quarter_turns(0).unwrap(), 0);
+        assert_eq!(quarter_turns(270).unwrap(), 3);
+        assert!(quarter_turns(45).is_err());
+        assert!(quarter_turns(360).is_err());
+    }
+
+    #[test]
+    fn test_parse_flip() {
+        assert_eq!(Flip::parse("both").unwrap(), Flip::Both);
+        assert_eq!(Flip::parse("none").unwrap(), Flip::None);
+        assert!(Flip::parse("sideways").is_err());
+    }
+
+    #[test]
+    fn test_empty_art() {
+        let art = AsciiArt::new(String::new());
+        assert_eq!(art.width(), 0);
+        assert_eq!(art.height(), 0);
+        assert!(art.is_blank());
+        assert!(AsciiArt::new("   \n  ".to_string()).is_blank());
+        assert!(!sample().is_blank());
+
+        for factor in [0.3, 0.5, 2.0, 0.0] {
+            assert_eq!(art.scale(factor).height(), 0);
+            assert_eq!(art.scale_centered(factor).height(), 0);
+        }
+        for opacity in [0.0, 0.5, 1.0] {
+            assert_eq!(art.apply_fade(opacity), "");
+        }
+    }
+
+    #[test]
+    fn test_trimmed_drops_padding() {
+        let art = AsciiArt::trimmed(" _   _   \n| |_| |  \n|_| |_|    \n   \n\n");
+        assert_eq!(art.height(), 3);
+        assert_eq!(art.get_lines(), [" _   _ ", "| |_| |", "|_| |_|  "]);
+
+        let art = AsciiArt::trimmed("\n  ab  \n\n  cd  \n");
+        assert_eq!(art.get_lines(), ["  ab", "", "  cd"]);
+
+        assert_eq!(AsciiArt::trimmed(" \n\n").height(), 0);
+    }
+
+    #[test]
+    fn test_trimmed_multibyte_whitespace() {
+        // Two ASCII spaces are as many bytes as a third of U+3000
+        let art = AsciiArt::trimmed("a  \nb\u{3000}");
+        assert_eq!(art.get_lines(), ["a ", "b"]);
+
+        let art = AsciiArt::trimmed("ab\u{a0}\u{a0}\nc\u{a0}\u{a0}\u{a0}");
+        assert_eq!(art.get_lines(), ["ab", "c\u{a0}"]);
+    }
+
+    #[test]
+    fn test_column_groups() {
+        let art = AsciiArt::new("|_| |   __\n| | |  |__".to_string());
+        assert_eq!(art.column_groups(2), vec![0..5, 7..10]);
+        assert_eq!(art.column_groups(1), vec![0..3, 4..5, 7..10]);
+        assert_eq!(art.column_groups(4), vec![0..10]);
+        assert!(AsciiArt::new("   ".to_string()).column_groups(2).is_empty());
+    }
+
+    #[test]
+    fn test_combining_accent_is_one_cell() {
+        // "é" spelled as 'e' followed by U+0301 COMBINING ACUTE ACCENT
+        let art = AsciiArt::new("e\u{301}x\n ab".to_string());
+        assert_eq!(art.char_count(), 4);
+        assert_eq!(art.width(), 3);
+        assert_eq!(art.char_at(0, 0), Some("e\u{301}"));
+        assert_eq!(art.char_at(1, 0), Some("x"));
+        assert_eq!(
+            art.grapheme_positions(),
+            [(0, 0, "e\u{301}"), (1, 0, "x"), (1, 1, "a"), (2, 1, "b")]
+        );
+
+        assert_eq!(art.apply_fade(0.0), "  \n   ");
+        assert_eq!(art.apply_fade(0.5), "~~\n ~~");
+        assert_eq!(art.scale(2.0).get_lines()[0], "e\u{301}e\u{301}xx");
+        assert_eq!(art.scale(0.5).get_lines(), ["e\u{301}"]);
+    }
+
+    /// Center of the inked cells when the art is drawn centered on a grid
+    fn ink_center(art: &AsciiArt) -> (f64, f64) {
+        let mut buffer = FrameBuffer::new(80, 40);
+        buffer.draw_centered(&art.render());
+        let text = buffer.to_text();
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = (usize::MAX, 0, usize::MAX, 0);
+        for (y, line) in text.lines().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                if !ch.is_whitespace() {
+                    min_x = min_x.min(x);
+                    max_x = max_x.max(x);
+                    min_y = min_y.min(y);
+                    max_y = max_y.max(y);
+                }
+            }
+        }
+        ((min_x + max_x) as f64 / 2.0, (min_y + max_y) as f64 / 2.0)
+    }
+
+    #[test]
+    fn test_scale_centered_keeps_center() {
+        let art = sample();
+        let (cx, cy) = ink_center(&art);
+        for factor in [2.0, 0.5, 0.3] {
+            let (sx, sy) = ink_center(&art.scale_centered(factor));
+            assert!((sx - cx).abs() <= 1.0, "x drifted at {factor}");
+            assert!((sy - cy).abs() <= 1.0, "y drifted at {factor}");
+        }
+    }
+
+    #[test]
+    fn test_scale_centered_keeps_ragged_columns_aligned() {
+        // Every third column from column 1 on, on short lines too
+        let art = AsciiArt::new("abcdef\nxxxxxx\nxxxxxx\nABCD".to_string());
+        assert_eq!(art.scale_centered(0.3).get_lines(), ["be", "B"]);
+    }
 }
@@ -0,0 +1,212 @@
+use anyhow::{bail, Result};
+
+use super::{ansi, buffer::FrameBuffer};
+
+/// Line style of a box drawn around the art
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStyle {
+    Single,
+    Double,
+    Rounded,
+    Ascii,
+}
+
+/// Corner and edge glyphs of a border style
+#[derive(Debug, Clone, Copy)]
+pub struct BorderChars {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+}
+
+impl BorderStyle {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "single" => Ok(Self::Single),
+            "double" => Ok(Self::Double),
+            "rounded" => Ok(Self::Rounded),
+            "ascii" => Ok(Self::Ascii),
+            _ => bail!(
+                "Unknown border style: {} (expected single, double, rounded or ascii)",
+                name
+            ),
+        }
+    }
+
+    pub fn chars(&self) -> BorderChars {
+        let (top_left, top_right, bottom_left, bottom_right, horizontal, vertical) = match self {
+            Self::Single => ('┌', '┐', '└', '┘', '─', '│'),
+            Self::Double => ('╔', '╗', '╚', '╝', '═', '║'),
+            Self::Rounded => ('╭', '╮', '╰', '╯', '─', '│'),
+            Self::Ascii => ('+', '+', '+', '+', '-', '|'),
+        };
+        BorderChars {
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        }
+    }
+}
+
+/// Lines of an empty box `width` columns wide and `height` rows tall,
+/// border included (both are raised to at least 2)
+pub fn border_lines(width: usize, height: usize, style: BorderStyle) -> Vec<String> {
+    let c = style.chars();
+    let inner = width.max(2) - 2;
+
+    let mut lines = Vec::with_capacity(height.max(2));
+    lines.push(format!(
+        "{}{}{}",
+        c.top_left,
+        c.horizontal.to_string().repeat(inner),
+        c.top_right
+    ));
+    for _ in 0..height.max(2) - 2 {
+        lines.push(format!("{}{}{}", c.vertical, " ".repeat(inner), c.vertical));
+    }
+    lines.push(format!(
+        "{}{}{}",
+        c.bottom_left,
+        c.horizontal.to_string().repeat(inner),
+        c.bottom_right
+    ));
+    lines
+}
+
+/// Most blank cells `--border-padding` puts between the art and the border
+pub const MAX_PADDING: usize = 16;
+
+/// A box around the rendered art, either following the frame (offsets and
+/// scale included) or fixed to the art's resting size and position
+#[derive(Debug, Clone, Copy)]
+pub struct Border {
+    pub style: BorderStyle,
+    pub padding: usize,
+    pub fixed: bool,
+}
+
+impl Border {
+    pub fn new(style: BorderStyle) -> Self {
+        Self {
+            style,
+            padding: 1,
+            fixed: false,
+        }
+    }
+
+    pub fn with_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_fixed(mut self, fixed: bool) -> Self {
+        self.fixed = fixed;
+        self
+    }
+
+    /// Box the frame text itself, keeping each line centered within the
+    /// block the same way the terminal centers it
+    pub fn wrap(&self, text: &str) -> String {
+        let lines: Vec<&str> = text.lines().collect();
+        let max_width = lines
+            .iter()
+            .map(|l| ansi::visual_width(l))
+            .max()
+            .unwrap_or(0);
+        let inner_width = max_width + self.padding * 2;
+        let c = self.style.chars();
+
+        let mut out = border_lines(
+            inner_width + 2,
+            lines.len() + self.padding * 2 + 2,
+            self.style,
+        );
+        let pad = " ".repeat(self.padding);
+        for (i, line) in lines.iter().enumerate() {
+            let width = ansi::visual_width(line);
+            let left = (max_width - width) / 2;
+            let right = max_width - width - left;
+            out[1 + self.padding + i] = format!(
+                "{}{}{}{}{}{}{}",
+                c.vertical,
+                pad,
+                " ".repeat(left),
+                line,
+                " ".repeat(right),
+                pad,
+                c.vertical
+            );
+        }
+
+        out.join("\n")
+    }
+
+    /// Box sized for `art_width` x `art_height` with the frame text laid out
+    /// inside it, shifted by the effect offset and clipped to the interior
+    pub fn frame(
+        &self,
+        text: &str,
+        art_width: usize,
+        art_height: usize,
+        offset_x: i32,
+        offset_y: i32,
+    ) -> String {
+        let inner_width = (art_width + self.padding * 2) as u16;
+        let inner_height = (art_height + self.padding * 2) as u16;
+
+        let mut inner = FrameBuffer::new(inner_width, inner_height);
+        inner.draw_frame(text, offset_x, offset_y);
+
+        let mut outer = FrameBuffer::new(inner_width + 2, inner_height + 2);
+        let lines = border_lines(
+            inner_width as usize + 2,
+            inner_height as usize + 2,
+            self.style,
+        );
+        outer.draw_text(0, 0, &lines.join("\n"));
+        outer.draw_text(1, 1, &inner.to_text());
+
+        outer.to_text()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_border_lines_corners_and_size() {
+        let lines = border_lines(5, 3, BorderStyle::Single);
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|l| l.chars().count() == 5));
+        assert_eq!(lines[0], "┌───┐");
+        assert_eq!(lines[1], "│   │");
+        assert_eq!(lines[2], "└───┘");
+
+        let lines = border_lines(5, 3, BorderStyle::Rounded);
+        assert!(lines[0].starts_with('╭') && lines[2].ends_with('╯'));
+    }
+
+    #[test]
+    fn test_wrap_pads_text() {
+        let boxed = Border::new(BorderStyle::Ascii).wrap("ab\nabcd");
+        let lines: Vec<&str> = boxed.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "+------+");
+        assert_eq!(lines[2], "|  ab  |");
+        assert_eq!(lines[3], "| abcd |");
+    }
+
+    #[test]
+    fn test_fixed_border_clips_offset_text() {
+        let border = Border::new(BorderStyle::Ascii).with_padding(0);
+        let boxed = border.frame("abcd", 4, 1, 2, 0);
+        assert_eq!(boxed, "+----+\n|  ab|\n+----+");
+    }
+}
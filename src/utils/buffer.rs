@@ -0,0 +1,324 @@
+use anyhow::Result;
+use crossterm::{cursor, queue};
+use std::io::Write;
+
+use super::{
+    ansi,
+    layout::{Alignment, VerticalPlacement},
+};
+
+const RESET: &str = "\x1b[0m";
+
+/// A single terminal cell: a visible character and the SGR style active for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: String,
+}
+
+impl Cell {
+    pub fn blank() -> Self {
+        Self {
+            ch: ' ',
+            style: String::new(),
+        }
+    }
+}
+
+/// Grid of cells covering the whole terminal for one frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameBuffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::blank(); width as usize * height as usize],
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get(y as usize * self.width as usize + x as usize)
+    }
+
+    fn set(&mut self, x: i32, y: i32, cell: Cell) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let index = y as usize * self.width as usize + x as usize;
+        self.cells[index] = cell;
+    }
+
+    /// Write an ANSI-colored line starting at (x, y), clipping to the grid
+    pub fn draw_line(&mut self, x: i32, y: i32, line: &str) {
+        for (i, cell) in parse_cells(line).into_iter().enumerate() {
+            self.set(x + i as i32, y, cell);
+        }
+    }
+
+    /// Write multi-line text with its top-left corner at (x, y)
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str) {
+        for (i, line) in text.lines().enumerate() {
+            self.draw_line(x, y + i as i32, line);
+        }
+    }
+
+    /// Write multi-line text centered in the grid, centering each line
+    /// within the block the same way `TerminalManager::print_centered` does
+    #[allow(dead_code)]
+    pub fn draw_centered(&mut self, text: &str) {
+        self.draw_aligned(text, Alignment::Center, VerticalPlacement::default());
+    }
+
+    /// Write multi-line text placed vertically by `vertical` and horizontally
+    /// by `alignment`, aligning each line within the block the same way
+    pub fn draw_aligned(&mut self, text: &str, alignment: Alignment, vertical: VerticalPlacement) {
+        let lines: Vec<&str> = text.lines().collect();
+        let max_width = lines
+            .iter()
+            .map(|l| ansi::visual_width(l))
+            .max()
+            .unwrap_or(0) as i32;
+        let height = lines.len() as u16;
+
+        let start_x = alignment.start_x(self.width as i32, max_width).max(0);
+        let start_y = vertical.start_y(self.height as i32, height as i32);
+
+        for (i, line) in lines.iter().enumerate() {
+            let line_width = ansi::visual_width(line) as i32;
+            let x = start_x + alignment.start_x(max_width, line_width).max(0);
+            let y = start_y + i as i32;
+            self.draw_line(x, y, line);
+        }
+    }
+
+    /// Lay out an effect frame: centered when there is no offset, otherwise
+    /// centered as a block and shifted by the offset
+    pub fn draw_frame(&mut self, text: &str, offset_x: i32, offset_y: i32) {
+        self.draw_frame_aligned(
+            text,
+            offset_x,
+            offset_y,
+            Alignment::Center,
+            VerticalPlacement::default(),
+        );
+    }
+
+    /// Like `draw_frame`, with the block placed by `alignment` and `vertical`
+    pub fn draw_frame_aligned(
+        &mut self,
+        text: &str,
+        offset_x: i32,
+        offset_y: i32,
+        alignment: Alignment,
+        vertical: VerticalPlacement,
+    ) {
+        if offset_x == 0 && offset_y == 0 {
+            self.draw_aligned(text, alignment, vertical);
+        } else {
+            let (x, y) = offset_origin(
+                text,
+                offset_x,
+                offset_y,
+                self.width,
+                self.height,
+                alignment,
+                vertical,
+            );
+            self.draw_text(x, y, text);
+        }
+    }
+
+    /// The grid as ANSI-colored text, one line per row
+    pub fn to_text(&self) -> String {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(cells_to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Emit the escape sequences needed to turn `previous` into `self`.
+    /// With no previous frame (or a resize) every cell is redrawn.
+    pub fn diff(&self, previous: Option<&FrameBuffer>, out: &mut impl Write) -> Result<()> {
+        let previous = previous.filter(|p| p.width == self.width && p.height == self.height);
+        let mut cursor_at: Option<(u16, u16)> = None;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y as usize * self.width as usize + x as usize;
+                let cell = &self.cells[index];
+
+                if let Some(prev) = previous {
+                    if &prev.cells[index] == cell {
+                        continue;
+                    }
+                }
+
+                if cursor_at != Some((x, y)) {
+                    queue!(out, cursor::MoveTo(x, y))?;
+                }
+
+                if cell.style.is_empty() {
+                    write!(out, "{}", cell.ch)?;
+                } else {
+                    write!(out, "{}{}{}", cell.style, cell.ch, RESET)?;
+                }
+
+                cursor_at = Some((x + 1, y));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Top-left position of text placed in a `width` x `height` area by
+/// `alignment` and `vertical` and shifted by an effect offset. It may lie
+/// outside the area, for text partly scrolled off screen.
+pub fn offset_origin(
+    text: &str,
+    offset_x: i32,
+    offset_y: i32,
+    width: u16,
+    height: u16,
+    alignment: Alignment,
+    vertical: VerticalPlacement,
+) -> (i32, i32) {
+    let lines: Vec<&str> = text.lines().collect();
+    let text_height = lines.len() as i32;
+    let text_width = lines
+        .iter()
+        .map(|l| ansi::visual_width(l))
+        .max()
+        .unwrap_or(0) as i32;
+
+    let base_x = alignment.start_x(width as i32, text_width);
+    let base_y = vertical.start_y(height as i32, text_height);
+
+    (base_x + offset_x, base_y + offset_y)
+}
+
+/// Split an ANSI-colored line into cells, carrying the active SGR style
+/// onto each visible character
+pub fn parse_cells(line: &str) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let mut style = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            if chars.peek() == Some(&'[') {
+                let mut sequence = String::from("\x1b");
+                sequence.push(chars.next().unwrap());
+                for c in chars.by_ref() {
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+
+                if is_reset(&sequence) {
+                    style.clear();
+                } else {
+                    style.push_str(&sequence);
+                }
+            }
+        } else {
+            cells.push(Cell {
+                ch,
+                style: style.clone(),
+            });
+        }
+    }
+
+    cells
+}
+
+/// Turn a row of cells back into an ANSI-colored string
+pub fn cells_to_string(cells: &[Cell]) -> String {
+    cells
+        .iter()
+        .map(|cell| {
+            if cell.style.is_empty() {
+                cell.ch.to_string()
+            } else {
+                format!("{}{}{}", cell.style, cell.ch, RESET)
+            }
+        })
+        .collect()
+}
+
+fn is_reset(sequence: &str) -> bool {
+    matches!(sequence, "\x1b[0m" | "\x1b[m" | "\x1b[39m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_frames_produce_no_output() {
+        let mut first = FrameBuffer::new(20, 5);
+        first.draw_centered("\x1b[38;2;255;0;0mHi\x1b[39m\nthere");
+        let second = first.clone();
+
+        let mut out = Vec::new();
+        second.diff(Some(&first), &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_diff_only_emits_changed_cells() {
+        let mut first = FrameBuffer::new(10, 2);
+        first.draw_text(0, 0, "abc");
+        let mut second = FrameBuffer::new(10, 2);
+        second.draw_text(0, 0, "abd");
+
+        let mut out = Vec::new();
+        second.diff(Some(&first), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(ansi::strip_ansi(&out), "d");
+    }
+
+    #[test]
+    fn test_resize_redraws_everything() {
+        let first = FrameBuffer::new(4, 1);
+        let mut second = FrameBuffer::new(5, 1);
+        second.draw_text(0, 0, "hello");
+
+        let mut out = Vec::new();
+        second.diff(Some(&first), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(ansi::strip_ansi(&out), "hello");
+    }
+
+    #[test]
+    fn test_parse_cells_keeps_style() {
+        let cells = parse_cells("\x1b[38;2;1;2;3mA\x1b[39m B");
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0].style, "\x1b[38;2;1;2;3m");
+        assert!(cells[1].style.is_empty());
+        assert!(cells[2].style.is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use std::io::Read;
+
+/// Text given on the command line, or read from `stdin` when it is `-`, or
+/// missing while stdin is piped. `None` means there is nothing to render.
+pub fn resolve_text(
+    text: Option<&str>,
+    mut stdin: impl Read,
+    stdin_is_terminal: bool,
+) -> Result<Option<String>> {
+    match text {
+        Some("-") => {}
+        Some(text) => return Ok(Some(text.to_string())),
+        None if stdin_is_terminal => return Ok(None),
+        None => {}
+    }
+
+    let mut input = String::new();
+    stdin
+        .read_to_string(&mut input)
+        .context("Failed to read text from stdin")?;
+
+    // `echo` and friends end with a newline that would become an empty banner
+    Ok(Some(input.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_text_argument_wins_over_stdin() {
+        let text = resolve_text(Some("Hi"), Cursor::new("piped"), false).unwrap();
+        assert_eq!(text.as_deref(), Some("Hi"));
+    }
+
+    #[test]
+    fn test_reads_piped_stdin() {
+        let text = resolve_text(None, Cursor::new("Hello\nWorld\n"), false).unwrap();
+        assert_eq!(text.as_deref(), Some("Hello\nWorld"));
+
+        let text = resolve_text(Some("-"), Cursor::new("Dash\r\n"), true).unwrap();
+        assert_eq!(text.as_deref(), Some("Dash"));
+    }
+
+    #[test]
+    fn test_interactive_stdin_is_not_read() {
+        let text = resolve_text(None, Cursor::new("ignored"), true).unwrap();
+        assert_eq!(text, None);
+    }
+}
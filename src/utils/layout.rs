@@ -0,0 +1,133 @@
+use anyhow::{bail, Result};
+
+/// Horizontal placement of the art within the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+impl Alignment {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "left" => Ok(Self::Left),
+            "center" => Ok(Self::Center),
+            "right" => Ok(Self::Right),
+            _ => bail!(
+                "Unknown alignment: {} (expected left, center or right)",
+                name
+            ),
+        }
+    }
+
+    /// Column where content `content_width` wide starts inside `available`
+    /// columns; negative when centered content is wider than the space
+    pub fn start_x(&self, available: i32, content_width: i32) -> i32 {
+        match self {
+            Self::Left => 0,
+            Self::Center => (available - content_width) / 2,
+            Self::Right => available - content_width,
+        }
+    }
+}
+
+/// Vertical anchor of the art within the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlignment {
+    Top,
+    #[default]
+    Middle,
+    Bottom,
+}
+
+impl VerticalAlignment {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "top" => Ok(Self::Top),
+            "middle" => Ok(Self::Middle),
+            "bottom" => Ok(Self::Bottom),
+            _ => bail!(
+                "Unknown vertical alignment: {} (expected top, middle or bottom)",
+                name
+            ),
+        }
+    }
+}
+
+/// Vertical placement of the art: anchored by `align` below `margin_top`
+/// blank rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VerticalPlacement {
+    pub align: VerticalAlignment,
+    pub margin_top: u16,
+}
+
+impl VerticalPlacement {
+    pub fn new(align: VerticalAlignment, margin_top: u16) -> Self {
+        Self { align, margin_top }
+    }
+
+    /// Row where content `content_height` tall starts inside `available`
+    /// rows. Middle centers in the space left under the margin. Clamped so
+    /// the art stays on screen whenever it fits, and never starts above it.
+    pub fn start_y(&self, available: i32, content_height: i32) -> i32 {
+        let margin = self.margin_top as i32;
+        let y = match self.align {
+            VerticalAlignment::Top => margin,
+            VerticalAlignment::Middle => margin + (available - margin - content_height) / 2,
+            VerticalAlignment::Bottom => available - content_height,
+        };
+        y.min(available - content_height).max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_x_for_each_alignment() {
+        assert_eq!(Alignment::Left.start_x(80, 20), 0);
+        assert_eq!(Alignment::Center.start_x(80, 20), 30);
+        assert_eq!(Alignment::Right.start_x(80, 20), 60);
+        assert_eq!(Alignment::Center.start_x(81, 20), 30);
+    }
+
+    #[test]
+    fn test_start_y_for_each_vertical_alignment() {
+        let at = |align| VerticalPlacement::new(align, 0).start_y(24, 6);
+        assert_eq!(at(VerticalAlignment::Top), 0);
+        assert_eq!(at(VerticalAlignment::Middle), 9);
+        assert_eq!(at(VerticalAlignment::Bottom), 18);
+
+        // Taller than the terminal: start at the top rather than above it
+        let tall = |align| VerticalPlacement::new(align, 0).start_y(4, 6);
+        assert_eq!(tall(VerticalAlignment::Middle), 0);
+        assert_eq!(tall(VerticalAlignment::Bottom), 0);
+    }
+
+    #[test]
+    fn test_start_y_with_top_margin() {
+        let at = |align, margin| VerticalPlacement::new(align, margin).start_y(24, 6);
+        assert_eq!(at(VerticalAlignment::Top, 3), 3);
+        assert_eq!(at(VerticalAlignment::Middle, 4), 11);
+        assert_eq!(at(VerticalAlignment::Bottom, 3), 18);
+
+        // A margin too large for the terminal pins the art to the bottom
+        assert_eq!(at(VerticalAlignment::Top, 40), 18);
+        assert_eq!(at(VerticalAlignment::Middle, 40), 18);
+    }
+
+    #[test]
+    fn test_parse_alignment() {
+        assert_eq!(Alignment::parse("right").unwrap(), Alignment::Right);
+        assert!(Alignment::parse("justify").is_err());
+        assert_eq!(
+            VerticalAlignment::parse("bottom").unwrap(),
+            VerticalAlignment::Bottom
+        );
+        assert!(VerticalAlignment::parse("center").is_err());
+    }
+}
@@ -1,3 +1,11 @@
 pub mod ansi;
 pub mod ascii;
+pub mod border;
+pub mod buffer;
+pub mod input;
+pub mod layout;
+pub mod plot;
+pub mod shadow;
+pub mod signals;
+pub mod suggest;
 pub mod terminal;
@@ -0,0 +1,86 @@
+use anyhow::Result;
+
+use crate::animation::easing::get_easing_function;
+
+/// Mark drawn in the cell `f(x)` falls into
+const POINT: char = '*';
+
+/// Plot `f` over 0.0..=1.0 on a `width` x `height` character grid, one point
+/// per column. The y range covers 0.0..=1.0 and widens to fit curves that
+/// overshoot it, so 1.0 is only the top row when nothing goes higher.
+pub fn plot(f: impl Fn(f64) -> f64, width: usize, height: usize) -> String {
+    if width == 0 || height == 0 {
+        return String::new();
+    }
+
+    let samples: Vec<f64> = (0..width)
+        .map(|column| f(column as f64 / (width - 1).max(1) as f64))
+        .collect();
+    let low = samples.iter().copied().fold(0.0, f64::min);
+    let high = samples.iter().copied().fold(1.0, f64::max);
+
+    let mut grid = vec![vec![' '; width]; height];
+    for (column, y) in samples.into_iter().enumerate() {
+        if !y.is_finite() {
+            continue;
+        }
+        let row = ((high - y) / (high - low) * (height - 1) as f64).round() as usize;
+        grid[row.min(height - 1)][column] = POINT;
+    }
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Plot the easing curve called `name`, as accepted by `--motion-ease`
+pub fn plot_easing(name: &str, width: usize, height: usize) -> Result<String> {
+    let easing = get_easing_function(name)?;
+    Ok(plot(|t| easing.ease(t), width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(plot: &str) -> Vec<(usize, usize)> {
+        plot.lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, ch)| *ch == POINT)
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_is_a_diagonal() {
+        let plot = plot_easing("linear", 40, 15).unwrap();
+        let lines: Vec<&str> = plot.lines().collect();
+        assert_eq!(lines.len(), 15);
+        assert!(lines.iter().all(|l| l.chars().count() == 40));
+
+        let points = points(&plot);
+        assert_eq!(points.len(), 40);
+        assert!(points.contains(&(39, 0)));
+        assert!(points.contains(&(0, 14)));
+        // Rows only ever climb from left to right
+        let mut columns = points.clone();
+        columns.sort();
+        assert!(columns.windows(2).all(|w| w[1].1 <= w[0].1));
+    }
+
+    #[test]
+    fn test_overshoot_stays_on_the_grid() {
+        let plot = plot(|t| 1.5 * t - 0.25, 10, 5);
+        assert_eq!(points(&plot).len(), 10);
+    }
+
+    #[test]
+    fn test_unknown_easing_errors() {
+        assert!(plot_easing("ease-sideways", 40, 15).is_err());
+    }
+}
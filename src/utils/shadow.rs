@@ -0,0 +1,168 @@
+use anyhow::{bail, Context, Result};
+
+use super::buffer::{self, Cell};
+use crate::parser::color::Color;
+
+/// Shadow color when `--shadow` doesn't name one
+pub const DEFAULT_COLOR: Color = Color::new(0x44, 0x44, 0x44);
+/// Farthest a shadow may fall from the art, in cells either way; the
+/// shadowed frame grows by the offset, so it can't be arbitrarily large
+pub const MAX_OFFSET: i32 = 32;
+
+/// A drop shadow: a copy of the glyphs offset by (dx, dy), drawn behind
+/// the art
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shadow {
+    pub dx: i32,
+    pub dy: i32,
+    pub color: Color,
+}
+
+impl Shadow {
+    /// Parse `dx,dy` or `dx,dy,color`, e.g. `1,1` or `2,1,#333333`
+    pub fn parse(spec: &str) -> Result<Self> {
+        // The color may have commas of its own, as in rgb(40, 40, 40)
+        let mut parts = spec.splitn(3, ',').map(str::trim);
+        let (Some(dx), Some(dy)) = (parts.next(), parts.next()) else {
+            bail!("Invalid shadow: {} (expected dx,dy or dx,dy,color)", spec);
+        };
+        let offset = |value: &str| {
+            let offset = value
+                .parse::<i32>()
+                .with_context(|| format!("Invalid shadow offset: {}", value))?;
+            if offset.unsigned_abs() > MAX_OFFSET.unsigned_abs() {
+                bail!(
+                    "Invalid shadow offset: {} (expected -{max} to {max})",
+                    value,
+                    max = MAX_OFFSET
+                );
+            }
+            Ok(offset)
+        };
+        let color = match parts.next() {
+            Some(color) => Color::parse(color)?,
+            None => DEFAULT_COLOR,
+        };
+        Ok(Self {
+            dx: offset(dx)?,
+            dy: offset(dy)?,
+            color,
+        })
+    }
+
+    /// Cast the shadow of `text`, styling its glyphs with `style`. The
+    /// result covers the art's bounding box grown by the offset, with the
+    /// shadow drawn first so the art's own cells overwrite it where the
+    /// two overlap.
+    pub fn cast(&self, text: &str, style: &str) -> String {
+        let art: Vec<Vec<Cell>> = text.lines().map(buffer::parse_cells).collect();
+        let width = art.iter().map(Vec::len).max().unwrap_or(0);
+        if width == 0 {
+            return text.to_string();
+        }
+
+        // A shadow up or to the left pushes the art down or right instead
+        let (art_x, art_y) = ((-self.dx).max(0) as usize, (-self.dy).max(0) as usize);
+        let (shadow_x, shadow_y) = (self.dx.max(0) as usize, self.dy.max(0) as usize);
+        let mut grid = vec![
+            vec![Cell::blank(); width + self.dx.unsigned_abs() as usize];
+            art.len() + self.dy.unsigned_abs() as usize
+        ];
+
+        for (y, row) in art.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if !cell.ch.is_whitespace() {
+                    grid[shadow_y + y][shadow_x + x] = Cell {
+                        ch: cell.ch,
+                        style: style.to_string(),
+                    };
+                }
+            }
+        }
+        for (y, row) in art.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                if !cell.ch.is_whitespace() || !cell.style.is_empty() {
+                    grid[art_y + y][art_x + x] = cell;
+                }
+            }
+        }
+
+        grid.iter()
+            .map(|row| buffer::cells_to_string(row))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ansi;
+
+    const STYLE: &str = "\x1b[2m";
+
+    fn shadow(dx: i32, dy: i32) -> Shadow {
+        Shadow {
+            dx,
+            dy,
+            color: DEFAULT_COLOR,
+        }
+    }
+
+    /// Positions of the cells drawn in the shadow style
+    fn shadow_cells(text: &str) -> Vec<(usize, usize)> {
+        text.lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                buffer::parse_cells(line)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.style == STYLE)
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Shadow::parse("1,1").unwrap(), shadow(1, 1));
+        let parsed = Shadow::parse("-2, 1, rgb(10, 20, 30)").unwrap();
+        assert_eq!((parsed.dx, parsed.dy), (-2, 1));
+        assert_eq!(parsed.color, Color::new(10, 20, 30));
+
+        assert!(Shadow::parse("1").is_err());
+        assert!(Shadow::parse("a,1").is_err());
+        assert!(Shadow::parse("1,1,not-a-color").is_err());
+
+        assert_eq!(Shadow::parse("-32,32").unwrap(), shadow(-32, 32));
+        assert!(Shadow::parse("33,1").is_err());
+        assert!(Shadow::parse("1,-2147483648").is_err());
+    }
+
+    #[test]
+    fn test_shadow_only_fills_empty_cells() {
+        let cast = shadow(1, 1).cast("##\n# ", STYLE);
+        assert_eq!(ansi::strip_ansi(&cast), "## \n###\n # ");
+        // The art's own (1, 0) hides the shadow of (0, 0); (1, 1) was empty
+        assert_eq!(shadow_cells(&cast), [(1, 1), (2, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_negative_offset_moves_the_art() {
+        let cast = shadow(-1, 0).cast("ab", STYLE);
+        assert_eq!(ansi::strip_ansi(&cast), "aab");
+        assert_eq!(shadow_cells(&cast), [(0, 0)]);
+        assert_eq!(shadow(0, 0).cast("  ", STYLE), "  ");
+    }
+
+    #[test]
+    fn test_art_colors_win_over_the_shadow() {
+        let red = "\x1b[38;2;255;0;0m";
+        let text = format!("{red}X\x1b[39m{red}Y\x1b[39m");
+        let cast = shadow(1, 0).cast(&text, STYLE);
+        let cells = buffer::parse_cells(&cast);
+        assert_eq!(cells.len(), 3);
+        assert!(cells[0].style.contains(red) && cells[1].style.contains(red));
+        assert_eq!((cells[2].ch, cells[2].style.as_str()), ('Y', STYLE));
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tokio::sync::Notify;
+
+/// Flags for the whole process, once `install` has run
+static SIGNALS: OnceLock<SignalFlags> = OnceLock::new();
+
+/// What a caught signal asks the render loop to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGTERM or SIGINT: tear down like `q` does
+    Terminate,
+    /// SIGWINCH: the terminal changed size
+    Resize,
+}
+
+/// Signals seen so far, as flags the render loop checks each frame
+#[derive(Debug, Default)]
+pub struct SignalFlags {
+    exit: AtomicBool,
+    resized: AtomicBool,
+    exit_notify: Notify,
+}
+
+impl SignalFlags {
+    pub fn handle(&self, signal: Signal) {
+        match signal {
+            Signal::Terminate => {
+                self.exit.store(true, Ordering::SeqCst);
+                self.exit_notify.notify_one();
+            }
+            Signal::Resize => self.resized.store(true, Ordering::SeqCst),
+        }
+    }
+
+    pub fn exit_requested(&self) -> bool {
+        self.exit.load(Ordering::SeqCst)
+    }
+
+    /// Whether the terminal was resized since the last call
+    pub fn take_resized(&self) -> bool {
+        self.resized.swap(false, Ordering::SeqCst)
+    }
+
+    /// Resolves once a terminating signal has arrived
+    pub async fn exited(&self) {
+        while !self.exit_requested() {
+            self.exit_notify.notified().await;
+        }
+    }
+
+    /// Set the flags from SIGTERM, SIGINT and SIGWINCH for as long as the
+    /// runtime lives
+    #[cfg(unix)]
+    pub fn listen(&'static self) -> anyhow::Result<()> {
+        use tokio::signal::unix::SignalKind;
+
+        self.listen_to(SignalKind::terminate(), Signal::Terminate)?;
+        self.listen_to(SignalKind::interrupt(), Signal::Terminate)?;
+        self.listen_to(SignalKind::window_change(), Signal::Resize)
+    }
+
+    /// Handle every `kind` signal as `caught`
+    #[cfg(unix)]
+    fn listen_to(
+        &'static self,
+        kind: tokio::signal::unix::SignalKind,
+        caught: Signal,
+    ) -> anyhow::Result<()> {
+        use anyhow::Context;
+
+        let mut stream =
+            tokio::signal::unix::signal(kind).context("Failed to listen for signals")?;
+        tokio::spawn(async move {
+            while stream.recv().await.is_some() {
+                self.handle(caught);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Start turning signals into the process-wide flags
+#[cfg(unix)]
+pub fn install() -> anyhow::Result<&'static SignalFlags> {
+    if let Some(flags) = SIGNALS.get() {
+        return Ok(flags);
+    }
+    let flags = SIGNALS.get_or_init(SignalFlags::default);
+    flags.listen()?;
+    Ok(flags)
+}
+
+/// The process-wide flags, if signals are being caught
+pub fn installed() -> Option<&'static SignalFlags> {
+    SIGNALS.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_signals_set_flags() {
+        let flags = SignalFlags::default();
+        assert!(!flags.exit_requested());
+        assert!(!flags.take_resized());
+
+        flags.handle(Signal::Resize);
+        assert!(!flags.exit_requested());
+        assert!(flags.take_resized());
+        assert!(!flags.take_resized());
+
+        flags.handle(Signal::Terminate);
+        assert!(flags.exit_requested());
+    }
+
+    #[tokio::test]
+    async fn test_exited_wakes_on_terminate() {
+        let flags: &'static SignalFlags = Box::leak(Box::default());
+        let waiter = tokio::spawn(flags.exited());
+        tokio::task::yield_now().await;
+        flags.handle(Signal::Terminate);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sigwinch_sets_resize_flag() {
+        // Only SIGWINCH: catching SIGINT or SIGTERM here would swallow
+        // them for every other test in the process
+        let flags: &'static SignalFlags = Box::leak(Box::default());
+        flags
+            .listen_to(
+                tokio::signal::unix::SignalKind::window_change(),
+                Signal::Resize,
+            )
+            .unwrap();
+
+        let status = std::process::Command::new("kill")
+            .args(["-WINCH", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        for _ in 0..100 {
+            if flags.take_resized() {
+                assert!(!flags.exit_requested());
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("SIGWINCH never reached the flags");
+    }
+}
@@ -0,0 +1,89 @@
+/// The candidate closest to `input` by edit distance, if it's close enough
+/// to plausibly be a typo
+pub fn closest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    close_matches(input, candidates, 1).into_iter().next()
+}
+
+/// Up to `max` candidates close enough to `input` to be a typo, closest first
+pub fn close_matches<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    max: usize,
+) -> Vec<&'a str> {
+    let limit = (input.chars().count() / 3).max(2);
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(input, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    // Stable, so equally close candidates keep their order
+    matches.sort_by_key(|(distance, _)| *distance);
+    matches
+        .into_iter()
+        .take(max)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// `" Did you mean 'x'?"` for the closest candidate, or an empty string
+pub fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+    closest(input, candidates)
+        .map(|candidate| format!(". Did you mean '{}'?", candidate))
+        .unwrap_or_default()
+}
+
+/// Number of single-character insertions, deletions and substitutions
+/// needed to turn `a` into `b`
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = ["fade-in", "fade-out", "ease-out", "ease-in-out"];
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_near_misses() {
+        assert_eq!(closest("fadein", NAMES), Some("fade-in"));
+        assert_eq!(closest("ease-ou", NAMES), Some("ease-out"));
+        assert_eq!(closest("fade-ot", NAMES), Some("fade-out"));
+        assert_eq!(closest("ease-inout", NAMES), Some("ease-in-out"));
+    }
+
+    #[test]
+    fn test_close_matches_closest_first() {
+        assert_eq!(
+            close_matches("fade-ou", NAMES, 3),
+            vec!["fade-out", "fade-in"]
+        );
+        assert_eq!(close_matches("fade-ou", NAMES, 1), vec!["fade-out"]);
+    }
+
+    #[test]
+    fn test_no_close_match() {
+        assert_eq!(closest("sparkle", NAMES), None);
+        assert_eq!(did_you_mean("sparkle", NAMES), "");
+    }
+}
@@ -1,51 +1,207 @@
 use anyhow::Result;
 use crossterm::{
-    cursor, execute,
+    cursor, execute, queue,
     terminal::{self, ClearType},
 };
-use std::io::{stdout, Write};
+use std::cell::RefCell;
+use std::io::{stderr, stdout, BufWriter, IsTerminal, Write};
+use std::rc::Rc;
+use std::sync::Once;
 
-use super::ansi;
+use super::{
+    ansi,
+    buffer::FrameBuffer,
+    layout::{Alignment, VerticalPlacement},
+};
+
+/// Guards `install_panic_hook` so repeated setups don't stack hooks
+static PANIC_HOOK: Once = Once::new();
 
 pub struct TerminalManager {
+    out: Box<dyn Write>,
+    /// Whether the size follows the real terminal or stays fixed
+    track_size: bool,
     width: u16,
     height: u16,
     original_state: bool,
+    raw_mode: bool,
+    diff_rendering: bool,
+    back_buffer: Option<FrameBuffer>,
+    inline: bool,
+    inline_rows: u16,
+    plain: bool,
+    /// Diagnostics held back until cleanup, see `log_writer`
+    held_log: Rc<RefCell<Vec<u8>>>,
 }
 
 impl TerminalManager {
     pub fn new() -> Result<Self> {
         let (width, height) = terminal::size()?;
-        Ok(Self {
+        let mut terminal = Self::with_output(Box::new(stdout()), width, height);
+        terminal.track_size = true;
+        Ok(terminal)
+    }
+
+    /// Write everything to `out` instead of stdout, as a fixed-size screen
+    pub fn with_output(out: Box<dyn Write>, width: u16, height: u16) -> Self {
+        Self {
+            out,
+            track_size: false,
             width,
             height,
             original_state: false,
-        })
+            raw_mode: false,
+            diff_rendering: true,
+            back_buffer: None,
+            inline: false,
+            inline_rows: 0,
+            plain: false,
+            held_log: Rc::default(),
+        }
+    }
+
+    /// Where diagnostics such as `--verbose` go while this terminal is in
+    /// use: stderr, unless stderr is the screen being drawn on. They are
+    /// then kept in memory and printed by `cleanup`, so they neither print
+    /// over the frames nor vanish with the alternate screen.
+    pub fn log_writer(&self) -> Box<dyn Write> {
+        if self.track_size && !self.plain && stderr().is_terminal() {
+            Box::new(HeldLog(Rc::clone(&self.held_log)))
+        } else {
+            Box::new(BufWriter::new(stderr()))
+        }
+    }
+
+    /// Render in the normal screen buffer below the cursor, leaving the
+    /// scrollback alone: no alternate screen and no raw mode
+    pub fn new_inline() -> Result<Self> {
+        let mut terminal = Self::new()?;
+        terminal.inline = true;
+        Ok(terminal)
+    }
+
+    pub fn is_inline(&self) -> bool {
+        self.inline
+    }
+
+    /// Leave the terminal alone for debugging: no raw mode and no alternate
+    /// screen, every frame printed below the last so all of them stay in
+    /// the scrollback. Only Ctrl-C stops playback early.
+    pub fn new_plain() -> Result<Self> {
+        let mut terminal = Self::new()?;
+        terminal.plain = true;
+        Ok(terminal)
+    }
+
+    pub fn is_plain(&self) -> bool {
+        self.plain
+    }
+
+    /// Enable or disable double-buffered diff rendering
+    pub fn with_diff_rendering(mut self, enabled: bool) -> Self {
+        self.diff_rendering = enabled;
+        self
+    }
+
+    pub fn diff_rendering(&self) -> bool {
+        self.diff_rendering
+    }
+
+    /// Draw a frame, emitting only the cells that changed since the last one
+    pub fn present(&mut self, frame: FrameBuffer) -> Result<()> {
+        frame.diff(self.back_buffer.as_ref(), &mut self.out)?;
+        self.out.flush()?;
+        self.back_buffer = Some(frame);
+        Ok(())
+    }
+
+    /// Draw a frame inline: move back up over the rows drawn last time and
+    /// overwrite them, reserving more rows below the cursor when it grows
+    pub fn present_inline(&mut self, frame: &FrameBuffer) -> Result<()> {
+        let rows = frame.height().max(self.inline_rows);
+        if self.inline_rows > 0 {
+            queue!(self.out, cursor::MoveToPreviousLine(self.inline_rows))?;
+        }
+
+        let text = frame.to_text();
+        let mut lines = text.lines();
+        for _ in 0..rows {
+            queue!(self.out, terminal::Clear(ClearType::CurrentLine))?;
+            write!(self.out, "{}\r\n", lines.next().unwrap_or_default())?;
+        }
+        self.out.flush()?;
+
+        self.inline_rows = rows;
+        Ok(())
+    }
+
+    /// Print a frame below the previous one, with a blank line between them
+    pub fn present_plain(&mut self, frame: &FrameBuffer) -> Result<()> {
+        for line in frame.to_text().lines() {
+            writeln!(self.out, "{}", line.trim_end())?;
+        }
+        writeln!(self.out)?;
+        self.out.flush()?;
+        Ok(())
     }
 
     pub fn setup(&mut self) -> Result<()> {
-        terminal::enable_raw_mode()?;
-        execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        if self.plain {
+            self.original_state = true;
+            return Ok(());
+        }
+        if self.track_size {
+            install_panic_hook(self.inline);
+        }
+        if !self.inline && self.track_size {
+            // Some consoles refuse raw mode. Keys then only arrive after
+            // Enter, but playback and Ctrl-C still work, so carry on.
+            self.raw_mode = terminal::enable_raw_mode().is_ok();
+        }
+        self.write_setup()?;
         self.original_state = true;
         Ok(())
     }
 
+    fn write_setup(&mut self) -> Result<()> {
+        if self.inline {
+            execute!(self.out, cursor::Hide)?;
+        } else {
+            execute!(self.out, terminal::EnterAlternateScreen, cursor::Hide)?;
+        }
+        Ok(())
+    }
+
     pub fn cleanup(&mut self) -> Result<()> {
-        if self.original_state {
-            execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen)?;
-            terminal::disable_raw_mode()?;
+        if self.original_state && self.plain {
+            self.original_state = false;
+        } else if self.original_state {
+            // Inline, the cursor already sits on the line below the art
+            write_restore(&mut self.out, self.inline)?;
+            if self.raw_mode {
+                terminal::disable_raw_mode()?;
+                self.raw_mode = false;
+            }
             self.original_state = false;
+            self.back_buffer = None;
+            self.inline_rows = 0;
+        }
+        let held = self.held_log.take();
+        if !held.is_empty() {
+            // Losing diagnostics is no reason to fail the cleanup
+            let _ = stderr().write_all(&held);
         }
         Ok(())
     }
 
-    pub fn clear(&self) -> Result<()> {
-        execute!(stdout(), terminal::Clear(ClearType::All))?;
+    pub fn clear(&mut self) -> Result<()> {
+        execute!(self.out, terminal::Clear(ClearType::All))?;
+        self.back_buffer = None;
         Ok(())
     }
 
-    pub fn move_to(&self, x: u16, y: u16) -> Result<()> {
-        execute!(stdout(), cursor::MoveTo(x, y))?;
+    pub fn move_to(&mut self, x: u16, y: u16) -> Result<()> {
+        execute!(self.out, cursor::MoveTo(x, y))?;
         Ok(())
     }
 
@@ -54,44 +210,227 @@ impl TerminalManager {
     }
 
     pub fn refresh_size(&mut self) -> Result<()> {
+        if !self.track_size {
+            return Ok(());
+        }
         let (width, height) = terminal::size()?;
         self.width = width;
         self.height = height;
         Ok(())
     }
 
-    pub fn print_at(&self, x: u16, y: u16, text: &str) -> Result<()> {
+    pub fn print_at(&mut self, x: u16, y: u16, text: &str) -> Result<()> {
         self.move_to(x, y)?;
-        print!("{}", text);
-        stdout().flush()?;
+        write!(self.out, "{}", text)?;
+        self.out.flush()?;
         Ok(())
     }
 
-    pub fn print_centered(&self, text: &str) -> Result<()> {
+    #[allow(dead_code)]
+    pub fn print_centered(&mut self, text: &str) -> Result<()> {
+        self.print_aligned(text, Alignment::Center, VerticalPlacement::default())
+    }
+
+    /// Print text placed vertically by `vertical` and horizontally by
+    /// `alignment`
+    pub fn print_aligned(
+        &mut self,
+        text: &str,
+        alignment: Alignment,
+        vertical: VerticalPlacement,
+    ) -> Result<()> {
         let lines: Vec<&str> = text.lines().collect();
         let max_width = lines
             .iter()
             .map(|l| ansi::visual_width(l))
             .max()
-            .unwrap_or(0) as u16;
+            .unwrap_or(0) as i32;
         let height = lines.len() as u16;
 
-        let start_x = (self.width.saturating_sub(max_width)) / 2;
-        let start_y = (self.height.saturating_sub(height)) / 2;
+        let start_x = alignment.start_x(self.width as i32, max_width).max(0);
+        let start_y = vertical.start_y(self.height as i32, height as i32) as u16;
 
         for (i, line) in lines.iter().enumerate() {
-            let line_width = ansi::visual_width(line) as u16;
-            let x = start_x + (max_width.saturating_sub(line_width)) / 2;
+            let line_width = ansi::visual_width(line) as i32;
+            let x = start_x + alignment.start_x(max_width, line_width).max(0);
             let y = start_y + i as u16;
-            self.print_at(x, y, line)?;
+            // Lines wider than the terminal are cut off rather than wrapped
+            let line = ansi::slice_columns(line, 0, (self.width as i32 - x).max(0) as usize);
+            self.print_at(x as u16, y, &line)?;
         }
 
         Ok(())
     }
 }
 
+/// Writer collecting log output for `TerminalManager::cleanup` to print
+struct HeldLog(Rc<RefCell<Vec<u8>>>);
+
+impl Write for HeldLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Undo `setup` on `out`: show the cursor and, unless inline, leave the
+/// alternate screen
+fn write_restore(out: &mut impl Write, inline: bool) -> Result<()> {
+    if inline {
+        execute!(out, cursor::Show)?;
+    } else {
+        execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    }
+    Ok(())
+}
+
+/// Put the terminal back before a panic message is printed, so a panic
+/// mid-animation doesn't leave raw mode and the alternate screen behind
+/// even when `Drop` never gets to run
+pub fn install_panic_hook(inline: bool) {
+    PANIC_HOOK.call_once(|| restore_on_panic(stdout, inline));
+}
+
+/// Panic hook writing the restore sequence to a writer from `out`, then
+/// running the previous hook
+fn restore_on_panic<W: Write>(out: impl Fn() -> W + Send + Sync + 'static, inline: bool) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_after_panic(&mut out(), inline);
+        previous(info);
+    }));
+}
+
+/// What the panic hook does before the panic message: leave raw mode and
+/// write the restore sequence to `out`, ignoring errors as there is no one
+/// left to report them to
+fn restore_after_panic(out: &mut impl Write, inline: bool) {
+    if terminal::is_raw_mode_enabled().unwrap_or(false) {
+        let _ = terminal::disable_raw_mode();
+    }
+    let _ = write_restore(out, inline);
+}
+
 impl Drop for TerminalManager {
     fn drop(&mut self) {
         let _ = self.cleanup();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+
+    /// Cloneable sink so a test can read back what the terminal wrote
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn take(&self) -> String {
+            String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn terminal(inline: bool) -> (TerminalManager, SharedBuffer) {
+        let buffer = SharedBuffer::default();
+        let mut terminal = TerminalManager::with_output(Box::new(buffer.clone()), 20, 10);
+        terminal.inline = inline;
+        (terminal, buffer)
+    }
+
+    #[test]
+    fn test_inline_setup_keeps_main_screen() {
+        let (mut inline, buffer) = terminal(true);
+        inline.setup().unwrap();
+        assert!(!buffer.take().contains(ENTER_ALTERNATE_SCREEN));
+
+        let (mut fullscreen, buffer) = terminal(false);
+        fullscreen.setup().unwrap();
+        assert!(buffer.take().contains(ENTER_ALTERNATE_SCREEN));
+    }
+
+    #[test]
+    fn test_inline_frames_overwrite_reserved_rows() {
+        let (mut terminal, buffer) = terminal(true);
+        let mut frame = FrameBuffer::new(20, 3);
+        frame.draw_text(0, 0, "one\ntwo\nthree");
+
+        terminal.present_inline(&frame).unwrap();
+        let first = buffer.take();
+        assert_eq!(first.matches("\r\n").count(), 3);
+        assert!(!first.contains("\x1b[3F"));
+
+        terminal.present_inline(&FrameBuffer::new(20, 2)).unwrap();
+        let second = buffer.take();
+        assert!(second.starts_with("\x1b[3F"));
+        assert_eq!(second.matches("\r\n").count(), 3);
+    }
+
+    #[test]
+    fn test_plain_setup_leaves_terminal_alone() {
+        let (mut terminal, buffer) = terminal(false);
+        terminal.plain = true;
+        terminal.setup().unwrap();
+        assert!(!terminal.raw_mode);
+        assert_eq!(buffer.take(), "");
+
+        let mut frame = FrameBuffer::new(20, 2);
+        frame.draw_text(0, 0, "one\ntwo");
+        terminal.present_plain(&frame).unwrap();
+        terminal.present_plain(&frame).unwrap();
+        terminal.cleanup().unwrap();
+        assert_eq!(buffer.take(), "one\ntwo\n\none\ntwo\n\n");
+    }
+
+    #[test]
+    fn test_panic_restores_terminal() {
+        // The hook itself is process-wide, so only what it runs is tested
+        let mut restored = Vec::new();
+        restore_after_panic(&mut restored, false);
+        let restored = String::from_utf8(restored).unwrap();
+        assert!(restored.contains("\x1b[?25h"));
+        assert!(restored.contains("\x1b[?1049l"));
+
+        let mut restored = Vec::new();
+        restore_after_panic(&mut restored, true);
+        let restored = String::from_utf8(restored).unwrap();
+        assert!(restored.contains("\x1b[?25h"));
+        assert!(!restored.contains("\x1b[?1049l"));
+    }
+
+    #[test]
+    fn test_wide_lines_are_clipped() {
+        let (mut terminal, buffer) = terminal(false);
+        let line = "\x1b[31m".to_string() + &"x".repeat(100) + "\x1b[0m";
+        terminal
+            .print_aligned(&line, Alignment::Center, VerticalPlacement::default())
+            .unwrap();
+
+        let written = buffer.take();
+        assert_eq!(ansi::visual_width(&written), 20);
+        assert!(written.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn test_print_at_writes_to_sink() {
+        let (mut terminal, buffer) = terminal(false);
+        terminal.print_at(3, 1, "hi").unwrap();
+        assert_eq!(buffer.take(), "\x1b[2;4Hhi");
+    }
+}
@@ -1,11 +1,16 @@
 use anyhow::Result;
 use piglet::{
     animation::easing::get_easing_function,
-    animation::effects::get_effect,
+    animation::effects::{get_effect, EffectContext},
+    animation::AnimationEngine,
     color::{palette::ColorPalette, ColorEngine},
     figlet::FigletWrapper,
     parser::{color::Color, duration::parse_duration, gradient::Gradient},
+    utils::{ascii::AsciiArt, terminal::TerminalManager},
 };
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[test]
 fn test_figlet_wrapper() -> Result<()> {
@@ -17,11 +22,12 @@ fn test_figlet_wrapper() -> Result<()> {
 
 #[test]
 fn test_duration_parser() -> Result<()> {
-    assert_eq!(parse_duration("1000ms")?, 1000);
-    assert_eq!(parse_duration("1s")?, 1000);
-    assert_eq!(parse_duration("1m")?, 60000);
-    assert_eq!(parse_duration("1h")?, 3600000);
-    assert_eq!(parse_duration("0.5s")?, 500);
+    assert_eq!(parse_duration("1000ms")?, Duration::from_millis(1000));
+    assert_eq!(parse_duration("1s")?, Duration::from_secs(1));
+    assert_eq!(parse_duration("1m")?, Duration::from_secs(60));
+    assert_eq!(parse_duration("1h")?, Duration::from_secs(3600));
+    assert_eq!(parse_duration("0.5s")?, Duration::from_millis(500));
+    assert_eq!(parse_duration("1500us")?, Duration::from_micros(1500));
     Ok(())
 }
 
@@ -81,6 +87,21 @@ fn test_color_palette() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_color_palette_interpolated() -> Result<()> {
+    let palette = ColorPalette::from_strings(&["red".to_string(), "blue".to_string()])?;
+
+    let color = palette.get_color_interpolated(0.25);
+    assert_eq!(color.r, 191);
+    assert_eq!(color.g, 0);
+    assert_eq!(color.b, 63);
+
+    let end = palette.get_color_interpolated(1.0);
+    assert_eq!((end.r, end.g, end.b), (0, 0, 255));
+
+    Ok(())
+}
+
 #[test]
 fn test_easing_functions() -> Result<()> {
     let linear = get_easing_function("linear")?;
@@ -111,6 +132,306 @@ fn test_effects() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_matrix_rain_effect() -> Result<()> {
+    let art = AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string());
+    let effect = get_effect("matrix-rain")?;
+
+    let start = effect.apply(&art, &EffectContext::new(0.0)).text;
+    assert!(start.chars().all(char::is_whitespace));
+    assert_eq!(start.lines().count(), art.height());
+
+    assert_eq!(
+        effect.apply(&art, &EffectContext::new(1.0)).text,
+        art.render()
+    );
+    assert_eq!(
+        effect.apply(&art, &EffectContext::new(0.5)).text,
+        effect.apply(&art, &EffectContext::new(0.5)).text
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_glitch_effect() -> Result<()> {
+    let art = AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string());
+    let effect = get_effect("glitch")?;
+
+    for progress in [0.0, 0.25, 0.5, 0.9] {
+        let text = effect.apply(&art, &EffectContext::new(progress)).text;
+        let widths: Vec<usize> = text.lines().map(|l| l.chars().count()).collect();
+        let expected: Vec<usize> = art.get_lines().iter().map(|l| l.chars().count()).collect();
+        assert_eq!(widths, expected);
+        assert_eq!(text, effect.apply(&art, &EffectContext::new(progress)).text);
+    }
+
+    assert_eq!(
+        effect.apply(&art, &EffectContext::new(1.0)).text,
+        art.render()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_seed_makes_glitch_reproducible() -> Result<()> {
+    let art = AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string());
+    let effect = get_effect("glitch")?;
+    let run = |seed: u64| -> Vec<String> {
+        (0..20)
+            .map(|frame| {
+                let ctx = EffectContext::new(frame as f64 / 20.0)
+                    .with_frame(frame, 20)
+                    .with_seed(seed);
+                effect.apply(&art, &ctx).text
+            })
+            .collect()
+    };
+
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(7));
+
+    Ok(())
+}
+
+/// Count how often vibrate's horizontal offset swings up to its peak over
+/// an animation of `duration_secs` at 120fps
+fn count_vibrate_peaks(duration_secs: usize, frequency: Option<f64>) -> Result<usize> {
+    let art = AsciiArt::new("Hi".to_string());
+    let effect = get_effect("vibrate")?;
+    let total = duration_secs * 120;
+    let offsets: Vec<i32> = (0..=total)
+        .map(|frame| {
+            let ctx = EffectContext::new(frame as f64 / total as f64)
+                .with_frame(frame, total)
+                .with_fps(120)
+                .with_frequency(frequency);
+            effect.apply(&art, &ctx).offset_x
+        })
+        .collect();
+    let peak = *offsets.iter().max().unwrap();
+    Ok(offsets
+        .windows(2)
+        .filter(|pair| pair[0] < peak && pair[1] == peak)
+        .count())
+}
+
+#[test]
+fn test_oscillation_rate_is_independent_of_duration() -> Result<()> {
+    let one_second = count_vibrate_peaks(1, None)?;
+    assert!(one_second > 0);
+    assert_eq!(count_vibrate_peaks(2, None)?, one_second * 2);
+    assert_eq!(count_vibrate_peaks(4, None)?, one_second * 4);
+
+    // An explicit frequency sets the rate in Hz
+    assert_eq!(count_vibrate_peaks(1, Some(3.0))?, 3);
+    assert_eq!(count_vibrate_peaks(2, Some(3.0))?, 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_line_reveal_effect() -> Result<()> {
+    let art = AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string());
+    let effect = get_effect("line-reveal")?;
+    let shown = |progress: f64| {
+        let text = effect.apply(&art, &EffectContext::new(progress)).text;
+        assert_eq!(text.lines().count(), art.height());
+        text.lines().filter(|l| !l.trim().is_empty()).count()
+    };
+
+    assert_eq!(shown(0.0), 0);
+    assert_eq!(shown(1.0), art.height());
+    let counts: Vec<usize> = (0..=20).map(|i| shown(i as f64 / 20.0)).collect();
+    assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_typewriter_cursor_effect() -> Result<()> {
+    let art = AsciiArt::new("ab\ncd".to_string());
+    let effect = get_effect("typewriter-cursor")?;
+    let frame = |progress: f64, frame: usize| {
+        let ctx = EffectContext::new(progress)
+            .with_frame(frame, 30)
+            .with_fps(30);
+        effect.apply(&art, &ctx).text
+    };
+
+    assert_eq!(frame(0.0, 0), "█ \n  ");
+    assert_eq!(frame(0.5, 0), "ab\n█ ");
+    // Blinked off a third of a second in
+    assert_eq!(frame(0.5, 10), "ab\n  ");
+    assert_eq!(frame(1.0, 0), "ab\ncd");
+    assert!(!frame(1.0, 0).contains('█'));
+
+    Ok(())
+}
+
+#[test]
+fn test_word_reveal_effect() -> Result<()> {
+    // "Hi yo" in a tiny font, the words two blank columns apart
+    let art = AsciiArt::new("|_| .  \\/ _\n| | |  / (_)".to_string());
+    assert_eq!(art.column_groups(2).len(), 2);
+
+    let effect = get_effect("word-reveal")?;
+    let reveal = |progress: f64| effect.apply(&art, &EffectContext::new(progress)).text;
+
+    assert!(reveal(0.0).trim().is_empty());
+    assert_eq!(reveal(0.5), "|_| .      \n| | |       ");
+    assert_eq!(reveal(1.0), art.render());
+
+    Ok(())
+}
+
+#[test]
+fn test_marquee_effect() -> Result<()> {
+    use piglet::animation::effects::marquee_offset;
+
+    // 3 rows of art through 10 rows of terminal, centered at row 3
+    let offsets: Vec<i32> = (0..100)
+        .map(|i| marquee_offset(3, 10, i as f64 / 100.0))
+        .collect();
+    assert!(offsets.windows(2).all(|pair| pair[0] <= pair[1]));
+    // Starts fully above the screen and ends fully below it
+    assert_eq!(offsets[0], -7);
+    assert!(3 + offsets[0] + 3 <= 0);
+    assert!(3 + marquee_offset(3, 10, 0.999) >= 9);
+    // Wraps back around rather than settling
+    assert_eq!(marquee_offset(3, 10, 1.0), offsets[0]);
+    assert_eq!(marquee_offset(3, 10, 1.25), marquee_offset(3, 10, 0.25));
+
+    let art = AsciiArt::new("abc\ndef\nghi".to_string());
+    let ctx = EffectContext::new(0.25).with_term_size(80, 10);
+    let up = get_effect("marquee-up")?.apply(&art, &ctx);
+    let down = get_effect("marquee-down")?.apply(&art, &ctx);
+    assert_eq!(up.offset_y, -down.offset_y);
+    assert_eq!(down.text, art.render());
+
+    // Wider than the terminal, entering from the right edge
+    let wide = AsciiArt::new("x".repeat(120));
+    let left = get_effect("marquee-left")?;
+    let offset_x = |ctx: &EffectContext| left.apply(&wide, ctx).offset_x;
+    let start = EffectContext::new(0.0).with_term_size(80, 24);
+    assert_eq!(offset_x(&start), marquee_offset(120, 80, 0.0).abs());
+    assert!(offset_x(&EffectContext::new(0.5).with_term_size(80, 24)) < offset_x(&start));
+
+    // With a frequency, passes follow the clock instead of the duration
+    let ctx = |frame: usize| {
+        EffectContext::new(0.0)
+            .with_term_size(80, 24)
+            .with_frame(frame, 300)
+            .with_fps(30)
+            .with_frequency(Some(0.5))
+    };
+    assert_eq!(offset_x(&ctx(60)), offset_x(&ctx(0)));
+    assert_eq!(
+        offset_x(&ctx(30)),
+        offset_x(&EffectContext::new(0.5).with_term_size(80, 24))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_composite_effect() -> Result<()> {
+    let art = AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string());
+    let effect = get_effect("slide-in-left+fade-in")?;
+    assert_eq!(effect.name(), "slide-in-left+fade-in");
+
+    let middle = effect.apply(&art, &EffectContext::new(0.5));
+    assert_ne!(middle.offset_x, 0);
+    assert!(middle.opacity < 1.0);
+
+    let end = effect.apply(&art, &EffectContext::new(1.0));
+    assert_eq!((end.offset_x, end.opacity), (0, 1.0));
+    assert_eq!(end.text, art.render());
+
+    assert!(get_effect("slide-in-left+").is_err());
+    assert!(get_effect("slide-in-left+nope").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_scramble_effect() -> Result<()> {
+    let art = AsciiArt::new(" _   _ \n| |_| |\n|  _  |\n|_| |_|".to_string());
+    let effect = get_effect("scramble")?;
+
+    let start = effect.apply(&art, &EffectContext::new(0.0)).text;
+    let start = AsciiArt::new(start);
+    for (x, y, glyph) in art.grapheme_positions() {
+        assert_ne!(start.char_at(x, y), Some(glyph));
+    }
+
+    assert_eq!(
+        effect.apply(&art, &EffectContext::new(1.0)).text,
+        art.render()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_effects_keep_combining_marks_on_their_letters() -> Result<()> {
+    // "é" spelled as 'e' followed by U+0301 COMBINING ACUTE ACCENT
+    let art = AsciiArt::new("e\u{301}x\n x\u{301}".to_string());
+    for name in ["matrix-rain", "scramble", "glitch", "typewriter", "fade-in"] {
+        let effect = get_effect(name)?;
+        for progress in [0.0, 0.5, 1.0] {
+            let text = effect.apply(&art, &EffectContext::new(progress)).text;
+            // Never an accent left on a blank cell or a glyph of its own
+            assert!(!text.contains(" \u{301}"), "{name} at {progress}: {text:?}");
+            let frame = AsciiArt::new(text);
+            assert_eq!(frame.width(), art.width(), "{name} at {progress}");
+        }
+        assert_eq!(
+            effect.apply(&art, &EffectContext::new(1.0)).text,
+            art.render(),
+            "{name}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_stacked_ascii_art() {
+    let hello = AsciiArt::new(" _  _ \n| || |\n|_||_|".to_string());
+    let world = AsciiArt::new("__  __\n\\ \\/ /\n \\__/".to_string());
+
+    let stacked = AsciiArt::stack(&[hello.clone(), world.clone()], 1);
+    assert_eq!(stacked.height(), hello.height() + world.height() + 1);
+    assert_eq!(stacked.get_lines()[hello.height()], "");
+}
+
+#[test]
+fn test_effect_preferred_easing() -> Result<()> {
+    use piglet::animation::effects::{BounceIn, Effect, FadeIn};
+
+    assert_eq!(BounceIn.preferred_easing(), Some("linear"));
+    assert_eq!(FadeIn.preferred_easing(), None);
+
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::from_millis(1000), 30)
+        .with_effect("bounce-in")?
+        .with_easing_or_default(None)?;
+    assert_eq!(engine.easing_name(), "linear");
+
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::from_millis(1000), 30)
+        .with_effect("bounce-in")?
+        .with_easing_or_default(Some("ease-in"))?;
+    assert_eq!(engine.easing_name(), "ease-in");
+
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::from_millis(1000), 30)
+        .with_effect("fade-in")?
+        .with_easing_or_default(None)?;
+    assert_eq!(engine.easing_name(), "ease-in-out");
+
+    Ok(())
+}
+
 #[test]
 fn test_color_engine() -> Result<()> {
     let engine = ColorEngine::new().with_palette(Some(&["red".to_string(), "blue".to_string()]))?;
@@ -164,3 +485,216 @@ fn test_invalid_effect() {
 fn test_invalid_easing() {
     assert!(get_easing_function("not-an-easing").is_err());
 }
+
+#[test]
+fn test_typo_suggestions() {
+    let err = get_effect("fadein").err().unwrap().to_string();
+    assert_eq!(err, "Unknown effect 'fadein'. Did you mean 'fade-in'?");
+
+    let err = get_easing_function("ease-ou").err().unwrap().to_string();
+    assert_eq!(
+        err,
+        "Unknown easing function 'ease-ou'. Did you mean 'ease-out'?"
+    );
+
+    let err = get_effect("not-an-effect").err().unwrap().to_string();
+    assert_eq!(err, "Unknown effect 'not-an-effect'");
+}
+
+#[test]
+fn test_render_static_ansi() -> Result<()> {
+    let color_engine =
+        ColorEngine::new().with_palette(Some(&["red".to_string(), "blue".to_string()]))?;
+    let engine = AnimationEngine::new("Hi\nHi".to_string(), Duration::from_millis(1000), 30)
+        .with_effect("fade-in")?
+        .with_color_engine(color_engine);
+
+    let output = engine.render_static();
+    assert!(output.contains("\x1b[38;2"));
+    assert_eq!(piglet::utils::ansi::strip_ansi(&output), "Hi\nHi");
+
+    Ok(())
+}
+
+#[test]
+fn test_no_color_disables_colors() -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    // Rendering shells out to figlet
+    if FigletWrapper::check_installed().is_err() {
+        return Ok(());
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_piglet"))
+        .args(["Hi", "--output-ansi", "-m", "rainbow", "-p", "red,blue"])
+        .args(["--color-gradient", "linear-gradient(red, blue)"])
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .output()?;
+    assert!(output.status.success());
+
+    let printed = String::from_utf8(output.stdout)?;
+    assert!(!printed.contains('\x1b'));
+    let expected = FigletWrapper::new().render("Hi")?;
+    assert_eq!(printed.trim_end(), expected.trim_end());
+
+    Ok(())
+}
+
+#[test]
+fn test_plot_easing_skips_the_project_config() -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    let dir = std::env::temp_dir().join(format!("piglet-plot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(".pigletrc"), "effect = [not toml")?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_piglet"))
+        .args(["--plot-easing", "linear"])
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .output()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8(output.stdout)?.lines().count(), 15);
+
+    Ok(())
+}
+
+/// Cloneable sink so the test can read back what the terminal wrote
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_render_frame_into_buffer() -> Result<()> {
+    let buffer = SharedBuffer::default();
+    let mut terminal = TerminalManager::with_output(Box::new(buffer.clone()), 20, 5);
+
+    // A zero-length animation draws its final frame once and completes
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::ZERO, 30);
+    let mut events = futures::stream::empty();
+    let exited = engine
+        .renderer()
+        .render_with_events(&mut terminal, &mut events)
+        .await?;
+    assert!(!exited);
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone())?;
+    // Rows are redrawn from their first column; "Hi" sits centered on row 3
+    assert!(output.starts_with("\x1b[1;1H"));
+    assert!(output.contains(&format!("\x1b[3;1H{}Hi{}", " ".repeat(9), " ".repeat(9))));
+    Ok(())
+}
+
+#[test]
+fn test_text_piped_through_stdin() -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    // Rendering shells out to figlet
+    if FigletWrapper::check_installed().is_err() {
+        return Ok(());
+    }
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_piglet"))
+        .args(["--output-ansi", "-p", "red"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(b"Hi\n")?;
+    let output = child.wait_with_output()?;
+    assert!(output.status.success());
+
+    let piped = String::from_utf8(output.stdout)?;
+    let expected = FigletWrapper::new().render("Hi")?;
+    let stripped = piglet::utils::ansi::strip_ansi(&piped);
+    assert_eq!(stripped.trim_end(), expected.trim_end());
+
+    Ok(())
+}
+
+#[test]
+fn test_static_frame_for_non_tty() -> Result<()> {
+    let colors = ColorEngine::new().with_palette(Some(&["red".to_string()]))?;
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::from_secs(1), 30)
+        .with_color_engine(colors);
+
+    let mut colored = Vec::new();
+    engine.write_static(&mut colored, true)?;
+    let colored = String::from_utf8(colored)?;
+    assert!(colored.contains("\x1b[38;2;255;0;0m"));
+
+    let mut plain = Vec::new();
+    engine.write_static(&mut plain, false)?;
+    assert_eq!(String::from_utf8(plain)?, "Hi\n");
+
+    // No alternate screen, cursor hiding or clearing on a pipe
+    for sequence in ["\x1b[?1049h", "\x1b[?25l", "\x1b[2J"] {
+        assert!(!colored.contains(sequence));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_piped_stdout_prints_final_frame() -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    // Rendering shells out to figlet
+    if FigletWrapper::check_installed().is_err() {
+        return Ok(());
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_piglet"))
+        .args(["Hi", "-d", "10ms"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .output()?;
+    assert!(output.status.success());
+
+    let piped = String::from_utf8(output.stdout)?;
+    assert!(!piped.contains('\x1b'));
+    let expected = FigletWrapper::new().render("Hi")?;
+    assert_eq!(piped.trim(), expected.trim());
+
+    Ok(())
+}
+
+#[test]
+fn test_frames_iterator() -> Result<()> {
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::from_millis(500), 10)
+        .with_effect("fade-in")?
+        .with_easing("linear")?;
+
+    let frames: Vec<_> = engine.frames().collect();
+    assert_eq!(frames.len(), 6);
+    assert_eq!(frames[0].index, 0);
+    assert_eq!(frames[0].progress, 0.0);
+    assert_eq!(frames[0].effect.opacity, 0.0);
+    assert_eq!(frames[5].progress, 1.0);
+    assert_eq!(frames[5].effect.opacity, 1.0);
+    assert_eq!(frames[5].text, "Hi");
+    assert!(frames
+        .windows(2)
+        .all(|pair| pair[0].effect.opacity <= pair[1].effect.opacity));
+
+    // A zero-length animation is just its final frame
+    let engine = AnimationEngine::new("Hi".to_string(), Duration::ZERO, 10);
+    let frames: Vec<_> = engine.frames().collect();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].progress, 1.0);
+
+    Ok(())
+}